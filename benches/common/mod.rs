@@ -0,0 +1,59 @@
+use std::{fs, path::Path};
+
+use serde_json::Value;
+
+/// Schemas in `benches/data`, each with a valid & an invalid instance.
+///
+/// `openapi.json` is the official OpenAPI 3.1 schema. The `-trimmed` ones are hand-trimmed
+/// versions of the GeoJSON, CITM catalog & Kubernetes schemas with generated instances, so their
+/// results do not reflect the throughput on the full upstream schemas.
+const SCHEMAS: &[&str] = &[
+    "citm_catalog-trimmed",
+    "geojson-trimmed",
+    "kubernetes-trimmed",
+    "openapi",
+];
+
+pub struct Benchmark {
+    pub name: String,
+    pub schema: Value,
+    pub valid: Value,
+    pub invalid: Value,
+}
+
+fn read(name: &str) -> Value {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("benches/data")
+        .join(name);
+    let content = fs::read_to_string(&path)
+        .unwrap_or_else(|error| panic!("Failed to read {}: {error}", path.display()));
+    serde_json::from_str(&content).expect("Invalid JSON")
+}
+
+pub fn schemas() -> Vec<Benchmark> {
+    SCHEMAS
+        .iter()
+        .map(|name| Benchmark {
+            name: (*name).to_string(),
+            schema: read(&format!("{name}.json")),
+            valid: read(&format!("{name}-valid.json")),
+            invalid: read(&format!("{name}-invalid.json")),
+        })
+        .collect()
+}
+
+/// A small schema per keyword, see `benches/data/keywords.json`.
+pub fn keywords() -> Vec<Benchmark> {
+    let Value::Array(cases) = read("keywords.json") else {
+        panic!("Keyword benchmarks should be an array");
+    };
+    cases
+        .into_iter()
+        .map(|mut case| Benchmark {
+            name: case["name"].as_str().expect("Missing name").to_string(),
+            schema: case["schema"].take(),
+            valid: case["valid"].take(),
+            invalid: case["invalid"].take(),
+        })
+        .collect()
+}
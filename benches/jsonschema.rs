@@ -0,0 +1,148 @@
+//! Compilation & validation with the `serde_json` backend.
+mod common;
+mod tree_walk;
+
+use common::Benchmark;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use jsonschema::{blocking, Validator};
+use serde_json::{json, Map, Value};
+use tree_walk::TreeWalk;
+
+fn bench_compile(c: &mut Criterion, benchmarks: &[Benchmark]) {
+    let mut group = c.benchmark_group("compile");
+    for benchmark in benchmarks {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(&benchmark.name),
+            &benchmark.schema,
+            |b, schema| b.iter(|| blocking::validator_for(schema).expect("Invalid schema")),
+        );
+    }
+    group.finish();
+}
+
+fn bench_validate(
+    c: &mut Criterion,
+    group: &str,
+    benchmarks: &[Benchmark],
+    build: impl Fn(&Value) -> Validator<Value>,
+) {
+    let mut group = c.benchmark_group(group);
+    for benchmark in benchmarks {
+        let validator = build(&benchmark.schema);
+        assert!(validator.is_valid(&benchmark.valid), "{}", benchmark.name);
+        assert!(
+            !validator.is_valid(&benchmark.invalid),
+            "{}",
+            benchmark.name
+        );
+        for (kind, instance) in [("valid", &benchmark.valid), ("invalid", &benchmark.invalid)] {
+            group.bench_with_input(
+                BenchmarkId::new(format!("{}/is_valid", benchmark.name), kind),
+                instance,
+                |b, instance| b.iter(|| validator.is_valid(instance)),
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("{}/iter_errors", benchmark.name), kind),
+                instance,
+                |b, instance| b.iter(|| validator.iter_errors(instance).count()),
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("{}/evaluate", benchmark.name), kind),
+                instance,
+                |b, instance| b.iter(|| validator.evaluate(instance).list()),
+            );
+        }
+    }
+    group.finish();
+}
+
+fn schemas(c: &mut Criterion) {
+    let benchmarks = common::schemas();
+    bench_compile(c, &benchmarks);
+    bench_validate(c, "validate", &benchmarks, |schema| {
+        blocking::validator_for(schema).expect("Invalid schema")
+    });
+}
+
+fn keywords(c: &mut Criterion) {
+    // Formats are only annotations by default, but `format` is benchmarked as an assertion
+    bench_validate(c, "keyword", &common::keywords(), |schema| {
+        blocking::ValidatorBuilder::default()
+            .validate_formats(true)
+            .build(schema)
+            .expect("Invalid schema")
+    });
+}
+
+/// The trimmed Kubernetes schema with its definitions copied, to resemble the size of the full bundle.
+///
+/// The root is a `oneOf` over the copies, which only differ in the accepted `apiVersion`, so the
+/// last one is evaluated in depth.
+fn kubernetes_bundle(schema: &Value, copies: usize) -> Value {
+    let mut root = schema.clone();
+    let Some(Value::Object(definitions)) = root
+        .as_object_mut()
+        .and_then(|root| root.remove("definitions"))
+    else {
+        panic!("Kubernetes schema should have definitions");
+    };
+    let draft = root["$schema"].take();
+    let mut bundle = Map::new();
+    let mut variants = Vec::new();
+    for copy in 0..copies {
+        let prefix = format!("{copy}.");
+        let mut list = root.clone();
+        if copy + 1 < copies {
+            list["properties"]["apiVersion"]["enum"] = json!([format!("v1.{copy}")]);
+        }
+        let renamed = serde_json::to_string(&(&definitions, &list))
+            .expect("Serializable schema")
+            .replace("#/definitions/", &format!("#/definitions/{prefix}"));
+        let (copied, list): (Map<String, Value>, Value) =
+            serde_json::from_str(&renamed).expect("Valid schema");
+        for (name, definition) in copied {
+            bundle.insert(format!("{prefix}{name}"), definition);
+        }
+        bundle.insert(format!("{prefix}List"), list);
+        variants.push(json!({"$ref": format!("#/definitions/{prefix}List")}));
+    }
+    json!({"$schema": draft, "definitions": bundle, "oneOf": variants})
+}
+
+/// The compiled graph against walking the schema document.
+fn graph(c: &mut Criterion) {
+    let kubernetes = common::schemas()
+        .into_iter()
+        .find(|benchmark| benchmark.name == "kubernetes-trimmed")
+        .expect("Kubernetes schema");
+    let bundle = kubernetes_bundle(&kubernetes.schema, 40);
+    let mut group = c.benchmark_group("graph");
+    for (name, schema) in [
+        ("kubernetes-trimmed", &kubernetes.schema),
+        ("kubernetes-trimmed-bundle", &bundle),
+    ] {
+        let validator = blocking::validator_for(schema).expect("Invalid schema");
+        let tree_walk = TreeWalk::new(schema);
+        for (kind, instance) in [
+            ("valid", &kubernetes.valid),
+            ("invalid", &kubernetes.invalid),
+        ] {
+            assert_eq!(validator.is_valid(instance), kind == "valid", "{name}");
+            assert_eq!(tree_walk.is_valid(instance), kind == "valid", "{name}");
+            group.bench_with_input(
+                BenchmarkId::new(format!("{name}/compiled"), kind),
+                instance,
+                |b, instance| b.iter(|| validator.is_valid(instance)),
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("{name}/tree_walk"), kind),
+                instance,
+                |b, instance| b.iter(|| tree_walk.is_valid(instance)),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, schemas, keywords, graph);
+criterion_main!(benches);
@@ -0,0 +1,79 @@
+//! Compilation & validation of Python objects, converted from the same data via `json.loads`.
+//!
+//! Python objects can not be created as owned `Json` values, so output with annotations and
+//! `propertyNames` are not benchmarked.
+mod common;
+
+use common::Benchmark;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use jsonschema::{blocking, Validator};
+use pyo3::{prelude::*, types::PyModule};
+
+fn to_python<'py>(json: &'py PyModule, value: &serde_json::Value) -> &'py PyAny {
+    let string = serde_json::to_string(value).expect("Failed to serialize");
+    json.call_method1("loads", (string,))
+        .expect("Failed to load JSON")
+}
+
+fn is_supported(benchmark: &Benchmark) -> bool {
+    !benchmark.schema.to_string().contains("\"propertyNames\"")
+}
+
+fn bench_validate(
+    c: &mut Criterion,
+    group: &str,
+    benchmarks: &[Benchmark],
+    build: impl Fn(&PyAny) -> Validator<PyAny>,
+) {
+    Python::with_gil(|py| {
+        let json = py.import("json").expect("Missing json module");
+        let mut group = c.benchmark_group(group);
+        for benchmark in benchmarks
+            .iter()
+            .filter(|benchmark| is_supported(benchmark))
+        {
+            let schema = to_python(json, &benchmark.schema);
+            let valid = to_python(json, &benchmark.valid);
+            let invalid = to_python(json, &benchmark.invalid);
+            group.bench_with_input(
+                BenchmarkId::new(&benchmark.name, "compile"),
+                schema,
+                |b, schema| b.iter(|| build(schema)),
+            );
+            let validator = build(schema);
+            assert!(validator.is_valid(valid), "{}", benchmark.name);
+            assert!(!validator.is_valid(invalid), "{}", benchmark.name);
+            for (kind, instance) in [("valid", valid), ("invalid", invalid)] {
+                group.bench_with_input(
+                    BenchmarkId::new(format!("{}/is_valid", benchmark.name), kind),
+                    instance,
+                    |b, instance| b.iter(|| validator.is_valid(instance)),
+                );
+                group.bench_with_input(
+                    BenchmarkId::new(format!("{}/iter_errors", benchmark.name), kind),
+                    instance,
+                    |b, instance| b.iter(|| validator.iter_errors(instance).count()),
+                );
+            }
+        }
+        group.finish();
+    });
+}
+
+fn schemas(c: &mut Criterion) {
+    bench_validate(c, "pyo3/validate", &common::schemas(), |schema| {
+        blocking::validator_for(schema).expect("Invalid schema")
+    });
+}
+
+fn keywords(c: &mut Criterion) {
+    bench_validate(c, "pyo3/keyword", &common::keywords(), |schema| {
+        blocking::ValidatorBuilder::default()
+            .validate_formats(true)
+            .build(schema)
+            .expect("Invalid schema")
+    });
+}
+
+criterion_group!(benches, schemas, keywords);
+criterion_main!(benches);
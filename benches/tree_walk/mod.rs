@@ -0,0 +1,182 @@
+//! A validator that walks the schema document itself, as a baseline for the compiled graph.
+//!
+//! Only the Draft 4 keywords used by `benches/data/kubernetes-trimmed.json` are supported. Patterns are
+//! compiled upfront and references are resolved when they are reached, as a tree walk has no
+//! other place to store them.
+use std::collections::HashMap;
+
+use regex::Regex;
+use serde_json::{Map, Value};
+
+pub struct TreeWalk<'s> {
+    root: &'s Value,
+    patterns: HashMap<&'s str, Regex>,
+}
+
+impl<'s> TreeWalk<'s> {
+    pub fn new(root: &'s Value) -> TreeWalk<'s> {
+        let mut patterns = HashMap::new();
+        collect_patterns(root, &mut patterns);
+        TreeWalk { root, patterns }
+    }
+
+    pub fn is_valid(&self, instance: &Value) -> bool {
+        self.is_valid_at(self.root, instance)
+    }
+
+    fn is_valid_at(&self, schema: &'s Value, instance: &Value) -> bool {
+        let Value::Object(schema) = schema else {
+            return schema.as_bool() != Some(false);
+        };
+        // `$ref` overrides its siblings in Draft 4
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let target = reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.root.pointer(pointer))
+                .expect("Unresolvable reference");
+            return self.is_valid_at(target, instance);
+        }
+        schema
+            .iter()
+            .all(|(keyword, value)| self.is_valid_keyword(schema, keyword, value, instance))
+    }
+
+    fn is_valid_keyword(
+        &self,
+        schema: &'s Map<String, Value>,
+        keyword: &str,
+        value: &'s Value,
+        instance: &Value,
+    ) -> bool {
+        match keyword {
+            "type" => match value {
+                Value::String(name) => has_type(instance, name),
+                Value::Array(names) => names
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .any(|name| has_type(instance, name)),
+                _ => true,
+            },
+            "enum" => value
+                .as_array()
+                .is_none_or(|options| options.contains(instance)),
+            "required" => match (value.as_array(), instance.as_object()) {
+                (Some(names), Some(object)) => names
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .all(|name| object.contains_key(name)),
+                _ => true,
+            },
+            "properties" => match (value.as_object(), instance.as_object()) {
+                (Some(properties), Some(object)) => properties.iter().all(|(name, subschema)| {
+                    object
+                        .get(name)
+                        .is_none_or(|value| self.is_valid_at(subschema, value))
+                }),
+                _ => true,
+            },
+            "additionalProperties" => {
+                let Some(object) = instance.as_object() else {
+                    return true;
+                };
+                let properties = schema.get("properties").and_then(Value::as_object);
+                let patterns = schema.get("patternProperties").and_then(Value::as_object);
+                object
+                    .iter()
+                    .filter(|(name, _)| {
+                        !properties.is_some_and(|properties| properties.contains_key(*name))
+                            && !patterns.is_some_and(|patterns| {
+                                patterns
+                                    .keys()
+                                    .any(|pattern| self.patterns[pattern.as_str()].is_match(name))
+                            })
+                    })
+                    .all(|(_, item)| self.is_valid_at(value, item))
+            }
+            "items" => {
+                let Some(items) = instance.as_array() else {
+                    return true;
+                };
+                match value {
+                    Value::Array(schemas) => schemas
+                        .iter()
+                        .zip(items)
+                        .all(|(schema, item)| self.is_valid_at(schema, item)),
+                    _ => items.iter().all(|item| self.is_valid_at(value, item)),
+                }
+            }
+            "minItems" => match (value.as_u64(), instance.as_array()) {
+                (Some(limit), Some(items)) => items.len() as u64 >= limit,
+                _ => true,
+            },
+            "maxLength" => match (value.as_u64(), instance.as_str()) {
+                (Some(limit), Some(string)) => string.chars().count() as u64 <= limit,
+                _ => true,
+            },
+            "minimum" => match (value.as_f64(), instance.as_f64()) {
+                (Some(limit), Some(number)) => number >= limit,
+                _ => true,
+            },
+            "maximum" => match (value.as_f64(), instance.as_f64()) {
+                (Some(limit), Some(number)) => number <= limit,
+                _ => true,
+            },
+            "pattern" => match (value.as_str(), instance.as_str()) {
+                (Some(pattern), Some(string)) => self.patterns[pattern].is_match(string),
+                _ => true,
+            },
+            "oneOf" => value.as_array().is_none_or(|schemas| {
+                schemas
+                    .iter()
+                    .filter(|schema| self.is_valid_at(schema, instance))
+                    .take(2)
+                    .count()
+                    == 1
+            }),
+            _ => true,
+        }
+    }
+}
+
+fn has_type(instance: &Value, name: &str) -> bool {
+    match name {
+        "null" => instance.is_null(),
+        "boolean" => instance.is_boolean(),
+        "object" => instance.is_object(),
+        "array" => instance.is_array(),
+        "string" => instance.is_string(),
+        "number" => instance.is_number(),
+        "integer" => {
+            instance.is_i64()
+                || instance.is_u64()
+                || instance
+                    .as_f64()
+                    .is_some_and(|number| number.fract() == 0.0)
+        }
+        _ => false,
+    }
+}
+
+fn collect_patterns<'s>(value: &'s Value, patterns: &mut HashMap<&'s str, Regex>) {
+    match value {
+        Value::Object(object) => {
+            if let Some(Value::String(pattern)) = object.get("pattern") {
+                patterns.insert(pattern, Regex::new(pattern).expect("Invalid pattern"));
+            }
+            if let Some(Value::Object(properties)) = object.get("patternProperties") {
+                for pattern in properties.keys() {
+                    patterns.insert(pattern, Regex::new(pattern).expect("Invalid pattern"));
+                }
+            }
+            for child in object.values() {
+                collect_patterns(child, patterns);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_patterns(item, patterns);
+            }
+        }
+        _ => {}
+    }
+}
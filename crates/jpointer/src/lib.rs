@@ -1,11 +1,16 @@
 //! A lightweight library for working with JSON Pointers (RFC 6901).
 //!
-//! This crate provides a simple and efficient way to represent and build JSON Pointers.
+//! This crate provides a simple and efficient way to represent, build and parse JSON Pointers
+//! and Relative JSON Pointers.
 //!
 //! Note: This crate focuses on the representation and manipulation of JSON Pointers and does not
 //! provide functionality for resolving JSON Pointers against JSON documents.
 use core::{fmt, fmt::Write};
 
+mod parser;
+
+pub use parser::ParseError;
+
 /// Owned JSON Pointer.
 /// TODO: Maybe cache the string representation to avoid doing it during serde serialization?
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
@@ -21,6 +26,19 @@ impl fmt::Display for JsonPointer {
     }
 }
 
+impl JsonPointer {
+    /// The last segment of the pointer, `None` for the root.
+    #[inline]
+    pub fn last(&self) -> Option<&Segment> {
+        self.0.last()
+    }
+    /// Segments of the pointer, from the root.
+    #[inline]
+    pub fn segments(&self) -> &[Segment] {
+        &self.0
+    }
+}
+
 #[cfg(feature = "serde")]
 impl serde::Serialize for JsonPointer {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -38,6 +56,51 @@ impl From<JsonPointerNode<'_>> for JsonPointer {
     }
 }
 
+impl From<&JsonPointerNode<'_>> for JsonPointer {
+    #[inline]
+    fn from(node: &JsonPointerNode<'_>) -> Self {
+        JsonPointer(node.to_vec())
+    }
+}
+
+/// Relative JSON Pointer as defined by `draft-handrews-relative-json-pointer-01`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RelativeJsonPointer {
+    prefix: u64,
+    target: RelativeJsonPointerTarget,
+}
+
+/// What a Relative JSON Pointer refers to after moving up by its prefix.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RelativeJsonPointerTarget {
+    /// A value located by a JSON Pointer.
+    Pointer(JsonPointer),
+    /// The key or index of the value, denoted by `#`.
+    Key,
+}
+
+impl RelativeJsonPointer {
+    /// Number of levels to move up from the current location.
+    #[inline]
+    pub fn prefix(&self) -> u64 {
+        self.prefix
+    }
+    #[inline]
+    pub fn target(&self) -> &RelativeJsonPointerTarget {
+        &self.target
+    }
+}
+
+impl fmt::Display for RelativeJsonPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.prefix)?;
+        match &self.target {
+            RelativeJsonPointerTarget::Pointer(pointer) => pointer.fmt(f),
+            RelativeJsonPointerTarget::Key => f.write_char('#'),
+        }
+    }
+}
+
 /// A segment within a JSON pointer.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Segment {
@@ -75,6 +138,13 @@ impl From<String> for Segment {
     }
 }
 
+impl From<&str> for Segment {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Segment::Key(value.into())
+    }
+}
+
 impl From<usize> for Segment {
     #[inline]
     fn from(value: usize) -> Self {
@@ -96,6 +166,13 @@ pub struct JsonPointerNode<'a> {
     pub(crate) parent: Option<&'a JsonPointerNode<'a>>,
 }
 
+impl Default for JsonPointerNode<'_> {
+    #[inline]
+    fn default() -> Self {
+        JsonPointerNode::new()
+    }
+}
+
 impl<'a> JsonPointerNode<'a> {
     #[inline]
     pub const fn new() -> Self {
@@ -145,6 +222,23 @@ mod tests {
         assert_eq!(pointer.to_string(), "/foo/42/bar");
     }
 
+    #[test]
+    fn test_json_pointer_last() {
+        let pointer = JsonPointer(vec![Segment::Key("foo".into()), Segment::Index(42)]);
+        assert_eq!(pointer.last(), Some(&Segment::Index(42)));
+        assert_eq!(JsonPointer::default().last(), None);
+    }
+
+    #[test]
+    fn test_json_pointer_segments() {
+        let pointer = JsonPointer(vec![Segment::Key("foo".into()), Segment::Index(42)]);
+        assert_eq!(
+            pointer.segments(),
+            [Segment::Key("foo".into()), Segment::Index(42)]
+        );
+        assert!(JsonPointer::default().segments().is_empty());
+    }
+
     #[test]
     fn test_segment_display() {
         let key_segment = Segment::Key("foo/bar~baz".into());
@@ -160,6 +254,12 @@ mod tests {
         assert_eq!(segment, Segment::Key("foo".into()));
     }
 
+    #[test]
+    fn test_segment_from_str() {
+        let segment = Segment::from("foo");
+        assert_eq!(segment, Segment::Key("foo".into()));
+    }
+
     #[test]
     fn test_segment_from_usize() {
         let segment = Segment::from(42_usize);
@@ -0,0 +1,179 @@
+//! Parsing of JSON Pointers (RFC 6901) and Relative JSON Pointers.
+use core::{fmt, str::FromStr};
+
+use crate::{JsonPointer, RelativeJsonPointer, RelativeJsonPointerTarget, Segment};
+
+/// An error that occurred while parsing a JSON Pointer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    position: usize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum ParseErrorKind {
+    MissingLeadingSlash,
+    InvalidEscape,
+    InvalidPrefix,
+    TrailingCharacters,
+}
+
+impl ParseError {
+    fn new(kind: ParseErrorKind, position: usize) -> ParseError {
+        ParseError { kind, position }
+    }
+    /// Byte offset of the error within the input.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self.kind {
+            ParseErrorKind::MissingLeadingSlash => "JSON Pointer must start with '/'",
+            ParseErrorKind::InvalidEscape => "'~' must be followed by '0' or '1'",
+            ParseErrorKind::InvalidPrefix => {
+                "Expected a non-negative integer without leading zeros"
+            }
+            ParseErrorKind::TrailingCharacters => "Expected a JSON Pointer or '#'",
+        };
+        write!(f, "{message} at position {}", self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse a JSON Pointer, `offset` is added to error positions.
+fn parse_pointer(value: &str, offset: usize) -> Result<JsonPointer, ParseError> {
+    if value.is_empty() {
+        return Ok(JsonPointer::default());
+    }
+    let Some(rest) = value.strip_prefix('/') else {
+        return Err(ParseError::new(ParseErrorKind::MissingLeadingSlash, offset));
+    };
+    let mut segments = Vec::new();
+    let mut start = offset + 1;
+    for token in rest.split('/') {
+        let mut key = String::with_capacity(token.len());
+        let mut chars = token.char_indices();
+        while let Some((idx, ch)) = chars.next() {
+            if ch == '~' {
+                match chars.next() {
+                    Some((_, '0')) => key.push('~'),
+                    Some((_, '1')) => key.push('/'),
+                    _ => return Err(ParseError::new(ParseErrorKind::InvalidEscape, start + idx)),
+                }
+            } else {
+                key.push(ch);
+            }
+        }
+        segments.push(Segment::from(key));
+        start += token.len() + 1;
+    }
+    Ok(JsonPointer(segments))
+}
+
+impl FromStr for JsonPointer {
+    type Err = ParseError;
+
+    /// Parse a JSON Pointer, all reference tokens become [`Segment::Key`].
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        parse_pointer(value, 0)
+    }
+}
+
+impl FromStr for RelativeJsonPointer {
+    type Err = ParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let digits = value.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 || (digits > 1 && value.starts_with('0')) {
+            return Err(ParseError::new(ParseErrorKind::InvalidPrefix, 0));
+        }
+        let prefix = value[..digits]
+            .parse()
+            .map_err(|_| ParseError::new(ParseErrorKind::InvalidPrefix, 0))?;
+        let target = match &value[digits..] {
+            "#" => RelativeJsonPointerTarget::Key,
+            rest if rest.starts_with('#') => {
+                return Err(ParseError::new(
+                    ParseErrorKind::TrailingCharacters,
+                    digits + 1,
+                ))
+            }
+            rest => RelativeJsonPointerTarget::Pointer(parse_pointer(rest, digits)?),
+        };
+        Ok(RelativeJsonPointer { prefix, target })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pointer() {
+        let pointer: JsonPointer = "/foo/0/a~1b/m~0n/".parse().expect("Invalid pointer");
+        assert_eq!(
+            pointer,
+            JsonPointer(vec![
+                Segment::Key("foo".into()),
+                Segment::Key("0".into()),
+                Segment::Key("a/b".into()),
+                Segment::Key("m~n".into()),
+                Segment::Key("".into()),
+            ])
+        );
+        assert_eq!(pointer.to_string(), "/foo/0/a~1b/m~0n/");
+    }
+
+    #[test]
+    fn test_parse_empty_pointer() {
+        assert_eq!("".parse(), Ok(JsonPointer::default()));
+    }
+
+    #[test]
+    fn test_parse_pointer_errors() {
+        let error = "foo".parse::<JsonPointer>().expect_err("Should fail");
+        assert_eq!(error.position(), 0);
+        let error = "/foo/b~2ar"
+            .parse::<JsonPointer>()
+            .expect_err("Should fail");
+        assert_eq!(error.position(), 6);
+        assert_eq!(
+            error.to_string(),
+            "'~' must be followed by '0' or '1' at position 6"
+        );
+        assert!("/foo~".parse::<JsonPointer>().is_err());
+    }
+
+    #[test]
+    fn test_parse_relative_pointer() {
+        let pointer: RelativeJsonPointer = "120/foo/bar".parse().expect("Invalid pointer");
+        assert_eq!(pointer.prefix(), 120);
+        assert_eq!(
+            pointer.target(),
+            &RelativeJsonPointerTarget::Pointer("/foo/bar".parse().expect("Invalid pointer"))
+        );
+        let pointer: RelativeJsonPointer = "0#".parse().expect("Invalid pointer");
+        assert_eq!(pointer.target(), &RelativeJsonPointerTarget::Key);
+        assert!("1".parse::<RelativeJsonPointer>().is_ok());
+    }
+
+    #[test]
+    fn test_parse_relative_pointer_errors() {
+        for value in [
+            "", "/foo/bar", "-1/foo", "+1/foo", "01/a", "0##", "0/a~", "1foo",
+        ] {
+            assert!(
+                value.parse::<RelativeJsonPointer>().is_err(),
+                "{value} should be invalid"
+            );
+        }
+        let error = "2/a~3"
+            .parse::<RelativeJsonPointer>()
+            .expect_err("Should fail");
+        assert_eq!(error.position(), 3);
+    }
+}
@@ -150,7 +150,7 @@ impl Json for PyAny {
     fn try_equal(&self, other: &Self) -> Result<bool, JsonError> {
         Ok(self.eq(other)?)
     }
-    fn from_str(s: &str) -> Result<Self, JsonError>
+    fn from_str(_s: &str) -> Result<Self, JsonError>
     where
         Self: Sized,
     {
@@ -24,6 +24,16 @@ impl JsonObject for serde_json::Map<String, serde_json::Value> {
     }
 }
 
+impl JsonObjectMut for serde_json::Map<String, serde_json::Value> {
+    fn get_mut(&mut self, key: &str) -> Option<&mut Self::Value> {
+        <serde_json::Map<String, serde_json::Value>>::get_mut(self, key)
+    }
+
+    fn insert(&mut self, key: &str, value: Self::Value) {
+        <serde_json::Map<String, serde_json::Value>>::insert(self, key.to_owned(), value);
+    }
+}
+
 pub struct ArrayIter<'a>(std::slice::Iter<'a, serde_json::Value>);
 
 impl<'a> Iterator for ArrayIter<'a> {
@@ -47,6 +57,12 @@ impl JsonArray for Vec<serde_json::Value> {
     }
 }
 
+impl JsonArrayMut for Vec<serde_json::Value> {
+    fn get_mut(&mut self, idx: usize) -> Option<&mut Self::Element> {
+        <[serde_json::Value]>::get_mut(self, idx)
+    }
+}
+
 #[cfg(not(feature = "arbitrary_precision"))]
 #[derive(Debug, Copy, Clone)]
 pub enum SerdeInteger {
@@ -122,6 +138,16 @@ impl Json for serde_json::Value {
     }
 }
 
+impl JsonMut for serde_json::Value {
+    fn as_object_mut(&mut self) -> Option<&mut serde_json::Map<String, serde_json::Value>> {
+        self.as_object_mut()
+    }
+
+    fn as_array_mut(&mut self) -> Option<&mut Vec<serde_json::Value>> {
+        self.as_array_mut()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::num::ParseIntError;
@@ -135,7 +161,7 @@ mod tests {
             assert_as_null, assert_as_number_float, assert_as_number_integer, assert_as_object,
             assert_as_string, assert_object_get, assert_object_str_number_iter, CustomInteger,
         },
-        Json,
+        Json, JsonArrayMut, JsonMut, JsonObjectMut,
     };
 
     #[cfg(not(feature = "arbitrary_precision"))]
@@ -286,4 +312,19 @@ mod tests {
         let parsed = Value::from_str(&s).unwrap();
         assert_eq!(value, parsed);
     }
+
+    #[test]
+    fn test_mutation() {
+        let mut value = json!({"a": [1], "b": 2});
+        let object = JsonMut::as_object_mut(&mut value).expect("Should be an object");
+        JsonObjectMut::insert(object, "b", json!(3));
+        JsonObjectMut::insert(object, "c", json!(4));
+        let array = JsonObjectMut::get_mut(object, "a")
+            .and_then(JsonMut::as_array_mut)
+            .expect("Should be an array");
+        *JsonArrayMut::get_mut(array, 0).expect("Non-empty array") = json!(0);
+        assert!(JsonArrayMut::get_mut(array, 1).is_none());
+        assert_eq!(value, json!({"a": [0], "b": 3, "c": 4}));
+        assert!(JsonMut::as_object_mut(&mut json!([])).is_none());
+    }
 }
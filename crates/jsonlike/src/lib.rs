@@ -6,7 +6,10 @@ mod error;
 pub use error::JsonError;
 
 pub mod prelude {
-    pub use crate::{error::JsonError, Json, JsonArray, JsonNumber, JsonObject};
+    pub use crate::{
+        error::JsonError, Json, JsonArray, JsonArrayMut, JsonMut, JsonNumber, JsonObject,
+        JsonObjectMut,
+    };
 }
 
 pub trait Json: fmt::Debug {
@@ -89,6 +92,12 @@ pub trait Json: fmt::Debug {
         Self: Sized;
 }
 
+/// Values that can be modified in place.
+pub trait JsonMut: Json<Object: JsonObjectMut, Array: JsonArrayMut> {
+    fn as_object_mut(&mut self) -> Option<&mut Self::Object>;
+    fn as_array_mut(&mut self) -> Option<&mut Self::Array>;
+}
+
 pub trait JsonObject {
     type Key: AsRef<str> + ?Sized;
     type Value: Json;
@@ -105,6 +114,12 @@ pub trait JsonObject {
     }
 }
 
+pub trait JsonObjectMut: JsonObject {
+    fn get_mut(&mut self, key: &str) -> Option<&mut Self::Value>;
+    /// Insert a value, replacing the previous one under the same key.
+    fn insert(&mut self, key: &str, value: Self::Value);
+}
+
 pub trait JsonArray {
     type Element: Json;
     type Iter<'a>: Iterator<Item = Result<&'a Self::Element, JsonError>>
@@ -123,6 +138,10 @@ pub trait JsonArray {
     fn try_get(&self, idx: usize) -> Result<Option<&Self::Element>, JsonError>;
 }
 
+pub trait JsonArrayMut: JsonArray {
+    fn get_mut(&mut self, idx: usize) -> Option<&mut Self::Element>;
+}
+
 pub trait JsonNumber<'a> {
     type Integer: fmt::Debug + 'a;
 
@@ -3,17 +3,54 @@ use std::path::Path;
 use glob::glob;
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, ItemFn, LitStr};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, Ident, ItemFn, LitStr, Token,
+};
+
+/// The path to the suite, optionally followed by `pattern = "..."` to select test case files
+/// within it and `env = "..."` to name an environment variable that overrides the path.
+struct Args {
+    path: LitStr,
+    pattern: Option<LitStr>,
+    env: Option<LitStr>,
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Args {
+            path: input.parse()?,
+            pattern: None,
+            env: None,
+        };
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            let name: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let value: LitStr = input.parse()?;
+            match name.to_string().as_str() {
+                "pattern" => args.pattern = Some(value),
+                "env" => args.env = Some(value),
+                _ => return Err(syn::Error::new(name.span(), "Unknown argument")),
+            }
+        }
+        Ok(args)
+    }
+}
 
 #[proc_macro_attribute]
 pub fn test(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as Args);
     // Either take the path from environment or use the provided path
-    let suite_path = match option_env!("REFERENCING_SUITE") {
-        Some(v) => v.to_string(),
-        None => parse_macro_input!(args as LitStr).value(),
-    };
+    let suite_path = args
+        .env
+        .and_then(|name| std::env::var(name.value()).ok())
+        .unwrap_or_else(|| args.path.value());
+    let pattern = args
+        .pattern
+        .map_or_else(|| "*/**/*.json".to_string(), |pattern| pattern.value());
     // Find all test case files within the suite
-    let paths = glob(&format!("{suite_path}/*/**/*.json"))
+    let paths = glob(&format!("{suite_path}/{pattern}"))
         .expect("Invalid pattern")
         .collect::<Result<Vec<_>, _>>()
         .expect("Failed to glob");
@@ -22,10 +59,12 @@ pub fn test(args: TokenStream, input: TokenStream) -> TokenStream {
     let input_fn_name = &input_fn.sig.ident;
 
     let test_fns = paths.iter().map(|path| {
-        let spec = normalize_path(path.parent().expect("Missing parent"));
-        let case = normalize_path(path);
+        let case = normalize_path(
+            path.strip_prefix(&suite_path)
+                .expect("Path is within the suite"),
+        );
         let test_fn_name = syn::Ident::new(
-            &format!("{input_fn_name}_{spec}_{case}"),
+            &format!("{input_fn_name}_{case}"),
             input_fn.sig.ident.span(),
         );
         let path = path.display().to_string();
@@ -37,7 +76,9 @@ pub fn test(args: TokenStream, input: TokenStream) -> TokenStream {
         }
     });
 
+    // Without test case files nothing calls the input function
     quote! {
+        #[allow(dead_code)]
         #input_fn
 
         #(#test_fns)*
@@ -45,11 +86,16 @@ pub fn test(args: TokenStream, input: TokenStream) -> TokenStream {
     .into()
 }
 
-/// Adjust the file stem so it is usable as an idntifier.
+/// Join the components of a path relative to the suite, so it is usable as an identifier.
 fn normalize_path(path: &Path) -> String {
-    path.file_stem()
-        .expect("Missing file stem")
-        .to_string_lossy()
-        .replace('-', "_")
-        .to_ascii_lowercase()
+    path.with_extension("")
+        .iter()
+        .map(|component| {
+            component
+                .to_string_lossy()
+                .replace(['-', '.'], "_")
+                .to_ascii_lowercase()
+        })
+        .collect::<Vec<_>>()
+        .join("_")
 }
@@ -78,7 +78,7 @@ impl TestCase {
     }
 }
 
-#[suite::test("crates/referencing/suite/tests")]
+#[suite::test("crates/referencing/suite/tests", env = "REFERENCING_SUITE")]
 fn test_suite(path: PathBuf) {
     let dialect_id = get_dialect_id(&path);
     let group = TestGroup::from_path(&path);
@@ -1,10 +1,14 @@
+use std::sync::Arc;
+
 use crate::{
-    compiler,
+    compiler::{self, CompilationCache},
+    content::{ContentEncodingFactory, ContentMediaTypeFactory},
     drafts::{draft_from_schema, Draft},
-    format::FormatFactory,
+    format::{AsyncFormatFactory, FormatFactory, UnknownFormatPolicy},
     output::Output,
+    regex_engine::RegexEngine,
     validation::builder::ValidatorBuilder as AsyncValidatorBuilder,
-    vocabulary::KeywordFactory,
+    vocabulary::{custom::AsyncKeywordFactory, CustomVocabulary, KeywordFactory},
     BuildResult, ReferenceResolver, ValidationError, ValidationErrorIter, Validator,
 };
 use jsonlike::Json;
@@ -56,6 +60,20 @@ pub fn validator_for<J: Json>(schema: &J) -> BuildResult<Validator<J>> {
     ValidatorBuilder::default().draft(draft).build(schema)
 }
 
+/// Configures how schemas are compiled into [`Validator`]s.
+///
+/// Like [`crate::ValidatorBuilder`], it borrows schemas for its lifetime `'a`, so they have to
+/// outlive the builder:
+///
+/// ```rust
+/// use jsonschema::blocking::ValidatorBuilder;
+/// use serde_json::json;
+///
+/// let schema = json!({"type": "integer"});
+/// let validator = ValidatorBuilder::default().build(&schema)?;
+/// assert!(validator.is_valid(&json!(1)));
+/// # Ok::<(), jsonschema::BuildError>(())
+/// ```
 pub struct ValidatorBuilder<'a, J: Json> {
     inner: AsyncValidatorBuilder<'a, J>,
 }
@@ -69,9 +87,16 @@ impl<'a, J: Json> Default for ValidatorBuilder<'a, J> {
 }
 
 impl<'a, J: Json> ValidatorBuilder<'a, J> {
-    pub fn build(&self, schema: &J) -> BuildResult<Validator<J>> {
+    pub fn build(&self, schema: &'a J) -> BuildResult<Validator<J>> {
         // TODO: Resolve references
-        compiler::compile::<J>(schema, self.inner.draft)
+        compiler::compile::<J>(schema, &self.inner)
+    }
+    /// Build a validator for a subschema, e.g. `#/$defs/Address`, or an anchor.
+    pub fn build_at(&self, schema: &'a J, reference: &str) -> BuildResult<Validator<J>> {
+        compiler::compile_at::<J>(schema, &self.inner, Some(reference))
+    }
+    pub fn from_bytes(&self, bytes: &[u8]) -> BuildResult<Validator<J>> {
+        self.inner.from_bytes(bytes)
     }
     pub fn draft(&mut self, draft: Draft) -> &mut Self {
         self.inner.draft(draft);
@@ -81,20 +106,84 @@ impl<'a, J: Json> ValidatorBuilder<'a, J> {
         self.inner.resolver(resolver);
         self
     }
-    pub fn format<F>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
+    pub fn format<F, M>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
     where
-        F: FormatFactory<'a, J>,
+        F: FormatFactory<'a, J, M>,
+        M: 'a,
     {
         self.inner.format(name, factory);
         self
     }
-    pub fn keyword<F>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
+    pub fn async_format<F, M>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
+    where
+        F: AsyncFormatFactory<'a, J, M>,
+        M: 'a,
+    {
+        self.inner.async_format(name, factory);
+        self
+    }
+    pub fn validate_formats(&mut self, yes: bool) -> &mut Self {
+        self.inner.validate_formats(yes);
+        self
+    }
+    pub fn unknown_formats(&mut self, policy: UnknownFormatPolicy) -> &mut Self {
+        self.inner.unknown_formats(policy);
+        self
+    }
+    pub fn validate_content(&mut self, yes: bool) -> &mut Self {
+        self.inner.validate_content(yes);
+        self
+    }
+    pub fn content_encoding<F>(&mut self, name: impl AsRef<str>, factory: F) -> &mut Self
+    where
+        F: ContentEncodingFactory<'a, J>,
+    {
+        self.inner.content_encoding(name, factory);
+        self
+    }
+    pub fn content_media_type<F>(&mut self, name: impl AsRef<str>, factory: F) -> &mut Self
     where
-        F: KeywordFactory<'a, J>,
+        F: ContentMediaTypeFactory<'a, J>,
+    {
+        self.inner.content_media_type(name, factory);
+        self
+    }
+    pub fn regex_engine(&mut self, engine: impl RegexEngine) -> &mut Self {
+        self.inner.regex_engine(engine);
+        self
+    }
+    pub fn cache(&mut self, cache: Arc<CompilationCache<J>>) -> &mut Self {
+        self.inner.cache(cache);
+        self
+    }
+    pub fn keyword<F, M>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
+    where
+        F: KeywordFactory<'a, J, M>,
+        M: 'a,
     {
         self.inner.keyword(name, factory);
         self
     }
+    pub fn async_keyword<F, M>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
+    where
+        F: AsyncKeywordFactory<'a, J, M>,
+        M: 'a,
+    {
+        self.inner.async_keyword(name, factory);
+        self
+    }
+    pub fn vocabulary(
+        &mut self,
+        uri: impl Into<String>,
+        vocabulary: CustomVocabulary<'a, J>,
+    ) -> &mut Self {
+        self.inner.vocabulary(uri, vocabulary);
+        self
+    }
+    pub fn meta_schema(&mut self, uri: impl Into<String>, meta_schema: J) -> &mut Self {
+        self.inner.meta_schema(uri, meta_schema);
+        self
+    }
 }
 
 #[cfg(all(test, feature = "serde_json"))]
@@ -104,13 +193,13 @@ mod tests {
     #[test]
     fn test_validator_for_blocking() {
         let schema = json!({"type": "integer"});
-        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        let _validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
     }
 
     #[test]
     fn test_options_blocking() {
         let schema = json!({"type": "integer"});
-        let validator = crate::blocking::ValidatorBuilder::default()
+        let _validator = crate::blocking::ValidatorBuilder::default()
             .build(&schema)
             .expect("Invalid schema");
     }
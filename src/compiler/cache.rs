@@ -0,0 +1,263 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use jsonlike::Json;
+
+use crate::{
+    drafts::Draft, format::UnknownFormatPolicy, validation::builder::ValidatorBuilder,
+    vocabulary::SchemaGraph, vocabulary::VocabularySet, RegexEngine,
+};
+
+/// Compiled reference targets shared by validators.
+///
+/// Targets of `$ref` within resources identified by an absolute `$id` are compiled once per
+/// canonical URI, draft & builder options, and every validator referencing them holds the same
+/// compiled schema. Resources with the same URI are assumed to have the same content.
+///
+/// Builders with custom keywords, formats or content handlers do not use the cache.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// # use jsonschema::{blocking::ValidatorBuilder, CompilationCache};
+/// # use serde_json::json;
+/// let common = json!({
+///     "$id": "https://example.com/common.json",
+///     "$defs": {"id": {"type": "integer", "minimum": 1}}
+/// });
+/// let user = json!({
+///     "properties": {"id": {"$ref": "https://example.com/common.json#/$defs/id"}},
+///     "$defs": {"common": common}
+/// });
+/// let order = json!({
+///     "items": {"$ref": "https://example.com/common.json#/$defs/id"},
+///     "$defs": {"common": common}
+/// });
+/// let cache = Arc::new(CompilationCache::new());
+/// let mut builder = ValidatorBuilder::default();
+/// builder.cache(Arc::clone(&cache));
+/// let user = builder.build(&user).expect("Invalid schema");
+/// let order = builder.build(&order).expect("Invalid schema");
+/// assert!(user.is_valid(&json!({"id": 1})));
+/// assert!(!order.is_valid(&json!([0])));
+/// assert_eq!(cache.len(), 1);
+/// ```
+pub struct CompilationCache<J: Json> {
+    entries: Mutex<HashMap<CacheKey, Entry<J>>>,
+}
+
+/// Canonical URI of the target with everything else that affects its compilation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey {
+    uri: String,
+    draft: Draft,
+    vocabularies: VocabularySet,
+    validate_formats: Option<bool>,
+    unknown_formats: UnknownFormatPolicy,
+    validate_content: Option<bool>,
+    /// Address of the regex engine, kept alive by the entry so it is not reused.
+    regex_engine: usize,
+}
+
+impl CacheKey {
+    pub(crate) fn new<J: Json>(
+        uri: String,
+        draft: Draft,
+        vocabularies: VocabularySet,
+        builder: &ValidatorBuilder<'_, J>,
+    ) -> CacheKey {
+        CacheKey {
+            uri,
+            draft,
+            vocabularies,
+            validate_formats: builder.validate_formats,
+            unknown_formats: builder.unknown_formats,
+            validate_content: builder.validate_content,
+            regex_engine: Arc::as_ptr(&builder.regex_engine).cast::<()>() as usize,
+        }
+    }
+    pub(crate) fn uri(&self) -> &str {
+        &self.uri
+    }
+}
+
+struct Entry<J: Json> {
+    graph: Arc<SchemaGraph<J>>,
+    _regex_engine: Arc<dyn RegexEngine>,
+}
+
+impl<J: Json> CompilationCache<J> {
+    pub fn new() -> CompilationCache<J> {
+        CompilationCache {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+    /// Number of compiled targets.
+    pub fn len(&self) -> usize {
+        self.entries.lock().expect("Poisoned lock").len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Drop all compiled targets, validators keep the ones they use.
+    pub fn clear(&self) {
+        self.entries.lock().expect("Poisoned lock").clear();
+    }
+    pub(crate) fn get(&self, key: &CacheKey) -> Option<Arc<SchemaGraph<J>>> {
+        self.entries
+            .lock()
+            .expect("Poisoned lock")
+            .get(key)
+            .map(|entry| Arc::clone(&entry.graph))
+    }
+    /// Store a compiled target, returning the already stored one if another build was faster.
+    pub(crate) fn insert(
+        &self,
+        key: CacheKey,
+        graph: SchemaGraph<J>,
+        regex_engine: &Arc<dyn RegexEngine>,
+    ) -> Arc<SchemaGraph<J>> {
+        let mut entries = self.entries.lock().expect("Poisoned lock");
+        let entry = entries.entry(key).or_insert_with(|| Entry {
+            graph: Arc::new(graph),
+            _regex_engine: Arc::clone(regex_engine),
+        });
+        Arc::clone(&entry.graph)
+    }
+}
+
+impl<J: Json> Default for CompilationCache<J> {
+    fn default() -> Self {
+        CompilationCache::new()
+    }
+}
+
+impl<J: Json> core::fmt::Debug for CompilationCache<J> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CompilationCache")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{blocking::ValidatorBuilder, BoxedKeyword, BuildResult, CompilationCache, Draft};
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn common() -> Value {
+        json!({
+            "$id": "https://example.com/common.json",
+            "$defs": {
+                "id": {"type": "integer", "minimum": 1},
+                "user": {
+                    "properties": {"id": {"$ref": "#/$defs/id"}, "manager": {"$ref": "#/$defs/user"}},
+                    "required": ["id"]
+                },
+                "node": {"type": "object", "properties": {"child": {"$ref": "nodes.json"}}},
+                "name": {"properties": {"name": {"type": "string"}}}
+            }
+        })
+    }
+
+    fn nodes() -> Value {
+        json!({
+            "$id": "https://example.com/nodes.json",
+            "properties": {"parent": {"$ref": "common.json#/$defs/node"}}
+        })
+    }
+
+    fn endpoint(schema: Value) -> Value {
+        let mut schema = schema;
+        schema["$defs"] = json!({"common": common(), "nodes": nodes()});
+        schema
+    }
+
+    #[test_case(&json!({"$ref": "https://example.com/common.json#/$defs/user"}), &json!({"id": 1, "manager": {"id": 2}}), &json!({"id": 1, "manager": {"id": 0}}))]
+    #[test_case(&json!({"items": {"$ref": "https://example.com/common.json#/$defs/id"}}), &json!([1, 2]), &json!([1, "2"]))]
+    #[test_case(&json!({"$ref": "https://example.com/nodes.json"}), &json!({"parent": {"child": {"parent": {}}}}), &json!({"parent": {"child": {"parent": 1}}}))]
+    #[test_case(&json!({"$ref": "https://example.com/common.json#/$defs/name", "unevaluatedProperties": false}), &json!({"name": "a"}), &json!({"name": "a", "age": 1}))]
+    fn test_cached_validation(schema: &Value, valid: &Value, invalid: &Value) {
+        let schema = endpoint(schema.clone());
+        let cache = Arc::new(CompilationCache::new());
+        let cached = ValidatorBuilder::default()
+            .cache(Arc::clone(&cache))
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(!cache.is_empty());
+        let validator = ValidatorBuilder::default()
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(cached.is_valid(valid));
+        assert!(!cached.is_valid(invalid));
+        let errors = |validator: &crate::Validator<Value>| {
+            validator
+                .iter_errors(invalid)
+                .map(|error| (error.to_string(), error.evaluation_path().to_string()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(errors(&cached), errors(&validator));
+        assert_eq!(
+            format!("{:?}", cached.evaluate(valid).list()),
+            format!("{:?}", validator.evaluate(valid).list())
+        );
+        let bytes = cached.to_bytes().expect("Serializable validator");
+        let loaded = crate::Validator::<Value>::from_bytes(&bytes).expect("Valid snapshot");
+        assert!(loaded.is_valid(valid));
+        assert_eq!(errors(&loaded), errors(&validator));
+    }
+
+    #[test]
+    fn test_shared_between_validators() {
+        let user = endpoint(json!({"$ref": "https://example.com/common.json#/$defs/user"}));
+        let order = endpoint(json!({
+            "properties": {
+                "buyer": {"$ref": "https://example.com/common.json#/$defs/user"},
+                "id": {"$ref": "https://example.com/common.json#/%24defs/id"}
+            }
+        }));
+        let cache = Arc::new(CompilationCache::new());
+        let mut builder = ValidatorBuilder::default();
+        builder.cache(Arc::clone(&cache));
+        builder.build(&user).expect("Invalid schema");
+        // `user` & `id`, which `user` refers to by the same canonical URI
+        assert_eq!(cache.len(), 2);
+        let order = builder.build(&order).expect("Invalid schema");
+        assert_eq!(cache.len(), 2);
+        assert!(order.is_valid(&json!({"buyer": {"id": 1}, "id": 2})));
+        assert!(!order.is_valid(&json!({"buyer": {"id": 1}, "id": 0})));
+        // Other options compile the same targets anew
+        builder.validate_formats(true);
+        builder.build(&user).expect("Invalid schema");
+        assert_eq!(cache.len(), 4);
+        cache.clear();
+        assert!(cache.is_empty());
+        assert!(order.is_valid(&json!({"buyer": {"id": 1}, "id": 2})));
+    }
+
+    #[test]
+    fn test_not_shared() {
+        // Anonymous documents are specific to their root schema
+        let local = json!({"$ref": "#/$defs/a", "$defs": {"a": {"type": "integer"}}});
+        let shared = endpoint(json!({"$ref": "https://example.com/common.json#/$defs/id"}));
+        let cache = Arc::new(CompilationCache::new());
+        ValidatorBuilder::default()
+            .cache(Arc::clone(&cache))
+            .build(&local)
+            .expect("Invalid schema");
+        assert!(cache.is_empty());
+        ValidatorBuilder::default()
+            .cache(Arc::clone(&cache))
+            .draft(Draft::Draft202012)
+            .keyword("even", |_: &Value| -> BuildResult<BoxedKeyword<Value>> {
+                unreachable!("Not used by the schema")
+            })
+            .build(&shared)
+            .expect("Invalid schema");
+        assert!(cache.is_empty());
+    }
+}
@@ -1,17 +1,527 @@
-use crate::{drafts::Draft, graph, BuildError, Validator};
-use jsonlike::{Json, JsonObject};
+mod cache;
+mod resources;
 
-pub(crate) fn compile<J: Json>(schema: &J, draft: Draft) -> Result<Validator<J>, BuildError> {
-    let mut graph = graph::Graph::new();
-    if let Some(obj) = schema.as_object() {
-        for (key, value) in obj.iter() {
-            if let Some(keyword) = draft.get_keyword(key.unwrap().as_ref(), value) {
-                graph.push_node(keyword);
-            }
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+    sync::Arc,
+};
+
+use jpointer::{JsonPointer, JsonPointerNode};
+use jsonlike::{Json, JsonArray, JsonObject};
+use url::Url;
+
+use crate::{
+    drafts::{self, Draft},
+    graph::{Graph, SchemaId},
+    validation::{builder::ValidatorBuilder, deferred::Deferred},
+    vocabulary::{
+        core::{Ref, Shared},
+        custom::{CompileContext, Compiler, ErasedKeywordFactory},
+        AsyncCustomKeyword, CustomKeyword, KeywordValue, SchemaGraph, Subschema, VocabularySet,
+    },
+    BuildError, BuildResult, Validator,
+};
+use cache::CacheKey;
+pub use cache::CompilationCache;
+use resources::{Resources, NON_SCHEMA_KEYWORDS};
+
+pub(crate) const DEFAULT_BASE_URI: &str = "json-schema:///";
+
+/// State shared by all keywords during compilation of a single validator.
+pub(crate) struct Context<'a, 'b, J: Json> {
+    pub(crate) draft: Draft,
+    pub(crate) vocabularies: VocabularySet,
+    pub(crate) builder: &'b ValidatorBuilder<'a, J>,
+    /// Keywords registered via the builder or custom vocabularies enabled by the meta-schema.
+    custom_keywords: HashMap<&'b str, &'b Arc<dyn ErasedKeywordFactory<'a, J>>>,
+    graph: Graph<KeywordValue<J>>,
+    resources: Resources<'a, J>,
+    base_uri: Vec<Url>,
+    /// Document where evaluation starts, the outermost resource of every dynamic scope.
+    scope: Url,
+    /// Compiled reference targets identified by their address within the root schema.
+    references: HashMap<*const J, SchemaId>,
+    /// All other compiled subschemas, used to locate them in the root schema afterwards.
+    subschemas: HashMap<*const J, SchemaId>,
+    deferred: Vec<Deferred<J>>,
+    /// Schemas whose keywords are being compiled, innermost last.
+    compiling: Vec<*const J>,
+    /// Schemas and the subschemas or reference targets compiled for their keywords.
+    edges: Vec<(*const J, *const J)>,
+    /// Schemas with asynchronous checks, along with the keywords of these checks.
+    deferred_keywords: Vec<(*const J, Box<str>)>,
+    /// Values of `contentSchema`.
+    content_schemas: Vec<*const J>,
+    /// Set if the builder has a cache and nothing that makes compiled schemas builder-specific.
+    cache: Option<&'b CompilationCache<J>>,
+    /// Canonical URIs of shared targets being compiled, references to them are compiled locally.
+    shared: Vec<String>,
+}
+
+pub(crate) fn compile<'a, J: Json>(
+    schema: &'a J,
+    builder: &ValidatorBuilder<'a, J>,
+) -> BuildResult<Validator<J>> {
+    compile_at(schema, builder, None)
+}
+
+/// Compile a schema starting from the given reference instead of its root.
+pub(crate) fn compile_at<'a, J: Json>(
+    schema: &'a J,
+    builder: &ValidatorBuilder<'a, J>,
+    entry: Option<&str>,
+) -> BuildResult<Validator<J>> {
+    let draft = builder.draft;
+    let base_uri = Url::parse(DEFAULT_BASE_URI).expect("Invalid default base URI");
+    let resources = Resources::new(schema, &base_uri, draft);
+    let (vocabularies, custom) = drafts::vocabularies(schema, draft, builder, |uri| {
+        let (meta_schema, _, _) = resources.resolve(&base_uri, uri).ok()?;
+        Some(meta_schema)
+    })?;
+    // Keywords registered via the builder come last, so they override vocabulary ones
+    let custom_keywords: HashMap<_, _> = custom
+        .into_iter()
+        .flat_map(|vocabulary| vocabulary.keywords.iter())
+        .chain(builder.keywords.iter())
+        .map(|(name, factory)| (name.as_str(), factory))
+        .collect();
+    // Custom factories are opaque, so schemas compiled with them can not be shared. Neither can
+    // schemas with dynamic anchors, as targets of `$dynamicRef` depend on the entry point
+    let cache = builder.cache.as_deref().filter(|_| {
+        custom_keywords.is_empty()
+            && builder.async_keywords.is_empty()
+            && builder.formats.is_empty()
+            && builder.async_formats.is_empty()
+            && builder.content_encodings.is_empty()
+            && builder.content_media_types.is_empty()
+            && !resources.has_dynamic_anchors()
+    });
+    let mut ctx = Context {
+        draft,
+        vocabularies,
+        builder,
+        custom_keywords,
+        graph: Graph::new(),
+        resources,
+        base_uri: vec![base_uri.clone()],
+        scope: base_uri.clone(),
+        references: HashMap::new(),
+        subschemas: HashMap::new(),
+        deferred: Vec::new(),
+        compiling: Vec::new(),
+        edges: Vec::new(),
+        deferred_keywords: Vec::new(),
+        content_schemas: Vec::new(),
+        cache,
+        shared: Vec::new(),
+    };
+    // The entry and anchors are relative to the root, which may have an `$id` of its own
+    let root_uri = ctx.resources.uri_of(schema).unwrap_or(&base_uri).clone();
+    let (entry, entry_uri) = match entry {
+        Some(reference) => {
+            let (target, target_uri, _) = ctx.resources.resolve(&root_uri, reference)?;
+            (target, target_uri)
         }
-    } else if let Some(b) = schema.as_boolean() {
-    } else {
-        return Err(todo!());
+        None => (schema, root_uri.clone()),
     };
-    Ok(Validator::new(graph))
+    // Registered upfront, so references to the entry point to its reserved slot
+    ctx.references.insert(entry as *const J, SchemaId::ROOT);
+    ctx.scope = entry_uri.clone();
+    ctx.base_uri.push(entry_uri);
+    let keywords = ctx.compile_schema_keywords(entry);
+    ctx.base_uri.pop();
+    ctx.graph.set_schema(SchemaId::ROOT, keywords?);
+    // Definitions are compiled even if nothing refers to them, so they can be validated against.
+    // Unlike reference targets, they are not worth sharing with other validators
+    let cache = ctx.cache.take();
+    ctx.compile_definitions(schema, &base_uri, &JsonPointerNode::new())?;
+    ctx.cache = cache;
+    ctx.check_content_schemas()?;
+    let mut locations = HashMap::new();
+    ctx.locate_schemas(schema, &JsonPointerNode::new(), &mut locations);
+    for (anchor, target) in ctx.resources.anchors(&root_uri) {
+        if let Some(id) = ctx.compiled(target) {
+            locations.insert(anchor.into(), id);
+        }
+    }
+    Ok(Validator::new(ctx.graph, draft, ctx.deferred).with_locations(locations))
+}
+
+impl<'a, 'b, J: Json> Context<'a, 'b, J> {
+    /// Compile a schema and return its id in the graph.
+    pub(crate) fn compile_subschema(&mut self, schema: &'a J) -> BuildResult<Subschema> {
+        self.add_edge(schema);
+        let keywords = self.compile_schema_keywords(schema)?;
+        let id = self.graph.push_schema(keywords);
+        self.subschemas.insert(schema as *const J, id);
+        Ok(id)
+    }
+
+    /// Compile the value of `contentSchema`.
+    pub(crate) fn compile_content_schema(&mut self, schema: &'a J) -> BuildResult<Subschema> {
+        self.content_schemas.push(schema as *const J);
+        self.compile_subschema(schema)
+    }
+
+    /// Record that the schema being compiled uses the given subschema or reference target.
+    fn add_edge(&mut self, schema: &J) {
+        if let Some(parent) = self.compiling.last() {
+            self.edges.push((*parent, schema as *const J));
+        }
+    }
+
+    /// Fail if an asynchronous check is reachable from `contentSchema`.
+    ///
+    /// Results of such checks are looked up by the address of the value they apply to, but
+    /// documents decoded from the instance are created anew on each validation pass.
+    fn check_content_schemas(&self) -> BuildResult<()> {
+        if self.deferred_keywords.is_empty() || self.content_schemas.is_empty() {
+            return Ok(());
+        }
+        let mut edges: HashMap<*const J, Vec<*const J>> = HashMap::new();
+        for (parent, child) in &self.edges {
+            edges.entry(*parent).or_default().push(*child);
+        }
+        let mut seen = HashSet::new();
+        let mut stack = self.content_schemas.clone();
+        while let Some(schema) = stack.pop() {
+            if !seen.insert(schema) {
+                continue;
+            }
+            if let Some((_, keyword)) = self.deferred_keywords.iter().find(|(at, _)| *at == schema)
+            {
+                return Err(BuildError::unsupported_keyword(
+                    keyword.as_ref(),
+                    "asynchronous checks can not be used within `contentSchema`",
+                ));
+            }
+            stack.extend(edges.get(&schema).into_iter().flatten());
+        }
+        Ok(())
+    }
+
+    /// Id of a schema from the root document, if it was compiled.
+    fn compiled(&self, schema: &J) -> Option<SchemaId> {
+        let address = schema as *const J;
+        self.references
+            .get(&address)
+            .or_else(|| self.subschemas.get(&address))
+            .copied()
+    }
+
+    /// Compile entries of `$defs` & `definitions` anywhere within the given schema.
+    ///
+    /// Fragments are relative to the closest resource, as in references to these entries.
+    fn compile_definitions(
+        &mut self,
+        value: &'a J,
+        document: &Url,
+        location: &JsonPointerNode,
+    ) -> BuildResult<()> {
+        let Some(object) = value.as_object() else {
+            if let Some(array) = value.as_array() {
+                for (idx, item) in array.iter().enumerate() {
+                    self.compile_definitions(item?, document, &location.push(idx))?;
+                }
+            }
+            return Ok(());
+        };
+        let root = JsonPointerNode::new();
+        let (document, location) = match self.resources.uri_of(value) {
+            Some(uri) => (uri.clone(), &root),
+            None => (document.clone(), location),
+        };
+        for (key, child) in object.iter() {
+            let key = key?;
+            let key = key.as_ref();
+            if NON_SCHEMA_KEYWORDS.contains(&key) {
+                continue;
+            }
+            let location = location.push(key);
+            if matches!(key, "$defs" | "definitions") {
+                if let Some(definitions) = child.as_object() {
+                    for (name, definition) in definitions.iter() {
+                        let name = name?;
+                        let location = location.push(name.as_ref());
+                        let fragment = JsonPointer::from(&location).to_string();
+                        self.compile_target(definition, document.clone(), &fragment)?;
+                    }
+                }
+            }
+            self.compile_definitions(child, &document, &location)?;
+        }
+        Ok(())
+    }
+
+    /// Collect locations of compiled schemas within the root document.
+    fn locate_schemas(
+        &self,
+        value: &J,
+        location: &JsonPointerNode,
+        locations: &mut HashMap<Box<str>, SchemaId>,
+    ) {
+        if let Some(id) = self.compiled(value) {
+            locations
+                .entry(JsonPointer::from(location).to_string().into())
+                .or_insert(id);
+        }
+        if let Some(object) = value.as_object() {
+            for (key, child) in object.iter() {
+                if let Ok(key) = key {
+                    if !NON_SCHEMA_KEYWORDS.contains(&key.as_ref()) {
+                        self.locate_schemas(child, &location.push(key.as_ref()), locations);
+                    }
+                }
+            }
+        } else if let Some(array) = value.as_array() {
+            for (idx, item) in array.iter().enumerate() {
+                if let Ok(item) = item {
+                    self.locate_schemas(item, &location.push(idx), locations);
+                }
+            }
+        }
+    }
+
+    /// Compile keywords of a schema in the order they are evaluated.
+    fn compile_schema_keywords(&mut self, schema: &'a J) -> BuildResult<Vec<KeywordValue<J>>> {
+        if let Some(object) = schema.as_object() {
+            let base_uri = self.resources.uri_of(schema).cloned();
+            let has_base_uri = base_uri.is_some();
+            if let Some(base_uri) = base_uri {
+                self.base_uri.push(base_uri);
+            }
+            self.compiling.push(schema as *const J);
+            let keywords = self.compile_keywords(object);
+            self.compiling.pop();
+            if has_base_uri {
+                self.base_uri.pop();
+            }
+            keywords
+        } else if let Some(value) = schema.as_boolean() {
+            if value {
+                Ok(Vec::new())
+            } else {
+                Ok(vec![KeywordValue::FalseSchema])
+            }
+        } else {
+            Err(BuildError::invalid_schema())
+        }
+    }
+
+    fn compile_keywords(&mut self, object: &'a J::Object) -> BuildResult<Vec<KeywordValue<J>>> {
+        let mut keywords = Vec::new();
+        if self.draft.ref_overrides_siblings() {
+            if let Some(reference) = object.get("$ref") {
+                keywords.extend(self.compile_keyword(object, "$ref", reference)?);
+                return Ok(keywords);
+            }
+        }
+        for (key, value) in object.iter() {
+            let key = key?.as_ref();
+            keywords.extend(self.compile_keyword(object, key, value)?);
+        }
+        // Cheap checks first, the sort is stable so equally expensive keywords keep their order
+        keywords.sort_by_key(KeywordValue::cost);
+        Ok(keywords)
+    }
+
+    /// Compile a keyword, preferring asynchronous, then custom, then the draft's keywords.
+    fn compile_keyword(
+        &mut self,
+        object: &'a J::Object,
+        key: &'a str,
+        value: &'a J,
+    ) -> BuildResult<Option<KeywordValue<J>>> {
+        if let Some(factory) = self.builder.async_keywords.get(key) {
+            let keyword = factory.init(&mut CompileContext::new(self, object, key), value)?;
+            let index = self.defer(key, Deferred::Keyword(Arc::from(keyword)));
+            Ok(Some(KeywordValue::AsyncCustom(AsyncCustomKeyword {
+                name: key.into(),
+                index,
+            })))
+        } else if let Some(factory) = self.custom_keywords.get(key).copied() {
+            Ok(Some(KeywordValue::Custom(CustomKeyword {
+                name: key.into(),
+                keyword: Arc::from(
+                    factory.init(&mut CompileContext::new(self, object, key), value)?,
+                ),
+            })))
+        } else {
+            let draft = self.draft;
+            draft.get_keyword(self, object, key, value)
+        }
+    }
+
+    /// Register an asynchronous check of the given keyword and return its index.
+    pub(crate) fn defer(&mut self, keyword: &str, check: Deferred<J>) -> usize {
+        if let Some(schema) = self.compiling.last() {
+            self.deferred_keywords.push((*schema, keyword.into()));
+        }
+        self.deferred.push(check);
+        self.deferred.len() - 1
+    }
+
+    /// Compile the target of a reference, reusing it if it was already compiled.
+    pub(crate) fn compile_reference(
+        &mut self,
+        keyword: &'static str,
+        reference: &str,
+    ) -> BuildResult<KeywordValue<J>> {
+        let schema = self.compile_entry(reference)?;
+        Ok(KeywordValue::Ref(Ref { keyword, schema }))
+    }
+
+    /// Compile a `$dynamicRef`, as long as its target does not depend on the dynamic scope.
+    pub(crate) fn compile_dynamic_reference(
+        &mut self,
+        reference: &str,
+    ) -> BuildResult<KeywordValue<J>> {
+        let base_uri = self.base_uri.last().expect("Base URI is always set");
+        let (target, target_uri, fragment) = self.resources.resolve(base_uri, reference)?;
+        let declared: Vec<Url> = self.resources.dynamic_anchors(&fragment).cloned().collect();
+        self.compile_dynamic_target("$dynamicRef", &declared, target, target_uri, &fragment)
+    }
+
+    /// Compile a `$recursiveRef`, as long as its target does not depend on the dynamic scope.
+    ///
+    /// It works like `$dynamicRef` to `#`, with `"$recursiveAnchor": true` at the roots of
+    /// documents in place of dynamic anchors.
+    pub(crate) fn compile_recursive_reference(&mut self) -> BuildResult<KeywordValue<J>> {
+        let base_uri = self.base_uri.last().expect("Base URI is always set");
+        let (target, target_uri, fragment) = self.resources.resolve(base_uri, "#")?;
+        let declared = self.resources.recursive_anchors().to_vec();
+        self.compile_dynamic_target("$recursiveRef", &declared, target, target_uri, &fragment)
+    }
+
+    /// Compile the target of a dynamic reference, given the documents declaring its anchor.
+    ///
+    /// The target is the outermost resource in the dynamic scope that declares the anchor.
+    /// Evaluation always starts in the entry document, so its declaration takes precedence, and
+    /// if only the initial target declares it, there is nothing else to choose from.
+    fn compile_dynamic_target(
+        &mut self,
+        keyword: &'static str,
+        declared: &[Url],
+        target: &'a J,
+        target_uri: Url,
+        fragment: &str,
+    ) -> BuildResult<KeywordValue<J>> {
+        let (target, target_uri) = if !declared.contains(&target_uri) {
+            // Without an anchor at the initial target it is a plain `$ref`
+            (target, target_uri)
+        } else if declared.contains(&self.scope) {
+            let (target, target_uri, _) = self
+                .resources
+                .resolve(&self.scope, &format!("#{fragment}"))?;
+            (target, target_uri)
+        } else if declared.iter().all(|uri| *uri == target_uri) {
+            (target, target_uri)
+        } else {
+            return Err(BuildError::unsupported_keyword(
+                keyword,
+                "its target depends on the dynamic scope",
+            ));
+        };
+        let schema = self.compile_target(target, target_uri, fragment)?;
+        Ok(KeywordValue::Ref(Ref { keyword, schema }))
+    }
+
+    /// Cache key of a reference target, if it can be shared.
+    ///
+    /// Only targets within resources with an absolute `$id` have a canonical URI, documents
+    /// without one are anonymous and differ between validators.
+    fn cache_key(&self, document: &Url, fragment: &str) -> Option<CacheKey> {
+        self.cache?;
+        if document.as_str().starts_with(DEFAULT_BASE_URI) {
+            return None;
+        }
+        let uri = format!("{document}#{fragment}");
+        if self.shared.contains(&uri) {
+            return None;
+        }
+        Some(CacheKey::new(
+            uri,
+            self.draft,
+            self.vocabularies,
+            self.builder,
+        ))
+    }
+
+    /// Compile a reference target into a graph of its own, unless another validator did it.
+    fn compile_shared(&mut self, key: CacheKey, target: &'a J) -> BuildResult<Arc<SchemaGraph<J>>> {
+        let cache = self.cache.expect("Only called with a cache");
+        if let Some(graph) = cache.get(&key) {
+            return Ok(graph);
+        }
+        // Ids are local to a graph, so the target becomes the root of a fresh one
+        let graph = mem::replace(&mut self.graph, Graph::new());
+        let references = mem::replace(
+            &mut self.references,
+            HashMap::from([(target as *const J, SchemaId::ROOT)]),
+        );
+        let subschemas = mem::take(&mut self.subschemas);
+        self.shared.push(key.uri().to_owned());
+        let keywords = self.compile_schema_keywords(target);
+        self.shared.pop();
+        self.references = references;
+        self.subschemas = subschemas;
+        let mut shared = mem::replace(&mut self.graph, graph);
+        shared.set_schema(SchemaId::ROOT, keywords?);
+        Ok(cache.insert(key, shared, &self.builder.regex_engine))
+    }
+}
+
+impl<'a, 'b, J: Json> Compiler<'a, J> for Context<'a, 'b, J> {
+    fn draft(&self) -> Draft {
+        self.draft
+    }
+
+    fn base_uri(&self) -> &Url {
+        self.base_uri.last().expect("Base URI is always set")
+    }
+
+    fn locate(&self, parent: &J::Object, keyword: &str) -> JsonPointer {
+        self.resources.locate(parent, keyword)
+    }
+
+    fn compile_subschema(&mut self, schema: &'a J) -> BuildResult<Subschema> {
+        Context::compile_subschema(self, schema)
+    }
+
+    /// Compile the target of a reference and return its id in the graph.
+    fn compile_entry(&mut self, reference: &str) -> BuildResult<Subschema> {
+        let base_uri = self.base_uri.last().expect("Base URI is always set");
+        let (target, target_uri, fragment) = self.resources.resolve(base_uri, reference)?;
+        self.compile_target(target, target_uri, &fragment)
+    }
+}
+
+impl<'a, 'b, J: Json> Context<'a, 'b, J> {
+    /// Compile a resolved reference target, reusing it if it was already compiled.
+    fn compile_target(
+        &mut self,
+        target: &'a J,
+        target_uri: Url,
+        fragment: &str,
+    ) -> BuildResult<Subschema> {
+        self.add_edge(target);
+        if let Some(id) = self.references.get(&(target as *const J)) {
+            return Ok(*id);
+        }
+        let id = self.graph.reserve_schema();
+        // Register before compiling, so recursive references point to the same schema
+        self.references.insert(target as *const J, id);
+        let key = self.cache_key(&target_uri, fragment);
+        self.base_uri.push(target_uri);
+        let keywords = match key {
+            Some(key) => self
+                .compile_shared(key, target)
+                .map(|graph| vec![KeywordValue::Shared(Shared { graph })]),
+            None => self.compile_schema_keywords(target),
+        };
+        self.base_uri.pop();
+        self.graph.set_schema(id, keywords?);
+        Ok(id)
+    }
 }
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use jpointer::{JsonPointer, JsonPointerNode};
+use jsonlike::prelude::*;
+use url::Url;
+
+use crate::{drafts::Draft, BuildError, BuildResult};
+
+/// Schema resources and anchors within a root schema, identified by their URIs.
+pub(crate) struct Resources<'a, J: Json> {
+    draft: Draft,
+    root: &'a J,
+    documents: HashMap<Url, &'a J>,
+    /// URIs of schemas that change the base URI via `$id`.
+    uris: HashMap<*const J, Url>,
+    anchors: HashMap<(Url, String), &'a J>,
+    /// Documents and names of `$dynamicAnchor`s, which are also stored as plain anchors.
+    dynamic_anchors: Vec<(Url, String)>,
+    /// Documents whose root has `"$recursiveAnchor": true`.
+    recursive_anchors: Vec<Url>,
+}
+
+/// Keywords whose values are never subschemas and therefore can not contain identifiers.
+pub(crate) const NON_SCHEMA_KEYWORDS: &[&str] = &["const", "default", "enum", "examples"];
+
+impl<'a, J: Json> Resources<'a, J> {
+    pub(crate) fn new(root: &'a J, base_uri: &Url, draft: Draft) -> Resources<'a, J> {
+        let mut resources = Resources {
+            draft,
+            root,
+            documents: HashMap::new(),
+            uris: HashMap::new(),
+            anchors: HashMap::new(),
+            dynamic_anchors: Vec::new(),
+            recursive_anchors: Vec::new(),
+        };
+        resources.documents.insert(base_uri.clone(), root);
+        resources.collect(root, base_uri);
+        resources
+    }
+
+    fn collect(&mut self, value: &'a J, base_uri: &Url) {
+        if let Some(object) = value.as_object() {
+            let mut base_uri = base_uri;
+            let mut joined;
+            if let Some(id) = self.draft.id_of(object) {
+                if let Some(anchor) = id.strip_prefix('#') {
+                    self.anchors
+                        .insert((base_uri.clone(), anchor.to_owned()), value);
+                } else if let Ok(uri) = base_uri.join(id) {
+                    joined = uri;
+                    if let Some(anchor) = joined.fragment().filter(|anchor| !anchor.is_empty()) {
+                        let anchor = anchor.to_owned();
+                        joined.set_fragment(None);
+                        self.anchors.insert((joined.clone(), anchor), value);
+                    }
+                    joined.set_fragment(None);
+                    self.documents.insert(joined.clone(), value);
+                    self.uris.insert(value as *const J, joined.clone());
+                    base_uri = &joined;
+                }
+            }
+            let is_resource =
+                std::ptr::eq(value, self.root) || self.uris.contains_key(&(value as *const J));
+            if self.draft == Draft::Draft201909
+                && is_resource
+                && object.get("$recursiveAnchor").and_then(Json::as_boolean) == Some(true)
+            {
+                self.recursive_anchors.push(base_uri.clone());
+            }
+            for keyword in self.draft.anchor_keywords() {
+                if let Some(anchor) = object.get(keyword).and_then(Json::as_string) {
+                    if *keyword == "$dynamicAnchor" {
+                        self.dynamic_anchors
+                            .push((base_uri.clone(), anchor.as_ref().to_owned()));
+                    }
+                    self.anchors
+                        .insert((base_uri.clone(), anchor.as_ref().to_owned()), value);
+                }
+            }
+            for (key, child) in object.iter() {
+                if let Ok(key) = key {
+                    if !NON_SCHEMA_KEYWORDS.contains(&key.as_ref()) {
+                        self.collect(child, base_uri);
+                    }
+                }
+            }
+        } else if let Some(array) = value.as_array() {
+            for item in array.iter().filter_map(Result::ok) {
+                self.collect(item, base_uri);
+            }
+        }
+    }
+
+    /// Anchors defined within the given document.
+    pub(crate) fn anchors<'r>(
+        &'r self,
+        document: &'r Url,
+    ) -> impl Iterator<Item = (&'r str, &'a J)> {
+        self.anchors
+            .iter()
+            .filter(move |((uri, _), _)| uri == document)
+            .map(|((_, anchor), target)| (anchor.as_str(), *target))
+    }
+
+    /// Documents that declare a `$dynamicAnchor` with the given name.
+    pub(crate) fn dynamic_anchors<'r>(&'r self, name: &'r str) -> impl Iterator<Item = &'r Url> {
+        self.dynamic_anchors
+            .iter()
+            .filter(move |(_, anchor)| anchor == name)
+            .map(|(uri, _)| uri)
+    }
+
+    /// Documents whose root has `"$recursiveAnchor": true`.
+    pub(crate) fn recursive_anchors(&self) -> &[Url] {
+        &self.recursive_anchors
+    }
+
+    /// Whether targets of `$dynamicRef` or `$recursiveRef` may depend on the entry point.
+    pub(crate) fn has_dynamic_anchors(&self) -> bool {
+        !self.dynamic_anchors.is_empty() || !self.recursive_anchors.is_empty()
+    }
+
+    /// URI of the given schema if it is a separate resource.
+    pub(crate) fn uri_of(&self, schema: &J) -> Option<&Url> {
+        self.uris.get(&(schema as *const J))
+    }
+
+    /// Location of a keyword of the given schema object within the root schema.
+    ///
+    /// Schemas are identified by their address, so it requires a traversal of the root schema.
+    pub(crate) fn locate(&self, parent: &J::Object, keyword: &str) -> JsonPointer {
+        fn find<J: Json>(
+            value: &J,
+            parent: &J::Object,
+            keyword: &str,
+            location: &JsonPointerNode,
+        ) -> Option<JsonPointer> {
+            if let Some(object) = value.as_object() {
+                if std::ptr::eq(object, parent) {
+                    return Some(JsonPointer::from(&location.push(keyword)));
+                }
+                object.iter().find_map(|(key, child)| {
+                    let key = key.ok()?;
+                    find(child, parent, keyword, &location.push(key.as_ref()))
+                })
+            } else if let Some(array) = value.as_array() {
+                array
+                    .iter()
+                    .enumerate()
+                    .find_map(|(idx, item)| find(item.ok()?, parent, keyword, &location.push(idx)))
+            } else {
+                None
+            }
+        }
+        let root = JsonPointerNode::new();
+        find(self.root, parent, keyword, &root)
+            .unwrap_or_else(|| JsonPointer::from(&root.push(keyword)))
+    }
+
+    /// Resolve a reference against the given base URI.
+    ///
+    /// Returns the target with the URI of its document and the unescaped fragment.
+    pub(crate) fn resolve(
+        &self,
+        base_uri: &Url,
+        reference: &str,
+    ) -> BuildResult<(&'a J, Url, String)> {
+        let unresolvable = || BuildError::unresolvable_reference(reference);
+        let mut uri = base_uri.join(reference).map_err(|_| unresolvable())?;
+        let fragment = uri
+            .fragment()
+            .map(|fragment| {
+                percent_encoding::percent_decode_str(fragment)
+                    .decode_utf8_lossy()
+                    .into_owned()
+            })
+            .unwrap_or_default();
+        uri.set_fragment(None);
+        let document = *self.documents.get(&uri).ok_or_else(unresolvable)?;
+        let target = if fragment.is_empty() {
+            document
+        } else if let Some(pointer) = fragment.strip_prefix('/') {
+            let mut target = document;
+            for segment in pointer.split('/') {
+                let segment = segment.replace("~1", "/").replace("~0", "~");
+                target = if let Some(object) = target.as_object() {
+                    object.get(&segment)
+                } else if let Some(array) = target.as_array() {
+                    segment.parse::<usize>().ok().and_then(|idx| array.get(idx))
+                } else {
+                    None
+                }
+                .ok_or_else(unresolvable)?;
+            }
+            target
+        } else {
+            *self
+                .anchors
+                .get(&(uri.clone(), fragment.clone()))
+                .ok_or_else(unresolvable)?
+        };
+        Ok((target, uri, fragment))
+    }
+}
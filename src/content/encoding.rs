@@ -0,0 +1,145 @@
+//! Decoders for `contentEncoding` values defined by RFC 4648 & RFC 2045.
+use super::ContentEncoding;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+pub(crate) struct Base64;
+
+impl ContentEncoding for Base64 {
+    fn decode(&self, value: &str) -> Option<Vec<u8>> {
+        decode_base64(value, BASE64, true)
+    }
+}
+
+pub(crate) struct Base64Url;
+
+impl ContentEncoding for Base64Url {
+    fn decode(&self, value: &str) -> Option<Vec<u8>> {
+        decode_base64(value, BASE64URL, false)
+    }
+}
+
+/// Padding is optional for the URL-safe alphabet, where it is commonly omitted.
+fn decode_base64(value: &str, alphabet: &[u8; 64], require_padding: bool) -> Option<Vec<u8>> {
+    let bytes = value.as_bytes();
+    let data = bytes
+        .strip_suffix(b"==")
+        .or_else(|| bytes.strip_suffix(b"="))
+        .unwrap_or(bytes);
+    let padded = data.len() != bytes.len();
+    if (padded || require_padding) && !bytes.len().is_multiple_of(4) || data.len() % 4 == 1 {
+        return None;
+    }
+    let mut decoded = Vec::with_capacity(data.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in data {
+        let index = alphabet.iter().position(|symbol| symbol == byte)?;
+        buffer = (buffer << 6) | u32::try_from(index).expect("Alphabet has 64 symbols");
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push(u8::try_from((buffer >> bits) & 0xFF).expect("Masked to a byte"));
+        }
+    }
+    // Leftover bits must be zero for a canonical encoding
+    if buffer & ((1 << bits) - 1) != 0 {
+        return None;
+    }
+    Some(decoded)
+}
+
+pub(crate) struct Base16;
+
+impl ContentEncoding for Base16 {
+    fn decode(&self, value: &str) -> Option<Vec<u8>> {
+        if !value.len().is_multiple_of(2) {
+            return None;
+        }
+        value
+            .as_bytes()
+            .chunks(2)
+            .map(|pair| {
+                let pair = core::str::from_utf8(pair).ok()?;
+                if !pair.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+                    return None;
+                }
+                u8::from_str_radix(pair, 16).ok()
+            })
+            .collect()
+    }
+}
+
+pub(crate) struct QuotedPrintable;
+
+impl ContentEncoding for QuotedPrintable {
+    fn decode(&self, value: &str) -> Option<Vec<u8>> {
+        let bytes = value.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut idx = 0;
+        while idx < bytes.len() {
+            match bytes[idx] {
+                b'=' => match bytes.get(idx + 1..idx + 3)? {
+                    // Soft line break
+                    b"\r\n" => idx += 3,
+                    [high, low] if high.is_ascii_hexdigit() && low.is_ascii_hexdigit() => {
+                        let hex = core::str::from_utf8(&bytes[idx + 1..idx + 3]).ok()?;
+                        decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                        idx += 3;
+                    }
+                    _ => return None,
+                },
+                byte @ (b'\t' | b'\r' | b'\n' | b' '..=b'~') => {
+                    decoded.push(byte);
+                    idx += 1;
+                }
+                _ => return None,
+            }
+        }
+        Some(decoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("", Some(b""))]
+    #[test_case("Zg==", Some(b"f"))]
+    #[test_case("Zm8=", Some(b"fo"))]
+    #[test_case("Zm9v", Some(b"foo"))]
+    #[test_case("eyJmb28iOiAiYmFyIn0K", Some(b"{\"foo\": \"bar\"}\n"))]
+    #[test_case("Zm9", None)]
+    #[test_case("Zh==", None)]
+    #[test_case("Zm9v%", None)]
+    #[test_case("-_-_", None)]
+    fn test_base64(value: &str, expected: Option<&[u8]>) {
+        assert_eq!(Base64.decode(value).as_deref(), expected);
+    }
+
+    #[test_case("-_-_", Some(&[0xFB, 0xFF, 0xBF]))]
+    #[test_case("Zm8", Some(b"fo"))]
+    #[test_case("Zm8=", Some(b"fo"))]
+    #[test_case("+/+/", None)]
+    fn test_base64url(value: &str, expected: Option<&[u8]>) {
+        assert_eq!(Base64Url.decode(value).as_deref(), expected);
+    }
+
+    #[test_case("666F6F", Some(b"foo"))]
+    #[test_case("666f6f", Some(b"foo"))]
+    #[test_case("666F6", None)]
+    #[test_case("+66F6F", None)]
+    fn test_base16(value: &str, expected: Option<&[u8]>) {
+        assert_eq!(Base16.decode(value).as_deref(), expected);
+    }
+
+    #[test_case("caf=C3=A9", Some("caf\u{e9}".as_bytes()); "escaped utf8")]
+    #[test_case("long=\r\nline", Some(b"longline"))]
+    #[test_case("a=3", None)]
+    #[test_case("a=ZZ", None)]
+    fn test_quoted_printable(value: &str, expected: Option<&[u8]>) {
+        assert_eq!(QuotedPrintable.decode(value).as_deref(), expected);
+    }
+}
@@ -0,0 +1,108 @@
+mod encoding;
+
+use std::sync::Arc;
+
+use jsonlike::Json;
+
+use crate::{BoxedContentEncoding, BoxedContentMediaType, BuildResult};
+
+/// Decoder for a `contentEncoding` value.
+pub trait ContentEncoding: Send + Sync + 'static {
+    /// Decode the string, returning `None` if it is not properly encoded.
+    fn decode(&self, value: &str) -> Option<Vec<u8>>;
+}
+
+/// Parser for a `contentMediaType` value.
+pub trait ContentMediaType<J: Json>: Send + Sync {
+    /// Parse the decoded content, returning `None` if it is not a valid document.
+    ///
+    /// The parsed document is validated against `contentSchema`.
+    fn parse(&self, content: &[u8]) -> Option<J>;
+}
+
+mod sealed {
+    pub trait EncodingSealed<J> {}
+    pub trait MediaTypeSealed<J> {}
+}
+
+/// Creates a decoder from the `contentEncoding` value.
+pub trait ContentEncodingFactory<'a, J: Json>:
+    Send + Sync + sealed::EncodingSealed<J> + 'a
+{
+    fn init(&self, schema: &'a J) -> BuildResult<BoxedContentEncoding>;
+}
+
+impl<'a, F, J: Json + 'a> sealed::EncodingSealed<J> for F where
+    F: Fn(&'a J) -> BuildResult<BoxedContentEncoding> + Send + Sync + 'a
+{
+}
+
+impl<'a, F, J: Json + 'a> ContentEncodingFactory<'a, J> for F
+where
+    F: Fn(&'a J) -> BuildResult<BoxedContentEncoding> + Send + Sync + 'a,
+{
+    fn init(&self, schema: &'a J) -> BuildResult<BoxedContentEncoding> {
+        self(schema)
+    }
+}
+
+/// Creates a parser from the `contentMediaType` value.
+pub trait ContentMediaTypeFactory<'a, J: Json>:
+    Send + Sync + sealed::MediaTypeSealed<J> + 'a
+{
+    fn init(&self, schema: &'a J) -> BuildResult<BoxedContentMediaType<J>>;
+}
+
+impl<'a, F, J: Json + 'a> sealed::MediaTypeSealed<J> for F where
+    F: Fn(&'a J) -> BuildResult<BoxedContentMediaType<J>> + Send + Sync + 'a
+{
+}
+
+impl<'a, F, J: Json + 'a> ContentMediaTypeFactory<'a, J> for F
+where
+    F: Fn(&'a J) -> BuildResult<BoxedContentMediaType<J>> + Send + Sync + 'a,
+{
+    fn init(&self, schema: &'a J) -> BuildResult<BoxedContentMediaType<J>> {
+        self(schema)
+    }
+}
+
+/// Built-in decoder for the given lowercase encoding.
+pub(crate) fn builtin_encoding(name: &str) -> Option<Arc<dyn ContentEncoding>> {
+    let encoding: Arc<dyn ContentEncoding> = match name {
+        "base64" => Arc::new(encoding::Base64),
+        "base64url" => Arc::new(encoding::Base64Url),
+        "base16" => Arc::new(encoding::Base16),
+        "quoted-printable" => Arc::new(encoding::QuotedPrintable),
+        _ => return None,
+    };
+    Some(encoding)
+}
+
+/// Built-in parser for the given media type essence.
+pub(crate) fn builtin_media_type<J: Json>(essence: &str) -> Option<Arc<dyn ContentMediaType<J>>> {
+    if essence == "application/json"
+        || essence.starts_with("application/") && essence.ends_with("+json")
+    {
+        Some(Arc::new(JsonMediaType))
+    } else {
+        None
+    }
+}
+
+/// The lowercase media type without parameters like `charset`.
+pub(crate) fn essence(name: &str) -> String {
+    name.split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+struct JsonMediaType;
+
+impl<J: Json> ContentMediaType<J> for JsonMediaType {
+    fn parse(&self, content: &[u8]) -> Option<J> {
+        J::from_str(core::str::from_utf8(content).ok()?).ok()
+    }
+}
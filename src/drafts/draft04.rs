@@ -1,6 +1,106 @@
-use crate::vocabulary::KeywordValue;
-use jsonlike::Json;
+use crate::{
+    compiler::Context,
+    vocabulary::{
+        applicator::{
+            AdditionalProperties, AllOf, AnyOf, Dependencies, Items, Not, OneOf, PatternProperties,
+            PrefixItems, Properties,
+        },
+        format::Format,
+        validation::{
+            as_f64, as_u64, Bound, Enum, Limit, MultipleOf, Pattern, Required, Size, SizeLimit,
+            Type, UniqueItems,
+        },
+        KeywordValue,
+    },
+    BuildError, BuildResult,
+};
+use jsonlike::prelude::*;
 
-pub(crate) fn get_keyword<J: Json>(key: &str, value: &J) -> Option<KeywordValue<J>> {
-    None
+pub(crate) fn get_keyword<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    key: &str,
+    value: &'a J,
+) -> BuildResult<Option<KeywordValue<J>>> {
+    let keyword = match key {
+        "$ref" => {
+            let reference = value
+                .as_string()
+                .ok_or_else(|| BuildError::invalid_keyword("$ref", "a string"))?;
+            ctx.compile_reference("$ref", reference.as_ref())?
+        }
+        "allOf" => KeywordValue::AllOf(AllOf::compile(ctx, value)?),
+        "anyOf" => KeywordValue::AnyOf(AnyOf::compile(ctx, value)?),
+        "oneOf" => KeywordValue::OneOf(OneOf::compile(ctx, value)?),
+        "not" => KeywordValue::Not(Not::compile(ctx, value)?),
+        "properties" => KeywordValue::Properties(Properties::compile(ctx, value)?),
+        "patternProperties" => {
+            KeywordValue::PatternProperties(PatternProperties::compile(ctx, value)?)
+        }
+        "additionalProperties" => {
+            KeywordValue::AdditionalProperties(AdditionalProperties::compile(ctx, parent, value)?)
+        }
+        "items" => {
+            if value.is_array() {
+                KeywordValue::PrefixItems(PrefixItems::compile(ctx, "items", value)?)
+            } else {
+                KeywordValue::Items(Items::compile(ctx, "items", value, 0)?)
+            }
+        }
+        "additionalItems" => {
+            // Only applies when `items` is an array
+            let Some(items) = parent.get("items").and_then(Json::as_array) else {
+                return Ok(None);
+            };
+            let skip = items.iter().count();
+            KeywordValue::Items(Items::compile(ctx, "additionalItems", value, skip)?)
+        }
+        "dependencies" => KeywordValue::Dependencies(Dependencies::compile(ctx, value)?),
+        "type" => KeywordValue::Type(Type::compile(value)?),
+        "enum" => KeywordValue::Enum(Enum::compile(value)?),
+        "multipleOf" => KeywordValue::MultipleOf(MultipleOf::compile(value)?),
+        "maximum" => {
+            let exclusive = parent.get("exclusiveMaximum").and_then(Json::as_boolean);
+            let bound = if exclusive == Some(true) {
+                Bound::ExclusiveMaximum
+            } else {
+                Bound::Maximum
+            };
+            KeywordValue::Limit(Limit::new(bound, as_f64(key, value)?))
+        }
+        "minimum" => {
+            let exclusive = parent.get("exclusiveMinimum").and_then(Json::as_boolean);
+            let bound = if exclusive == Some(true) {
+                Bound::ExclusiveMinimum
+            } else {
+                Bound::Minimum
+            };
+            KeywordValue::Limit(Limit::new(bound, as_f64(key, value)?))
+        }
+        "maxLength" => {
+            KeywordValue::SizeLimit(SizeLimit::new(Size::MaxLength, as_u64(key, value)?))
+        }
+        "minLength" => {
+            KeywordValue::SizeLimit(SizeLimit::new(Size::MinLength, as_u64(key, value)?))
+        }
+        "pattern" => KeywordValue::Pattern(Pattern::compile(ctx, value)?),
+        "maxItems" => KeywordValue::SizeLimit(SizeLimit::new(Size::MaxItems, as_u64(key, value)?)),
+        "minItems" => KeywordValue::SizeLimit(SizeLimit::new(Size::MinItems, as_u64(key, value)?)),
+        "uniqueItems" => {
+            if value.as_boolean() != Some(true) {
+                return Ok(None);
+            }
+            KeywordValue::UniqueItems(UniqueItems)
+        }
+        "maxProperties" => {
+            KeywordValue::SizeLimit(SizeLimit::new(Size::MaxProperties, as_u64(key, value)?))
+        }
+        "minProperties" => {
+            KeywordValue::SizeLimit(SizeLimit::new(Size::MinProperties, as_u64(key, value)?))
+        }
+        "required" => KeywordValue::Required(Required::compile(value)?),
+        "format" => KeywordValue::Format(Format::compile(ctx, parent, value)?),
+        _ => return Ok(None),
+    };
+    Ok(Some(keyword))
 }
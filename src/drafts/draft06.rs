@@ -1,6 +1,32 @@
-use crate::vocabulary::KeywordValue;
-use jsonlike::Json;
+use crate::{
+    compiler::Context,
+    drafts::draft04,
+    vocabulary::{
+        applicator::{Contains, PropertyNames},
+        validation::{as_f64, Bound, Const, Limit},
+        KeywordValue,
+    },
+    BuildResult,
+};
+use jsonlike::prelude::*;
 
-pub(crate) fn get_keyword<J: Json>(key: &str, value: &J) -> Option<KeywordValue<J>> {
-    None
+pub(crate) fn get_keyword<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    key: &str,
+    value: &'a J,
+) -> BuildResult<Option<KeywordValue<J>>> {
+    let keyword = match key {
+        "const" => KeywordValue::Const(Const::compile(value)),
+        "contains" => KeywordValue::Contains(Contains::compile(ctx, value, 1, None)?),
+        "propertyNames" => KeywordValue::PropertyNames(PropertyNames::compile(ctx, value)?),
+        "exclusiveMaximum" => {
+            KeywordValue::Limit(Limit::new(Bound::ExclusiveMaximum, as_f64(key, value)?))
+        }
+        "exclusiveMinimum" => {
+            KeywordValue::Limit(Limit::new(Bound::ExclusiveMinimum, as_f64(key, value)?))
+        }
+        _ => return draft04::get_keyword(ctx, parent, key, value),
+    };
+    Ok(Some(keyword))
 }
@@ -1,6 +1,25 @@
-use crate::vocabulary::KeywordValue;
-use jsonlike::Json;
+use crate::{
+    compiler::Context,
+    drafts::draft06,
+    vocabulary::{applicator::IfThenElse, content::Content, KeywordValue},
+    BuildResult,
+};
+use jsonlike::prelude::*;
 
-pub(crate) fn get_keyword<J: Json>(key: &str, value: &J) -> Option<KeywordValue<J>> {
-    None
+pub(crate) fn get_keyword<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    key: &str,
+    value: &'a J,
+) -> BuildResult<Option<KeywordValue<J>>> {
+    let keyword = match key {
+        "if" => KeywordValue::IfThenElse(IfThenElse::compile(ctx, parent, value)?),
+        // All content keywords are compiled together, at `contentMediaType` if present
+        "contentEncoding" if parent.get("contentMediaType").is_some() => return Ok(None),
+        "contentEncoding" | "contentMediaType" => {
+            KeywordValue::Content(Content::compile(ctx, parent)?)
+        }
+        _ => return draft06::get_keyword(ctx, parent, key, value),
+    };
+    Ok(Some(keyword))
 }
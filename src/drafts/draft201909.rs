@@ -1,6 +1,86 @@
-use crate::vocabulary::KeywordValue;
-use jsonlike::Json;
+use crate::{
+    compiler::Context,
+    drafts::draft07,
+    vocabulary::{
+        applicator::{Contains, DependentSchemas},
+        unevaluated::{UnevaluatedItems, UnevaluatedProperties},
+        validation::{as_u64, DependentRequired},
+        KeywordValue, Vocabulary,
+    },
+    BuildResult,
+};
+use jsonlike::prelude::*;
 
-pub(crate) fn get_keyword<J: Json>(key: &str, value: &J) -> Option<KeywordValue<J>> {
-    None
+pub(crate) fn vocabulary(key: &str) -> Option<Vocabulary> {
+    match key {
+        "$id" | "$schema" | "$anchor" | "$ref" | "$recursiveRef" | "$recursiveAnchor"
+        | "$vocabulary" | "$comment" | "$defs" => Some(Vocabulary::Core),
+        "allOf"
+        | "anyOf"
+        | "oneOf"
+        | "not"
+        | "if"
+        | "then"
+        | "else"
+        | "dependentSchemas"
+        | "items"
+        | "additionalItems"
+        | "unevaluatedItems"
+        | "contains"
+        | "properties"
+        | "patternProperties"
+        | "additionalProperties"
+        | "unevaluatedProperties"
+        | "propertyNames" => Some(Vocabulary::Applicator),
+        "type" | "const" | "enum" | "multipleOf" | "maximum" | "exclusiveMaximum" | "minimum"
+        | "exclusiveMinimum" | "maxLength" | "minLength" | "pattern" | "maxItems" | "minItems"
+        | "uniqueItems" | "maxContains" | "minContains" | "maxProperties" | "minProperties"
+        | "required" | "dependentRequired" => Some(Vocabulary::Validation),
+        "title" | "description" | "default" | "deprecated" | "readOnly" | "writeOnly"
+        | "examples" => Some(Vocabulary::MetaData),
+        "format" => Some(Vocabulary::FormatAnnotation),
+        "contentEncoding" | "contentMediaType" | "contentSchema" => Some(Vocabulary::Content),
+        _ => None,
+    }
+}
+
+pub(crate) fn get_keyword<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    key: &str,
+    value: &'a J,
+) -> BuildResult<Option<KeywordValue<J>>> {
+    let keyword = match key {
+        "$recursiveRef" => ctx.compile_recursive_reference()?,
+        "contains" => {
+            // `minContains` & `maxContains` belong to the validation vocabulary
+            let (min, max) = if ctx.vocabularies.contains(Vocabulary::Validation) {
+                let min = match parent.get("minContains") {
+                    Some(min) => as_u64("minContains", min)?,
+                    None => 1,
+                };
+                let max = match parent.get("maxContains") {
+                    Some(max) => Some(as_u64("maxContains", max)?),
+                    None => None,
+                };
+                (min, max)
+            } else {
+                (1, None)
+            };
+            KeywordValue::Contains(Contains::compile(ctx, value, min, max)?)
+        }
+        "dependentSchemas" => {
+            KeywordValue::DependentSchemas(DependentSchemas::compile(ctx, value)?)
+        }
+        "dependentRequired" => KeywordValue::DependentRequired(DependentRequired::compile(value)?),
+        "unevaluatedProperties" => KeywordValue::UnevaluatedProperties(UnevaluatedProperties {
+            schema: ctx.compile_subschema(value)?,
+        }),
+        "unevaluatedItems" => KeywordValue::UnevaluatedItems(UnevaluatedItems {
+            schema: ctx.compile_subschema(value)?,
+        }),
+        "dependencies" | "contentSchema" => return Ok(None),
+        _ => return draft07::get_keyword(ctx, parent, key, value),
+    };
+    Ok(Some(keyword))
 }
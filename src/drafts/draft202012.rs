@@ -1,6 +1,49 @@
-use crate::vocabulary::KeywordValue;
-use jsonlike::Json;
+use crate::{
+    compiler::Context,
+    drafts::draft201909,
+    vocabulary::{
+        applicator::{Items, PrefixItems},
+        KeywordValue, Vocabulary,
+    },
+    BuildError, BuildResult,
+};
+use jsonlike::prelude::*;
 
-pub(crate) fn get_keyword<J: Json>(key: &str, value: &J) -> Option<KeywordValue<J>> {
-    None
+pub(crate) fn vocabulary(key: &str) -> Option<Vocabulary> {
+    match key {
+        "$dynamicRef" | "$dynamicAnchor" => Some(Vocabulary::Core),
+        "prefixItems" => Some(Vocabulary::Applicator),
+        "unevaluatedItems" | "unevaluatedProperties" => Some(Vocabulary::Unevaluated),
+        "$recursiveRef" | "$recursiveAnchor" | "additionalItems" => None,
+        _ => draft201909::vocabulary(key),
+    }
+}
+
+pub(crate) fn get_keyword<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    key: &str,
+    value: &'a J,
+) -> BuildResult<Option<KeywordValue<J>>> {
+    let keyword = match key {
+        "$dynamicRef" => {
+            let reference = value
+                .as_string()
+                .ok_or_else(|| BuildError::invalid_keyword("$dynamicRef", "a string"))?;
+            ctx.compile_dynamic_reference(reference.as_ref())?
+        }
+        "prefixItems" => {
+            KeywordValue::PrefixItems(PrefixItems::compile(ctx, "prefixItems", value)?)
+        }
+        "items" => {
+            let skip = parent
+                .get("prefixItems")
+                .and_then(Json::as_array)
+                .map_or(0, |prefix| prefix.iter().count());
+            KeywordValue::Items(Items::compile(ctx, "items", value, skip)?)
+        }
+        "$recursiveRef" | "additionalItems" => return Ok(None),
+        _ => return draft201909::get_keyword(ctx, parent, key, value),
+    };
+    Ok(Some(keyword))
 }
@@ -4,10 +4,15 @@ mod draft07;
 mod draft201909;
 mod draft202012;
 
-use crate::vocabulary::KeywordValue;
+use crate::{
+    compiler::Context,
+    validation::builder::ValidatorBuilder,
+    vocabulary::{CustomVocabulary, KeywordValue, Vocabulary, VocabularySet},
+    BuildError, BuildResult,
+};
 use jsonlike::prelude::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Draft {
     Draft04,
     Draft06,
@@ -20,15 +25,178 @@ impl Draft {
     pub fn latest() -> Self {
         Self::Draft202012
     }
-    pub(crate) fn get_keyword<J: Json>(&self, key: &str, value: &J) -> Option<KeywordValue<J>> {
+    pub(crate) fn get_keyword<'a, J: Json>(
+        &self,
+        ctx: &mut Context<'a, '_, J>,
+        parent: &'a J::Object,
+        key: &str,
+        value: &'a J,
+    ) -> BuildResult<Option<KeywordValue<J>>> {
+        if let Some(vocabulary) = self.vocabulary(key) {
+            // `format` is enabled by either of the format vocabularies
+            let enabled = ctx.vocabularies.contains(vocabulary)
+                || (vocabulary == Vocabulary::FormatAnnotation
+                    && ctx.vocabularies.contains(Vocabulary::FormatAssertion));
+            if !enabled {
+                return Ok(None);
+            }
+        }
+        match self {
+            Draft::Draft04 => draft04::get_keyword(ctx, parent, key, value),
+            Draft::Draft06 => draft06::get_keyword(ctx, parent, key, value),
+            Draft::Draft07 => draft07::get_keyword(ctx, parent, key, value),
+            Draft::Draft201909 => draft201909::get_keyword(ctx, parent, key, value),
+            Draft::Draft202012 => draft202012::get_keyword(ctx, parent, key, value),
+        }
+    }
+    /// Vocabulary a keyword belongs to. Drafts before 2019-09 have no vocabularies.
+    fn vocabulary(&self, key: &str) -> Option<Vocabulary> {
+        match self {
+            Draft::Draft04 | Draft::Draft06 | Draft::Draft07 => None,
+            Draft::Draft201909 => draft201909::vocabulary(key),
+            Draft::Draft202012 => draft202012::vocabulary(key),
+        }
+    }
+    /// Vocabularies enabled when the meta-schema does not declare `$vocabulary`.
+    pub(crate) fn default_vocabularies(&self) -> VocabularySet {
+        let vocabularies = VocabularySet::empty()
+            .with(Vocabulary::Core)
+            .with(Vocabulary::Applicator)
+            .with(Vocabulary::Validation)
+            .with(Vocabulary::MetaData)
+            .with(Vocabulary::FormatAnnotation)
+            .with(Vocabulary::Content);
+        match self {
+            Draft::Draft202012 => vocabularies.with(Vocabulary::Unevaluated),
+            // `unevaluated*` keywords are a part of the applicator vocabulary before 2020-12
+            _ => vocabularies,
+        }
+    }
+    pub(crate) fn id_of<'a, O: JsonObject + ?Sized>(&self, object: &'a O) -> Option<&'a str> {
+        let key = if *self == Draft::Draft04 { "id" } else { "$id" };
+        // Before 2019-09 `$ref` overrides all its siblings, including identifiers
+        if self.ref_overrides_siblings() && object.contains_key("$ref") {
+            return None;
+        }
+        object.get(key).and_then(Json::as_string).map(AsRef::as_ref)
+    }
+    pub(crate) fn anchor_keywords(&self) -> &'static [&'static str] {
         match self {
-            Draft::Draft04 => draft04::get_keyword(key, value),
-            Draft::Draft06 => draft06::get_keyword(key, value),
-            Draft::Draft07 => draft07::get_keyword(key, value),
-            Draft::Draft201909 => draft201909::get_keyword(key, value),
-            Draft::Draft202012 => draft202012::get_keyword(key, value),
+            Draft::Draft04 | Draft::Draft06 | Draft::Draft07 => &[],
+            Draft::Draft201909 => &["$anchor"],
+            Draft::Draft202012 => &["$anchor", "$dynamicAnchor"],
+        }
+    }
+    pub(crate) fn ref_overrides_siblings(&self) -> bool {
+        matches!(self, Draft::Draft04 | Draft::Draft06 | Draft::Draft07)
+    }
+}
+
+/// `$vocabulary` of the official 2019-09 meta-schema.
+const DRAFT201909_VOCABULARIES: &[(&str, bool)] = &[
+    ("https://json-schema.org/draft/2019-09/vocab/core", true),
+    (
+        "https://json-schema.org/draft/2019-09/vocab/applicator",
+        true,
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/vocab/validation",
+        true,
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/vocab/meta-data",
+        true,
+    ),
+    ("https://json-schema.org/draft/2019-09/vocab/format", false),
+    ("https://json-schema.org/draft/2019-09/vocab/content", true),
+];
+
+/// `$vocabulary` of the official 2020-12 meta-schema.
+const DRAFT202012_VOCABULARIES: &[(&str, bool)] = &[
+    ("https://json-schema.org/draft/2020-12/vocab/core", true),
+    (
+        "https://json-schema.org/draft/2020-12/vocab/applicator",
+        true,
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/vocab/unevaluated",
+        true,
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/vocab/validation",
+        true,
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/vocab/meta-data",
+        true,
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/vocab/format-annotation",
+        true,
+    ),
+    ("https://json-schema.org/draft/2020-12/vocab/content", true),
+];
+
+/// Collect vocabularies enabled by the meta-schema of `schema`.
+///
+/// `$vocabulary` is only taken into account since 2019-09. The meta-schema is looked up among
+/// the ones registered via `ValidatorBuilder::meta_schema`, then via `resolve` among resources of
+/// the schema itself. The official meta-schemas are known, all others enable the default
+/// vocabularies of the draft.
+pub(crate) fn vocabularies<'a, 'b, J: Json>(
+    schema: &J,
+    draft: Draft,
+    builder: &'b ValidatorBuilder<'a, J>,
+    resolve: impl FnOnce(&str) -> Option<&'b J>,
+) -> BuildResult<(VocabularySet, Vec<&'b CustomVocabulary<'a, J>>)> {
+    let uri = schema
+        .as_object()
+        .and_then(|object| object.get("$schema"))
+        .and_then(Json::as_string);
+    let (Some(uri), Draft::Draft201909 | Draft::Draft202012) = (uri, draft) else {
+        return Ok((draft.default_vocabularies(), Vec::new()));
+    };
+    let uri = uri.as_ref();
+    let meta_schema = match builder
+        .meta_schemas
+        .get(uri)
+        .or_else(|| builder.meta_schemas.get(uri.trim_end_matches('#')))
+    {
+        Some(meta_schema) => Some(meta_schema),
+        None => resolve(uri),
+    };
+    let declared: Vec<(&str, bool)> = if let Some(declared) = meta_schema
+        .and_then(Json::as_object)
+        .and_then(|object| object.get("$vocabulary"))
+        .and_then(Json::as_object)
+    {
+        declared
+            .iter()
+            .map(|(uri, required)| Ok((uri?.as_ref(), required.as_boolean() == Some(true))))
+            .collect::<BuildResult<_>>()?
+    } else {
+        match from_url(uri) {
+            Some(Draft::Draft201909) if meta_schema.is_none() => DRAFT201909_VOCABULARIES.to_vec(),
+            Some(Draft::Draft202012) if meta_schema.is_none() => DRAFT202012_VOCABULARIES.to_vec(),
+            _ => return Ok((draft.default_vocabularies(), Vec::new())),
+        }
+    };
+    let mut vocabularies = VocabularySet::empty().with(Vocabulary::Core);
+    let mut custom = Vec::new();
+    for (uri, required) in declared {
+        if let Some(vocabulary) = Vocabulary::from_uri(uri) {
+            vocabularies.insert(vocabulary);
+            // `unevaluated*` keywords are a part of the applicator vocabulary before 2020-12
+            if uri == "https://json-schema.org/draft/2019-09/vocab/applicator" {
+                vocabularies.insert(Vocabulary::Unevaluated);
+            }
+        } else if let Some(vocabulary) = builder.vocabularies.get(uri) {
+            custom.push(vocabulary);
+        } else if required {
+            return Err(BuildError::unknown_vocabulary(uri));
         }
     }
+    Ok((vocabularies, custom))
 }
 
 pub(crate) fn from_url(mut url: &str) -> Option<Draft> {
@@ -0,0 +1,656 @@
+//! ECMA-262 regular expressions.
+//!
+//! Patterns are parsed using the grammar for the `u` flag, which does not include the lenient
+//! extensions from Annex B, e.g. lone `{` is a syntax error. The exceptions are `\-` outside of
+//! classes, which is common in schemas, and identity escapes like `\a` if they are enabled. Valid
+//! patterns are then translated to the syntax of the `regex` crate, keeping the ECMA-262
+//! semantics, e.g. `\d` only matches ASCII digits. Lookarounds and backreferences are not
+//! supported by `regex`, so such patterns need a backtracking engine.
+use core::fmt;
+
+/// A syntax error within an ECMA-262 regular expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EcmaRegexError {
+    pub(crate) message: &'static str,
+    /// Byte offset within the pattern.
+    pub(crate) position: usize,
+}
+
+impl fmt::Display for EcmaRegexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for EcmaRegexError {}
+
+/// An ECMA-262 pattern translated to the `regex` syntax.
+#[derive(Debug)]
+pub(crate) struct Translation {
+    pub(crate) pattern: String,
+    /// The first lookaround or backreference, which requires a backtracking engine.
+    pub(crate) backtracking: Option<EcmaRegexError>,
+    /// A valid construct that can not be represented in the translated pattern.
+    pub(crate) unsupported: Option<EcmaRegexError>,
+}
+
+/// Parse an ECMA-262 pattern and translate it to the `regex` syntax.
+///
+/// With `identity_escapes`, an escaped character without a special meaning matches itself.
+pub(crate) fn translate(
+    pattern: &str,
+    identity_escapes: bool,
+) -> Result<Translation, EcmaRegexError> {
+    Parser::new(pattern, identity_escapes).parse()
+}
+
+/// `.` does not match line terminators.
+const DOT: &str = r"[^\n\r\x{2028}\x{2029}]";
+const DIGIT: &str = "0-9";
+const WORD: &str = "0-9A-Za-z_";
+const SPACE: &str = r"\t\n\x0B\x0C\r \x{A0}\x{1680}\x{2000}-\x{200A}\x{2028}\x{2029}\x{202F}\x{205F}\x{3000}\x{FEFF}";
+
+/// Output that depends on the whole pattern, so it is written once parsing is done.
+enum Deferred<'p> {
+    NamedBackreference {
+        name: &'p str,
+        position: usize,
+    },
+    /// Word characters are ASCII-only, which `fancy-regex` can express only via lookarounds.
+    WordBoundary {
+        negated: bool,
+    },
+}
+
+/// An atom within a character class.
+enum ClassAtom {
+    Char(u32),
+    /// Escapes like `\d` or `\p{L}` which can not be range bounds.
+    Class,
+}
+
+struct Parser<'p> {
+    pattern: &'p str,
+    position: usize,
+    output: String,
+    groups: usize,
+    group_names: Vec<(&'p str, usize)>,
+    backreferences: Vec<(usize, usize)>,
+    /// Ordered by their positions in the output.
+    deferred: Vec<(usize, Deferred<'p>)>,
+    backtracking: Option<EcmaRegexError>,
+    unsupported: Option<EcmaRegexError>,
+    identity_escapes: bool,
+}
+
+impl<'p> Parser<'p> {
+    fn new(pattern: &'p str, identity_escapes: bool) -> Parser<'p> {
+        Parser {
+            pattern,
+            position: 0,
+            output: String::with_capacity(pattern.len()),
+            groups: 0,
+            group_names: Vec::new(),
+            backreferences: Vec::new(),
+            deferred: Vec::new(),
+            backtracking: None,
+            unsupported: None,
+            identity_escapes,
+        }
+    }
+
+    fn error<T>(&self, message: &'static str) -> Result<T, EcmaRegexError> {
+        self.error_at(message, self.position)
+    }
+
+    fn error_at<T>(&self, message: &'static str, position: usize) -> Result<T, EcmaRegexError> {
+        Err(EcmaRegexError { message, position })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.pattern[self.position..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.position += ch.len_utf8();
+        Some(ch)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: char, message: &'static str) -> Result<(), EcmaRegexError> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            self.error(message)
+        }
+    }
+
+    /// Write a single code point, escaped if needed.
+    fn push_code(&mut self, code: u32, position: usize) {
+        match char::from_u32(code) {
+            Some(ch) if ch.is_alphanumeric() || !ch.is_ascii() && !ch.is_whitespace() => {
+                self.output.push(ch);
+            }
+            Some(_) => {
+                self.output.push_str(&format!("\\x{{{code:X}}}"));
+            }
+            None => {
+                self.unsupported.get_or_insert(EcmaRegexError {
+                    message: "Lone surrogates are not supported",
+                    position,
+                });
+            }
+        }
+    }
+
+    fn requires_backtracking(&mut self, message: &'static str, position: usize) {
+        self.backtracking
+            .get_or_insert(EcmaRegexError { message, position });
+    }
+
+    fn parse(mut self) -> Result<Translation, EcmaRegexError> {
+        self.disjunction()?;
+        if self.peek() == Some(')') {
+            return self.error("Unmatched ')'");
+        }
+        for (number, position) in &self.backreferences {
+            if *number > self.groups {
+                return self.error_at("Invalid backreference", *position);
+            }
+        }
+        for (output, deferred) in self.deferred.iter().rev() {
+            let insertion = match deferred {
+                // Named groups are translated to plain ones, so references become numbered ones
+                Deferred::NamedBackreference { name, position } => {
+                    let Some((_, index)) = self.group_names.iter().find(|(group, _)| group == name)
+                    else {
+                        return self.error_at("Invalid named backreference", *position);
+                    };
+                    format!("\\k<{index}>")
+                }
+                Deferred::WordBoundary { negated } => {
+                    // Whether the characters on both sides are word characters or not
+                    let (same, different) = if *negated { ("=", "!") } else { ("!", "=") };
+                    match (self.backtracking.is_some(), negated) {
+                        (false, false) => r"(?-u:\b)".to_owned(),
+                        (false, true) => r"(?-u:\B)".to_owned(),
+                        (true, _) => format!(
+                            "(?:(?<=[{WORD}])(?{same}[{WORD}])|(?<![{WORD}])(?{different}[{WORD}]))"
+                        ),
+                    }
+                }
+            };
+            self.output.insert_str(*output, &insertion);
+        }
+        Ok(Translation {
+            pattern: self.output,
+            backtracking: self.backtracking,
+            unsupported: self.unsupported,
+        })
+    }
+
+    fn disjunction(&mut self) -> Result<(), EcmaRegexError> {
+        loop {
+            while !matches!(self.peek(), None | Some('|' | ')')) {
+                self.term()?;
+            }
+            if !self.eat('|') {
+                return Ok(());
+            }
+            self.output.push('|');
+        }
+    }
+
+    fn term(&mut self) -> Result<(), EcmaRegexError> {
+        let start = self.position;
+        let quantifiable = match self.next().expect("Checked by the caller") {
+            ch @ ('^' | '$') => {
+                self.output.push(ch);
+                false
+            }
+            '\\' => match self.peek() {
+                Some(ch @ ('b' | 'B')) => {
+                    self.next();
+                    let negated = ch == 'B';
+                    self.deferred
+                        .push((self.output.len(), Deferred::WordBoundary { negated }));
+                    false
+                }
+                _ => {
+                    self.atom_escape(start)?;
+                    true
+                }
+            },
+            '(' => self.group(start)?,
+            '[' => {
+                self.class(start)?;
+                true
+            }
+            '.' => {
+                self.output.push_str(DOT);
+                true
+            }
+            '*' | '+' | '?' | '{' => return self.error_at("Nothing to repeat", start),
+            ']' | '}' => return self.error_at("Lone quantifier brackets", start),
+            ch => {
+                self.push_code(u32::from(ch), start);
+                true
+            }
+        };
+        if quantifiable {
+            self.quantifier()
+        } else if matches!(self.peek(), Some('*' | '+' | '?' | '{')) {
+            self.error("Nothing to repeat")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Parse a group after `(`, return whether it can be quantified.
+    fn group(&mut self, start: usize) -> Result<bool, EcmaRegexError> {
+        let mut quantifiable = true;
+        if self.eat('?') {
+            match self.next() {
+                Some(':') => self.output.push_str("(?:"),
+                // Lookarounds can not be quantified with the `u` flag
+                Some(ch @ ('=' | '!')) => {
+                    quantifiable = false;
+                    self.requires_backtracking("Lookarounds are not supported", start);
+                    self.output.push_str("(?");
+                    self.output.push(ch);
+                }
+                Some('<') if matches!(self.peek(), Some('=' | '!')) => {
+                    quantifiable = false;
+                    self.requires_backtracking("Lookarounds are not supported", start);
+                    self.output.push_str("(?<");
+                    let kind = self.next().expect("Checked above");
+                    self.output.push(kind);
+                }
+                Some('<') => {
+                    let name = self.group_name()?;
+                    if self.group_names.iter().any(|(group, _)| *group == name) {
+                        return self.error_at("Duplicate capture group name", start);
+                    }
+                    self.groups += 1;
+                    self.group_names.push((name, self.groups));
+                    self.output.push('(');
+                }
+                _ => return self.error_at("Invalid group", start),
+            }
+        } else {
+            self.groups += 1;
+            self.output.push('(');
+        }
+        self.disjunction()?;
+        if !self.eat(')') {
+            return self.error_at("Unterminated group", start);
+        }
+        self.output.push(')');
+        Ok(quantifiable)
+    }
+
+    /// Parse a group name after `<` including the closing `>`.
+    fn group_name(&mut self) -> Result<&'p str, EcmaRegexError> {
+        let start = self.position;
+        match self.next() {
+            Some(ch) if ch.is_alphabetic() || ch == '$' || ch == '_' => {}
+            _ => return self.error_at("Invalid capture group name", start),
+        }
+        while let Some(ch) = self.peek() {
+            if ch.is_alphanumeric() || matches!(ch, '$' | '_' | '\u{200C}' | '\u{200D}') {
+                self.next();
+            } else {
+                break;
+            }
+        }
+        let name = &self.pattern[start..self.position];
+        self.expect('>', "Invalid capture group name")?;
+        Ok(name)
+    }
+
+    fn quantifier(&mut self) -> Result<(), EcmaRegexError> {
+        let start = self.position;
+        match self.peek() {
+            Some('*' | '+' | '?') => {
+                self.next();
+            }
+            Some('{') => {
+                self.next();
+                let Some(min) = self.decimal() else {
+                    return self.error_at("Incomplete quantifier", start);
+                };
+                if self.eat(',') {
+                    if let Some(max) = self.decimal() {
+                        if max < min {
+                            return self.error_at("Numbers out of order in {} quantifier", start);
+                        }
+                    }
+                }
+                if !self.eat('}') {
+                    return self.error_at("Incomplete quantifier", start);
+                }
+            }
+            _ => return Ok(()),
+        }
+        self.eat('?');
+        self.output.push_str(&self.pattern[start..self.position]);
+        Ok(())
+    }
+    fn decimal(&mut self) -> Option<u64> {
+        let start = self.position;
+        while self.peek().is_some_and(|ch| ch.is_ascii_digit()) {
+            self.next();
+        }
+        let digits = &self.pattern[start..self.position];
+        // Values that overflow are still syntactically valid
+        (!digits.is_empty()).then(|| digits.parse().unwrap_or(u64::MAX))
+    }
+
+    fn hex(&mut self, length: usize) -> Option<u32> {
+        let digits = self.pattern.get(self.position..self.position + length)?;
+        if !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return None;
+        }
+        self.position += length;
+        u32::from_str_radix(digits, 16).ok()
+    }
+
+    /// Parse an escape within or outside a class after `\`, except for escapes that differ.
+    fn character_escape(&mut self, start: usize) -> Result<u32, EcmaRegexError> {
+        let code = match self.next() {
+            None => return self.error_at("\\ at end of pattern", start),
+            Some('f') => 0x0C,
+            Some('n') => 0x0A,
+            Some('r') => 0x0D,
+            Some('t') => 0x09,
+            Some('v') => 0x0B,
+            Some('c') => match self.next() {
+                Some(ch) if ch.is_ascii_alphabetic() => u32::from(ch) % 32,
+                _ => return self.error_at("Invalid unicode escape", start),
+            },
+            Some('0') => {
+                if self.peek().is_some_and(|ch| ch.is_ascii_digit()) {
+                    return self.error_at("Invalid decimal escape", start);
+                }
+                0
+            }
+            Some('x') => match self.hex(2) {
+                Some(code) => code,
+                None => return self.error_at("Invalid escape", start),
+            },
+            Some('u') => self.unicode_escape(start)?,
+            Some(
+                ch @ ('^' | '$' | '\\' | '.' | '*' | '+' | '?' | '(' | ')' | '[' | ']' | '{' | '}'
+                | '|' | '/' | '-'),
+            ) => u32::from(ch),
+            Some(ch) if self.identity_escapes => u32::from(ch),
+            Some(_) => return self.error_at("Invalid escape", start),
+        };
+        Ok(code)
+    }
+    /// Parse `\u{...}` or `\uXXXX`, possibly followed by a trailing surrogate.
+    fn unicode_escape(&mut self, start: usize) -> Result<u32, EcmaRegexError> {
+        if self.eat('{') {
+            let digits_start = self.position;
+            while self.peek().is_some_and(|ch| ch.is_ascii_hexdigit()) {
+                self.next();
+            }
+            let code = u32::from_str_radix(&self.pattern[digits_start..self.position], 16);
+            return match code {
+                Ok(code) if code <= 0x10FFFF && self.eat('}') => Ok(code),
+                _ => self.error_at("Invalid unicode escape", start),
+            };
+        }
+        let Some(code) = self.hex(4) else {
+            return self.error_at("Invalid unicode escape", start);
+        };
+        if (0xD800..=0xDBFF).contains(&code) && self.pattern[self.position..].starts_with("\\u") {
+            let position = self.position;
+            self.position += 2;
+            match self.hex(4) {
+                Some(trail) if (0xDC00..=0xDFFF).contains(&trail) => {
+                    return Ok(0x10000 + ((code - 0xD800) << 10) + (trail - 0xDC00));
+                }
+                _ => self.position = position,
+            }
+        }
+        Ok(code)
+    }
+
+    /// Parse `{Name}` or `{Name=Value}` after `\p` or `\P`.
+    fn property(&mut self, start: usize) -> Result<(), EcmaRegexError> {
+        if !self.eat('{') {
+            return self.error_at("Invalid property name", start);
+        }
+        let name_start = self.position;
+        while self
+            .peek()
+            .is_some_and(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '=')
+        {
+            self.next();
+        }
+        let name = &self.pattern[name_start..self.position];
+        let is_valid = !name.is_empty()
+            && name.split('=').count() <= 2
+            && name.split('=').all(|part| !part.is_empty());
+        if !is_valid || !self.eat('}') {
+            return self.error_at("Invalid property name", start);
+        }
+        Ok(())
+    }
+
+    /// Parse an escape outside of a class after `\`.
+    fn atom_escape(&mut self, start: usize) -> Result<(), EcmaRegexError> {
+        match self.peek() {
+            Some('1'..='9') => {
+                let number = self.decimal().expect("At least one digit");
+                let number = usize::try_from(number).unwrap_or(usize::MAX);
+                self.backreferences.push((number, start));
+                self.requires_backtracking("Backreferences are not supported", start);
+                self.output.push_str(&format!("\\k<{number}>"));
+            }
+            Some(ch @ ('d' | 'D' | 's' | 'S' | 'w' | 'W')) => {
+                self.next();
+                let negated = if ch.is_ascii_uppercase() { "^" } else { "" };
+                let class = class_escape(ch);
+                self.output.push_str(&format!("[{negated}{class}]"));
+            }
+            Some('p' | 'P') => {
+                self.next();
+                self.property(start)?;
+                self.output.push_str(&self.pattern[start..self.position]);
+            }
+            Some('k') => {
+                self.next();
+                self.expect('<', "Invalid named reference")?;
+                let name = self.group_name()?;
+                let deferred = Deferred::NamedBackreference {
+                    name,
+                    position: start,
+                };
+                self.deferred.push((self.output.len(), deferred));
+                self.requires_backtracking("Backreferences are not supported", start);
+            }
+            _ => {
+                let code = self.character_escape(start)?;
+                self.push_code(code, start);
+            }
+        }
+        Ok(())
+    }
+
+    fn class_atom(&mut self) -> Result<ClassAtom, EcmaRegexError> {
+        let start = self.position;
+        match self.next() {
+            None => self.error("Unterminated character class"),
+            Some('\\') => match self.peek() {
+                Some('b') => {
+                    self.next();
+                    Ok(ClassAtom::Char(0x08))
+                }
+                Some('-') => {
+                    self.next();
+                    Ok(ClassAtom::Char(u32::from('-')))
+                }
+                Some(ch @ ('d' | 'D' | 's' | 'S' | 'w' | 'W')) => {
+                    self.next();
+                    let class = class_escape(ch);
+                    if ch.is_ascii_uppercase() {
+                        self.output.push_str(&format!("[^{class}]"));
+                    } else {
+                        self.output.push_str(class);
+                    }
+                    Ok(ClassAtom::Class)
+                }
+                Some('p' | 'P') => {
+                    self.next();
+                    self.property(start)?;
+                    self.output.push_str(&self.pattern[start..self.position]);
+                    Ok(ClassAtom::Class)
+                }
+                Some('1'..='9') => self.error_at("Invalid class escape", start),
+                _ => {
+                    let code = self.character_escape(start)?;
+                    self.push_code(code, start);
+                    Ok(ClassAtom::Char(code))
+                }
+            },
+            Some(ch) => {
+                self.push_code(u32::from(ch), start);
+                Ok(ClassAtom::Char(u32::from(ch)))
+            }
+        }
+    }
+
+    /// Parse a character class after `[`.
+    fn class(&mut self, start: usize) -> Result<(), EcmaRegexError> {
+        let negated = self.eat('^');
+        if self.eat(']') {
+            // `[]` never matches and `[^]` matches any code point
+            self.output.push_str(if negated {
+                r"[\x{0}-\x{10FFFF}]"
+            } else {
+                r"[^\x{0}-\x{10FFFF}]"
+            });
+            return Ok(());
+        }
+        self.output.push_str(if negated { "[^" } else { "[" });
+        loop {
+            match self.peek() {
+                None => return self.error_at("Unterminated character class", start),
+                Some(']') => {
+                    self.next();
+                    self.output.push(']');
+                    return Ok(());
+                }
+                _ => {}
+            }
+            let range_start = self.position;
+            let low = self.class_atom()?;
+            if self.peek() != Some('-') || self.pattern[self.position + 1..].starts_with(']') {
+                continue;
+            }
+            self.next();
+            self.output.push('-');
+            let high = self.class_atom()?;
+            match (low, high) {
+                (ClassAtom::Char(low), ClassAtom::Char(high)) if low > high => {
+                    return self.error_at("Range out of order in character class", range_start);
+                }
+                (ClassAtom::Char(_), ClassAtom::Char(_)) => {}
+                _ => return self.error_at("Invalid character class", range_start),
+            }
+        }
+    }
+}
+
+/// The contents of a character class for `\d`, `\s` or `\w` and their negations.
+fn class_escape(ch: char) -> &'static str {
+    match ch.to_ascii_lowercase() {
+        'd' => DIGIT,
+        's' => SPACE,
+        _ => WORD,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("([abc])+\\s+$")]
+    #[test_case("^[a-z]{1,10}?$")]
+    #[test_case("^\\p{Letter}+$")]
+    #[test_case("\\p{Script=Greek}")]
+    #[test_case("(?<year>\\d{4})-\\k<year>")]
+    #[test_case("(a)\\1")]
+    #[test_case("(?=a)(?!b)(?<=c)(?<!d)")]
+    #[test_case("[\\w-]")]
+    #[test_case("[a-]")]
+    #[test_case("\\u{1F600}|\\uD83D\\uDE00|\\x41|\\cJ|\\0")]
+    #[test_case("\\/\\^\\$")]
+    #[test_case("^a\\-b$")]
+    #[test_case("a{2,}")]
+    #[test_case("")]
+    fn test_valid(pattern: &str) {
+        assert!(translate(pattern, false).is_ok());
+    }
+
+    #[test_case("^(abc]", "Lone quantifier brackets", 5)]
+    #[test_case("^(abc", "Unterminated group", 1)]
+    #[test_case("\\a", "Invalid escape", 0)]
+    #[test_case("abc)", "Unmatched ')'", 3)]
+    #[test_case("[abc", "Unterminated character class", 0)]
+    #[test_case("*a", "Nothing to repeat", 0)]
+    #[test_case("a**", "Nothing to repeat", 2)]
+    #[test_case("^*", "Nothing to repeat", 1)]
+    #[test_case("(?=a)*", "Nothing to repeat", 5)]
+    #[test_case("a{", "Incomplete quantifier", 1)]
+    #[test_case("a{2,1}", "Numbers out of order in {} quantifier", 1)]
+    #[test_case("a}", "Lone quantifier brackets", 1)]
+    #[test_case("[z-a]", "Range out of order in character class", 1)]
+    #[test_case("[\\d-z]", "Invalid character class", 1)]
+    #[test_case("(a)\\2", "Invalid backreference", 3)]
+    #[test_case("\\k<name>", "Invalid named backreference", 0)]
+    #[test_case("(?<a>x)(?<a>y)", "Duplicate capture group name", 7)]
+    #[test_case("(?x)", "Invalid group", 0)]
+    #[test_case("\\p{}", "Invalid property name", 0)]
+    #[test_case("\\u{110000}", "Invalid unicode escape", 0)]
+    #[test_case("\\01", "Invalid decimal escape", 0)]
+    #[test_case("abc\\", "\\ at end of pattern", 3)]
+    fn test_invalid(pattern: &str, message: &str, position: usize) {
+        let error = translate(pattern, false).expect_err("Should fail");
+        assert_eq!(error.message, message);
+        assert_eq!(error.position, position);
+    }
+
+    #[test_case("\\a")]
+    #[test_case("[\\a-\\z]")]
+    #[test_case("\\_\\@")]
+    fn test_identity_escapes(pattern: &str) {
+        let error = translate(pattern, false).expect_err("Should fail");
+        assert_eq!(error.message, "Invalid escape");
+        assert!(translate(pattern, true).is_ok());
+    }
+
+    #[test_case("(?=a)\\1(a)", "Lookarounds are not supported", 0)]
+    #[test_case("(a)(?<!a)", "Lookarounds are not supported", 3)]
+    #[test_case("(a)\\1", "Backreferences are not supported", 3)]
+    #[test_case("(?<a>a)\\k<a>", "Backreferences are not supported", 7)]
+    fn test_backtracking(pattern: &str, message: &'static str, position: usize) {
+        let translation = translate(pattern, false).expect("Valid pattern");
+        assert_eq!(
+            translation.backtracking,
+            Some(EcmaRegexError { message, position })
+        );
+    }
+}
@@ -1,3 +1,7 @@
+use jpointer::{JsonPointer, JsonPointerNode};
+
+use crate::{ecma::EcmaRegexError, vocabulary::PrimitiveType};
+
 /// An error that occured during the building of a validator.
 #[derive(Debug)]
 pub struct BuildError {
@@ -7,12 +11,177 @@ pub struct BuildError {
 #[derive(Debug)]
 enum BuildErrorKind {
     Json(jsonlike::JsonError),
+    InvalidSchema,
+    InvalidKeyword {
+        keyword: String,
+        expected: &'static str,
+    },
+    InvalidRegex {
+        pattern: String,
+        error: Box<dyn std::error::Error + Send + Sync>,
+    },
+    UnsupportedRegex {
+        pattern: String,
+        error: EcmaRegexError,
+    },
+    UnresolvableReference {
+        reference: String,
+    },
+    UnknownVocabulary {
+        uri: String,
+    },
+    UnknownFormat {
+        format: String,
+    },
+    InvalidSnapshot,
+    IncompatibleSnapshot {
+        version: String,
+    },
+    NotSerializable {
+        keyword: String,
+    },
+    RequiresLookahead {
+        keyword: String,
+    },
+    UnsupportedKeyword {
+        keyword: String,
+        reason: &'static str,
+    },
+}
+
+impl BuildError {
+    pub(crate) fn invalid_schema() -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::InvalidSchema,
+        }
+    }
+    pub(crate) fn invalid_keyword(
+        keyword: impl Into<String>,
+        expected: &'static str,
+    ) -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::InvalidKeyword {
+                keyword: keyword.into(),
+                expected,
+            },
+        }
+    }
+    pub(crate) fn invalid_regex(
+        pattern: impl Into<String>,
+        error: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::InvalidRegex {
+                pattern: pattern.into(),
+                error: error.into(),
+            },
+        }
+    }
+    pub(crate) fn unsupported_regex(
+        pattern: impl Into<String>,
+        error: EcmaRegexError,
+    ) -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::UnsupportedRegex {
+                pattern: pattern.into(),
+                error,
+            },
+        }
+    }
+    pub(crate) fn unresolvable_reference(reference: impl Into<String>) -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::UnresolvableReference {
+                reference: reference.into(),
+            },
+        }
+    }
+    pub(crate) fn unknown_vocabulary(uri: impl Into<String>) -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::UnknownVocabulary { uri: uri.into() },
+        }
+    }
+    pub(crate) fn unknown_format(format: impl Into<String>) -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::UnknownFormat {
+                format: format.into(),
+            },
+        }
+    }
+    pub(crate) fn invalid_snapshot() -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::InvalidSnapshot,
+        }
+    }
+    pub(crate) fn incompatible_snapshot(version: impl Into<String>) -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::IncompatibleSnapshot {
+                version: version.into(),
+            },
+        }
+    }
+    pub(crate) fn not_serializable(keyword: impl Into<String>) -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::NotSerializable {
+                keyword: keyword.into(),
+            },
+        }
+    }
+    pub(crate) fn requires_lookahead(keyword: impl Into<String>) -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::RequiresLookahead {
+                keyword: keyword.into(),
+            },
+        }
+    }
+    pub(crate) fn unsupported_keyword(
+        keyword: impl Into<String>,
+        reason: &'static str,
+    ) -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::UnsupportedKeyword {
+                keyword: keyword.into(),
+                reason,
+            },
+        }
+    }
 }
 
 impl core::fmt::Display for BuildError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match &self.kind {
             BuildErrorKind::Json(error) => error.fmt(f),
+            BuildErrorKind::InvalidSchema => f.write_str("Schema must be an object or a boolean"),
+            BuildErrorKind::InvalidKeyword { keyword, expected } => {
+                write!(f, "'{keyword}' must be {expected}")
+            }
+            BuildErrorKind::InvalidRegex { pattern, error } => {
+                write!(f, "'{pattern}' is not a valid regular expression: {error}")
+            }
+            BuildErrorKind::UnsupportedRegex { pattern, error } => {
+                write!(f, "'{pattern}' is not supported: {error}")
+            }
+            BuildErrorKind::UnresolvableReference { reference } => {
+                write!(f, "Unresolvable reference: '{reference}'")
+            }
+            BuildErrorKind::UnknownVocabulary { uri } => {
+                write!(f, "Unknown required vocabulary: '{uri}'")
+            }
+            BuildErrorKind::UnknownFormat { format } => write!(f, "Unknown format: '{format}'"),
+            BuildErrorKind::InvalidSnapshot => f.write_str("Invalid validator snapshot"),
+            BuildErrorKind::IncompatibleSnapshot { version } => write!(
+                f,
+                "Snapshot was created by version {version}, expected {}",
+                env!("CARGO_PKG_VERSION")
+            ),
+            BuildErrorKind::NotSerializable { keyword } => {
+                write!(f, "'{keyword}' can not be serialized")
+            }
+            BuildErrorKind::RequiresLookahead { keyword } => {
+                write!(f, "'{keyword}' can not be evaluated in a single pass")
+            }
+            BuildErrorKind::UnsupportedKeyword { keyword, reason } => {
+                write!(f, "'{keyword}' is not supported: {reason}")
+            }
         }
     }
 }
@@ -21,6 +190,9 @@ impl std::error::Error for BuildError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match &self.kind {
             BuildErrorKind::Json(error) => Some(error),
+            BuildErrorKind::InvalidRegex { error, .. } => Some(error.as_ref()),
+            BuildErrorKind::UnsupportedRegex { error, .. } => Some(error),
+            _ => None,
         }
     }
 }
@@ -35,14 +207,284 @@ impl From<jsonlike::JsonError> for BuildError {
 
 /// An error that occured during JSON Schema validation.
 #[derive(Clone, Debug)]
-pub struct ValidationError(Box<ValidationErrorKind>);
+pub struct ValidationError(Box<ValidationErrorInner>);
 
 #[derive(Clone, Debug)]
-pub enum ValidationErrorKind {}
+struct ValidationErrorInner {
+    kind: ValidationErrorKind,
+    instance_location: JsonPointer,
+    evaluation_path: JsonPointer,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ValidationErrorKind {
+    FalseSchema,
+    Type {
+        expected: Vec<PrimitiveType>,
+    },
+    Const {
+        expected: String,
+    },
+    Enum {
+        options: String,
+    },
+    MultipleOf {
+        multiple_of: f64,
+    },
+    Minimum {
+        limit: f64,
+    },
+    Maximum {
+        limit: f64,
+    },
+    ExclusiveMinimum {
+        limit: f64,
+    },
+    ExclusiveMaximum {
+        limit: f64,
+    },
+    MinLength {
+        limit: u64,
+    },
+    MaxLength {
+        limit: u64,
+    },
+    Pattern {
+        pattern: String,
+    },
+    Format {
+        format: String,
+    },
+    ContentEncoding {
+        encoding: String,
+    },
+    ContentMediaType {
+        media_type: String,
+    },
+    MinItems {
+        limit: u64,
+    },
+    MaxItems {
+        limit: u64,
+    },
+    UniqueItems,
+    Contains,
+    MinContains {
+        limit: u64,
+    },
+    MaxContains {
+        limit: u64,
+    },
+    MinProperties {
+        limit: u64,
+    },
+    MaxProperties {
+        limit: u64,
+    },
+    Required {
+        property: String,
+    },
+    DependentRequired {
+        property: String,
+        dependency: String,
+    },
+    AdditionalProperties {
+        unexpected: Vec<String>,
+    },
+    UnevaluatedProperties {
+        unexpected: Vec<String>,
+    },
+    UnevaluatedItems {
+        unexpected: Vec<usize>,
+    },
+    AnyOf,
+    OneOfNotValid,
+    OneOfMultipleValid,
+    Not,
+    Custom {
+        keyword: String,
+    },
+    CustomMessage {
+        keyword: String,
+        message: String,
+    },
+    /// A line of newline-delimited input is not a JSON document.
+    InvalidJson {
+        message: String,
+    },
+    /// Reading newline-delimited input failed, no further lines are read.
+    Read {
+        message: String,
+    },
+    /// A synchronous method reached a keyword or format that is checked asynchronously.
+    RequiresAsync,
+}
+
+impl ValidationError {
+    pub(crate) fn new(
+        kind: ValidationErrorKind,
+        instance_location: &JsonPointerNode,
+        evaluation_path: &JsonPointerNode,
+    ) -> ValidationError {
+        ValidationError(Box::new(ValidationErrorInner {
+            kind,
+            instance_location: instance_location.into(),
+            evaluation_path: evaluation_path.into(),
+        }))
+    }
+    pub(crate) fn requires_async() -> ValidationError {
+        ValidationError::new(
+            ValidationErrorKind::RequiresAsync,
+            &JsonPointerNode::new(),
+            &JsonPointerNode::new(),
+        )
+    }
+    /// The kind of this error.
+    pub fn kind(&self) -> &ValidationErrorKind {
+        &self.0.kind
+    }
+    /// Location of the invalid value within the instance.
+    pub fn instance_location(&self) -> &JsonPointer {
+        &self.0.instance_location
+    }
+    /// Path to the failed keyword, following references.
+    pub fn evaluation_path(&self) -> &JsonPointer {
+        &self.0.evaluation_path
+    }
+}
+
+fn write_list<T: core::fmt::Display>(
+    f: &mut core::fmt::Formatter<'_>,
+    items: &[T],
+) -> core::fmt::Result {
+    for (idx, item) in items.iter().enumerate() {
+        if idx > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "'{item}'")?;
+    }
+    Ok(())
+}
 
 impl core::fmt::Display for ValidationError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        todo!()
+        match self.kind() {
+            ValidationErrorKind::FalseSchema => {
+                f.write_str("False schema does not allow any value")
+            }
+            ValidationErrorKind::Type { expected } => {
+                f.write_str("Value is not of type ")?;
+                write_list(f, expected)
+            }
+            ValidationErrorKind::Const { expected } => write!(f, "{expected} was expected"),
+            ValidationErrorKind::Enum { options } => write!(f, "Value is not one of {options}"),
+            ValidationErrorKind::MultipleOf { multiple_of } => {
+                write!(f, "Value is not a multiple of {multiple_of}")
+            }
+            ValidationErrorKind::Minimum { limit } => {
+                write!(f, "Value is less than the minimum of {limit}")
+            }
+            ValidationErrorKind::Maximum { limit } => {
+                write!(f, "Value is greater than the maximum of {limit}")
+            }
+            ValidationErrorKind::ExclusiveMinimum { limit } => {
+                write!(f, "Value is less than or equal to the minimum of {limit}")
+            }
+            ValidationErrorKind::ExclusiveMaximum { limit } => {
+                write!(
+                    f,
+                    "Value is greater than or equal to the maximum of {limit}"
+                )
+            }
+            ValidationErrorKind::MinLength { limit } => {
+                write!(f, "Value is shorter than {limit} characters")
+            }
+            ValidationErrorKind::MaxLength { limit } => {
+                write!(f, "Value is longer than {limit} characters")
+            }
+            ValidationErrorKind::Pattern { pattern } => {
+                write!(f, "Value does not match '{pattern}'")
+            }
+            ValidationErrorKind::Format { format } => {
+                write!(f, "Value is not a valid '{format}'")
+            }
+            ValidationErrorKind::ContentEncoding { encoding } => {
+                write!(f, "Value is not encoded as '{encoding}'")
+            }
+            ValidationErrorKind::ContentMediaType { media_type } => {
+                write!(f, "Value is not a valid '{media_type}' document")
+            }
+            ValidationErrorKind::MinItems { limit } => {
+                write!(f, "Value has less than {limit} items")
+            }
+            ValidationErrorKind::MaxItems { limit } => {
+                write!(f, "Value has more than {limit} items")
+            }
+            ValidationErrorKind::UniqueItems => f.write_str("Value has non-unique elements"),
+            ValidationErrorKind::Contains => {
+                f.write_str("None of the items are valid under the given schema")
+            }
+            ValidationErrorKind::MinContains { limit } => {
+                write!(
+                    f,
+                    "Less than {limit} items are valid under the given schema"
+                )
+            }
+            ValidationErrorKind::MaxContains { limit } => {
+                write!(
+                    f,
+                    "More than {limit} items are valid under the given schema"
+                )
+            }
+            ValidationErrorKind::MinProperties { limit } => {
+                write!(f, "Value has less than {limit} properties")
+            }
+            ValidationErrorKind::MaxProperties { limit } => {
+                write!(f, "Value has more than {limit} properties")
+            }
+            ValidationErrorKind::Required { property } => {
+                write!(f, "'{property}' is a required property")
+            }
+            ValidationErrorKind::DependentRequired {
+                property,
+                dependency,
+            } => write!(f, "'{dependency}' is required when '{property}' is present"),
+            ValidationErrorKind::AdditionalProperties { unexpected } => {
+                f.write_str("Additional properties are not allowed (")?;
+                write_list(f, unexpected)?;
+                f.write_str(" unexpected)")
+            }
+            ValidationErrorKind::UnevaluatedProperties { unexpected } => {
+                f.write_str("Unevaluated properties are not allowed (")?;
+                write_list(f, unexpected)?;
+                f.write_str(" unexpected)")
+            }
+            ValidationErrorKind::UnevaluatedItems { unexpected } => {
+                f.write_str("Unevaluated items are not allowed (")?;
+                write_list(f, unexpected)?;
+                f.write_str(" unexpected)")
+            }
+            ValidationErrorKind::AnyOf => {
+                f.write_str("Value is not valid under any of the given schemas")
+            }
+            ValidationErrorKind::OneOfNotValid => {
+                f.write_str("Value is not valid under any of the given schemas")
+            }
+            ValidationErrorKind::OneOfMultipleValid => {
+                f.write_str("Value is valid under more than one of the given schemas")
+            }
+            ValidationErrorKind::Not => f.write_str("Value should not be valid under the schema"),
+            ValidationErrorKind::Custom { keyword } => {
+                write!(f, "Value is not valid under the '{keyword}' keyword")
+            }
+            ValidationErrorKind::CustomMessage { message, .. } => f.write_str(message),
+            ValidationErrorKind::InvalidJson { message } => write!(f, "Invalid JSON: {message}"),
+            ValidationErrorKind::Read { message } => write!(f, "Failed to read input: {message}"),
+            ValidationErrorKind::RequiresAsync => f.write_str(
+                "Asynchronous keywords and formats require the `*_async` methods of `Validator`",
+            ),
+        }
     }
 }
 
@@ -0,0 +1,258 @@
+//! Date & time formats as defined by RFC 3339.
+use super::Format;
+
+pub(crate) struct Date;
+
+impl Format for Date {
+    fn is_valid(&self, value: &str) -> bool {
+        is_valid_date(value)
+    }
+}
+
+pub(crate) struct Time;
+
+impl Format for Time {
+    fn is_valid(&self, value: &str) -> bool {
+        is_valid_time(value)
+    }
+}
+
+pub(crate) struct DateTime;
+
+impl Format for DateTime {
+    fn is_valid(&self, value: &str) -> bool {
+        is_valid_date_time(value)
+    }
+}
+
+pub(crate) struct Duration;
+
+impl Format for Duration {
+    fn is_valid(&self, value: &str) -> bool {
+        is_valid_duration(value)
+    }
+}
+
+/// Parse exactly two ASCII digits.
+fn two_digits(bytes: &[u8]) -> Option<u8> {
+    match bytes {
+        [first, second] if first.is_ascii_digit() && second.is_ascii_digit() => {
+            Some((first - b'0') * 10 + (second - b'0'))
+        }
+        _ => None,
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// `full-date = date-fullyear "-" date-month "-" date-mday`
+fn is_valid_date(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return false;
+    }
+    let (Some(century), Some(year), Some(month), Some(day)) = (
+        two_digits(&bytes[..2]),
+        two_digits(&bytes[2..4]),
+        two_digits(&bytes[5..7]),
+        two_digits(&bytes[8..]),
+    ) else {
+        return false;
+    };
+    let year = u16::from(century) * 100 + u16::from(year);
+    (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month)
+}
+
+/// `full-time = partial-time time-offset`
+///
+/// A leap second is only valid at the end of a UTC day.
+fn is_valid_time(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    if bytes.len() < 9 || bytes[2] != b':' || bytes[5] != b':' {
+        return false;
+    }
+    let (Some(hour), Some(minute), Some(second)) = (
+        two_digits(&bytes[..2]),
+        two_digits(&bytes[3..5]),
+        two_digits(&bytes[6..8]),
+    ) else {
+        return false;
+    };
+    if hour > 23 || minute > 59 || second > 60 {
+        return false;
+    }
+    let mut rest = &bytes[8..];
+    if let Some(fraction) = rest.strip_prefix(b".") {
+        let digits = fraction.iter().take_while(|b| b.is_ascii_digit()).count();
+        if digits == 0 {
+            return false;
+        }
+        rest = &fraction[digits..];
+    }
+    let offset = match rest {
+        [b'Z' | b'z'] => 0,
+        [sign @ (b'+' | b'-'), hours @ .., b':', _, _] if hours.len() == 2 => {
+            let (Some(hours), Some(minutes)) =
+                (two_digits(hours), two_digits(&rest[rest.len() - 2..]))
+            else {
+                return false;
+            };
+            if hours > 23 || minutes > 59 {
+                return false;
+            }
+            let offset = i32::from(hours) * 60 + i32::from(minutes);
+            if *sign == b'+' {
+                offset
+            } else {
+                -offset
+            }
+        }
+        _ => return false,
+    };
+    if second == 60 {
+        let utc = (i32::from(hour) * 60 + i32::from(minute) - offset).rem_euclid(24 * 60);
+        return utc == 23 * 60 + 59;
+    }
+    true
+}
+
+/// `date-time = full-date "T" full-time`
+fn is_valid_date_time(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() > 11
+        && matches!(bytes[10], b'T' | b't')
+        && is_valid_date(&value[..10])
+        && is_valid_time(&value[11..])
+}
+
+/// Check that `value` consists of `1*DIGIT unit` components with consecutive units.
+///
+/// E.g. for `["Y", "M", "D"]` the value may be `1Y2M`, but not `1Y2D`.
+fn is_valid_components(value: &str, units: &[u8]) -> bool {
+    let mut rest = value.as_bytes();
+    let mut next = None;
+    while !rest.is_empty() {
+        let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+        if digits == 0 || digits == rest.len() {
+            return false;
+        }
+        let Some(position) = units.iter().position(|unit| *unit == rest[digits]) else {
+            return false;
+        };
+        if next.is_some_and(|next| next != position) {
+            return false;
+        }
+        next = Some(position + 1);
+        rest = &rest[digits + 1..];
+    }
+    true
+}
+
+/// `duration = "P" (dur-date / dur-time / dur-week)` from RFC 3339 Appendix A.
+fn is_valid_duration(value: &str) -> bool {
+    let Some(rest) = value.strip_prefix('P') else {
+        return false;
+    };
+    let (date, time) = match rest.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (rest, None),
+    };
+    if let (Some(weeks), None) = (date.strip_suffix('W'), time) {
+        return !weeks.is_empty() && weeks.bytes().all(|b| b.is_ascii_digit());
+    }
+    if let Some(time) = time {
+        if time.is_empty() || !is_valid_components(time, b"HMS") {
+            return false;
+        }
+    } else if date.is_empty() {
+        return false;
+    }
+    is_valid_components(date, b"YMD")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("1963-06-19", true)]
+    #[test_case("2020-02-29", true)]
+    #[test_case("2021-02-29", false)]
+    #[test_case("1900-02-29", false)]
+    #[test_case("2000-02-29", true)]
+    #[test_case("2020-04-31", false)]
+    #[test_case("2020-13-01", false)]
+    #[test_case("2020-00-01", false)]
+    #[test_case("2020-01-00", false)]
+    #[test_case("1998-1-20", false)]
+    #[test_case("1963-06-1৪", false)]
+    #[test_case("06/19/1963", false)]
+    fn test_date(value: &str, expected: bool) {
+        assert_eq!(is_valid_date(value), expected);
+    }
+
+    #[test_case("08:30:06Z", true)]
+    #[test_case("08:30:06.283185Z", true)]
+    #[test_case("08:30:06+01:00", true)]
+    #[test_case("08:30:06z", true)]
+    #[test_case("23:59:60Z", true)]
+    #[test_case("22:59:60-01:00", true)]
+    #[test_case("01:29:60+01:30", true)]
+    #[test_case("23:59:60+01:00", false)]
+    #[test_case("23:58:60Z", false)]
+    #[test_case("08:30:06", false)]
+    #[test_case("08:30:06.Z", false)]
+    #[test_case("24:00:00Z", false)]
+    #[test_case("08:60:00Z", false)]
+    #[test_case("08:30:06+24:00", false)]
+    #[test_case("08:30:06+01:60", false)]
+    #[test_case("08:30:06 PST", false)]
+    #[test_case("1২:00:00Z", false)]
+    fn test_time(value: &str, expected: bool) {
+        assert_eq!(is_valid_time(value), expected);
+    }
+
+    #[test_case("1963-06-19T08:30:06.283185Z", true)]
+    #[test_case("1963-06-19t08:30:06z", true)]
+    #[test_case("1998-12-31T23:59:60Z", true)]
+    #[test_case("1963-06-19 08:30:06Z", false)]
+    #[test_case("1963-06-19T08:30:06", false)]
+    #[test_case("1963-06-19", false)]
+    #[test_case("1963-06-1৪T00:00:00Z", false)]
+    fn test_date_time(value: &str, expected: bool) {
+        assert_eq!(is_valid_date_time(value), expected);
+    }
+
+    #[test_case("P4DT12H30M5S", true)]
+    #[test_case("P4Y", true)]
+    #[test_case("PT0S", true)]
+    #[test_case("PT36H", true)]
+    #[test_case("P0D", true)]
+    #[test_case("P1Y2M", true)]
+    #[test_case("P4W", true)]
+    #[test_case("P", false)]
+    #[test_case("PT", false)]
+    #[test_case("P1YT", false)]
+    #[test_case("P1D2H", false)]
+    #[test_case("P2S", false)]
+    #[test_case("P1Y2D", false)]
+    #[test_case("PT1S2M", false)]
+    #[test_case("P1Y2W", false)]
+    #[test_case("P2W1D", false)]
+    #[test_case("4DT12H30M5S", false)]
+    #[test_case("P১Y", false)]
+    fn test_duration(value: &str, expected: bool) {
+        assert_eq!(is_valid_duration(value), expected);
+    }
+}
@@ -0,0 +1,215 @@
+mod datetime;
+mod network;
+mod syntax;
+mod uri;
+
+use std::sync::Arc;
+
+use jsonlike::Json;
+
+use crate::{
+    vocabulary::{
+        custom::{
+            sealed::{ContextAndSchema, Schema},
+            Erased,
+        },
+        CompileContext,
+    },
+    BoxFuture, BoxedAsyncFormat, BoxedFormat, BuildResult, Draft, RegexEngine,
+};
+
+pub trait Format: Send + Sync + 'static {
+    fn is_valid(&self, value: &str) -> bool;
+}
+
+/// A format that is checked asynchronously, e.g. with a lookup in a directory service.
+///
+/// It is only checked by the `*_async` methods of [`crate::Validator`], other methods fail with
+/// [`crate::ValidationErrorKind::RequiresAsync`] if they reach it.
+pub trait AsyncFormat: Send + Sync + 'static {
+    fn is_valid<'v>(&'v self, value: &'v str) -> BoxFuture<'v, bool>;
+}
+
+/// Built-in implementation of the given format, if the draft defines it.
+pub(crate) fn builtin(
+    name: &str,
+    draft: Draft,
+    regex_engine: &Arc<dyn RegexEngine>,
+) -> Option<Arc<dyn Format>> {
+    let format: Arc<dyn Format> = match name {
+        "date-time" => Arc::new(datetime::DateTime),
+        "date" if draft >= Draft::Draft07 => Arc::new(datetime::Date),
+        "time" if draft >= Draft::Draft07 => Arc::new(datetime::Time),
+        "duration" if draft >= Draft::Draft201909 => Arc::new(datetime::Duration),
+        "email" => Arc::new(network::Email),
+        "idn-email" if draft >= Draft::Draft07 => Arc::new(network::IdnEmail),
+        "hostname" => Arc::new(network::Hostname),
+        "idn-hostname" if draft >= Draft::Draft07 => Arc::new(network::IdnHostname),
+        "ipv4" => Arc::new(network::Ipv4),
+        "ipv6" => Arc::new(network::Ipv6),
+        "uri" => Arc::new(uri::Uri),
+        "uri-reference" if draft >= Draft::Draft06 => Arc::new(uri::UriReference),
+        "iri" if draft >= Draft::Draft07 => Arc::new(uri::Iri),
+        "iri-reference" if draft >= Draft::Draft07 => Arc::new(uri::IriReference),
+        "uri-template" if draft >= Draft::Draft06 => Arc::new(uri::UriTemplate),
+        "uuid" if draft >= Draft::Draft201909 => Arc::new(uri::Uuid),
+        "json-pointer" if draft >= Draft::Draft06 => Arc::new(syntax::JsonPointerFormat),
+        "relative-json-pointer" if draft >= Draft::Draft07 => {
+            Arc::new(syntax::RelativeJsonPointerFormat)
+        }
+        "regex" => Arc::new(syntax::Regex {
+            engine: Arc::clone(regex_engine),
+        }),
+        _ => return None,
+    };
+    Some(format)
+}
+
+/// How to handle `format` values without a registered implementation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum UnknownFormatPolicy {
+    /// Treat unknown formats as annotations only.
+    #[default]
+    Ignore,
+    /// Emit a `warning` annotation next to the `format` one.
+    Warn,
+    /// Fail with `BuildError`.
+    Error,
+}
+
+mod sealed {
+    pub trait Sealed<J, M> {}
+}
+
+pub trait FormatFactory<'a, J: Json, M>: Send + Sync + sealed::Sealed<J, M> + 'a {
+    fn init(&self, ctx: &mut CompileContext<'a, '_, J>, schema: &'a J) -> BuildResult<BoxedFormat>;
+}
+
+impl<'a, F, J: Json + 'a> sealed::Sealed<J, Schema> for F where
+    F: Fn(&'a J) -> BuildResult<BoxedFormat> + Send + Sync + 'a
+{
+}
+
+impl<'a, F, J: Json + 'a> FormatFactory<'a, J, Schema> for F
+where
+    F: Fn(&'a J) -> BuildResult<BoxedFormat> + Send + Sync + 'a,
+{
+    fn init(
+        &self,
+        _ctx: &mut CompileContext<'a, '_, J>,
+        schema: &'a J,
+    ) -> BuildResult<BoxedFormat> {
+        self(schema)
+    }
+}
+
+impl<'a, F, J: Json + 'a> sealed::Sealed<J, ContextAndSchema> for F where
+    F: Fn(&mut CompileContext<'a, '_, J>, &'a J) -> BuildResult<BoxedFormat> + Send + Sync + 'a
+{
+}
+
+impl<'a, F, J: Json + 'a> FormatFactory<'a, J, ContextAndSchema> for F
+where
+    F: Fn(&mut CompileContext<'a, '_, J>, &'a J) -> BuildResult<BoxedFormat> + Send + Sync + 'a,
+{
+    fn init(&self, ctx: &mut CompileContext<'a, '_, J>, schema: &'a J) -> BuildResult<BoxedFormat> {
+        self(ctx, schema)
+    }
+}
+
+/// A [`FormatFactory`] with its marker type erased.
+pub(crate) trait ErasedFormatFactory<'a, J: Json>: Send + Sync + 'a {
+    fn init(&self, ctx: &mut CompileContext<'a, '_, J>, schema: &'a J) -> BuildResult<BoxedFormat>;
+}
+
+impl<'a, F, M, J> ErasedFormatFactory<'a, J> for Erased<F, M>
+where
+    F: FormatFactory<'a, J, M>,
+    M: 'a,
+    J: Json,
+{
+    fn init(&self, ctx: &mut CompileContext<'a, '_, J>, schema: &'a J) -> BuildResult<BoxedFormat> {
+        self.0.init(ctx, schema)
+    }
+}
+
+/// Creates an [`AsyncFormat`] from the `format` value.
+pub trait AsyncFormatFactory<'a, J: Json, M>:
+    Send + Sync + async_sealed::Sealed<J, M> + 'a
+{
+    fn init(
+        &self,
+        ctx: &mut CompileContext<'a, '_, J>,
+        schema: &'a J,
+    ) -> BuildResult<BoxedAsyncFormat>;
+}
+
+mod async_sealed {
+    pub trait Sealed<J, M> {}
+}
+
+impl<'a, F, J: Json + 'a> async_sealed::Sealed<J, Schema> for F where
+    F: Fn(&'a J) -> BuildResult<BoxedAsyncFormat> + Send + Sync + 'a
+{
+}
+
+impl<'a, F, J: Json + 'a> AsyncFormatFactory<'a, J, Schema> for F
+where
+    F: Fn(&'a J) -> BuildResult<BoxedAsyncFormat> + Send + Sync + 'a,
+{
+    fn init(
+        &self,
+        _ctx: &mut CompileContext<'a, '_, J>,
+        schema: &'a J,
+    ) -> BuildResult<BoxedAsyncFormat> {
+        self(schema)
+    }
+}
+
+impl<'a, F, J: Json + 'a> async_sealed::Sealed<J, ContextAndSchema> for F where
+    F: Fn(&mut CompileContext<'a, '_, J>, &'a J) -> BuildResult<BoxedAsyncFormat>
+        + Send
+        + Sync
+        + 'a
+{
+}
+
+impl<'a, F, J: Json + 'a> AsyncFormatFactory<'a, J, ContextAndSchema> for F
+where
+    F: Fn(&mut CompileContext<'a, '_, J>, &'a J) -> BuildResult<BoxedAsyncFormat>
+        + Send
+        + Sync
+        + 'a,
+{
+    fn init(
+        &self,
+        ctx: &mut CompileContext<'a, '_, J>,
+        schema: &'a J,
+    ) -> BuildResult<BoxedAsyncFormat> {
+        self(ctx, schema)
+    }
+}
+
+/// An [`AsyncFormatFactory`] with its marker type erased.
+pub(crate) trait ErasedAsyncFormatFactory<'a, J: Json>: Send + Sync + 'a {
+    fn init(
+        &self,
+        ctx: &mut CompileContext<'a, '_, J>,
+        schema: &'a J,
+    ) -> BuildResult<BoxedAsyncFormat>;
+}
+
+impl<'a, F, M, J> ErasedAsyncFormatFactory<'a, J> for Erased<F, M>
+where
+    F: AsyncFormatFactory<'a, J, M>,
+    M: 'a,
+    J: Json,
+{
+    fn init(
+        &self,
+        ctx: &mut CompileContext<'a, '_, J>,
+        schema: &'a J,
+    ) -> BuildResult<BoxedAsyncFormat> {
+        self.0.init(ctx, schema)
+    }
+}
@@ -0,0 +1,339 @@
+//! Email, host name & IP address formats.
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use super::Format;
+
+pub(crate) struct Email;
+
+impl Format for Email {
+    fn is_valid(&self, value: &str) -> bool {
+        is_valid_email(value, false)
+    }
+}
+
+pub(crate) struct IdnEmail;
+
+impl Format for IdnEmail {
+    fn is_valid(&self, value: &str) -> bool {
+        is_valid_email(value, true)
+    }
+}
+
+pub(crate) struct Hostname;
+
+impl Format for Hostname {
+    fn is_valid(&self, value: &str) -> bool {
+        is_valid_hostname(value)
+    }
+}
+
+pub(crate) struct IdnHostname;
+
+impl Format for IdnHostname {
+    fn is_valid(&self, value: &str) -> bool {
+        is_valid_idn_hostname(value)
+    }
+}
+
+pub(crate) struct Ipv4;
+
+impl Format for Ipv4 {
+    fn is_valid(&self, value: &str) -> bool {
+        // Leading zeros and non-ASCII digits are rejected by the parser
+        value.parse::<Ipv4Addr>().is_ok()
+    }
+}
+
+pub(crate) struct Ipv6;
+
+impl Format for Ipv6 {
+    fn is_valid(&self, value: &str) -> bool {
+        value.parse::<Ipv6Addr>().is_ok()
+    }
+}
+
+const MAX_LOCAL_PART_LENGTH: usize = 64;
+
+/// `atext` from RFC 5322, extended with non-ASCII characters by RFC 6531.
+fn is_atext(ch: char, international: bool) -> bool {
+    ch.is_ascii_alphanumeric()
+        || "!#$%&'*+-/=?^_`{|}~".contains(ch)
+        || (international && !ch.is_ascii())
+}
+
+/// `Dot-string = Atom *("." Atom)`
+fn is_valid_dot_string(value: &str, international: bool) -> bool {
+    value
+        .split('.')
+        .all(|atom| !atom.is_empty() && atom.chars().all(|ch| is_atext(ch, international)))
+}
+
+/// `Quoted-string = DQUOTE *QcontentSMTP DQUOTE`
+fn is_valid_quoted_string(value: &str, international: bool) -> bool {
+    let Some(content) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    else {
+        return false;
+    };
+    let mut chars = content.chars();
+    while let Some(ch) = chars.next() {
+        let is_valid = match ch {
+            // quoted-pairSMTP
+            '\\' => chars.next().is_some_and(|ch| matches!(ch, ' '..='~')),
+            // qtextSMTP
+            ' ' | '!' | '#'..='[' | ']'..='~' => true,
+            _ => international && !ch.is_ascii(),
+        };
+        if !is_valid {
+            return false;
+        }
+    }
+    true
+}
+
+/// `address-literal = "[" ( IPv4-address-literal / IPv6-address-literal ) "]"`
+fn is_valid_address_literal(value: &str) -> bool {
+    let Some(address) = value
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+    else {
+        return false;
+    };
+    match address.strip_prefix("IPv6:") {
+        Some(address) => address.parse::<Ipv6Addr>().is_ok(),
+        None => address.parse::<Ipv4Addr>().is_ok(),
+    }
+}
+
+/// `Mailbox = Local-part "@" ( Domain / address-literal )` from RFC 5321 or RFC 6531.
+fn is_valid_email(value: &str, international: bool) -> bool {
+    let Some((local, domain)) = value.rsplit_once('@') else {
+        return false;
+    };
+    if local.is_empty() || local.len() > MAX_LOCAL_PART_LENGTH {
+        return false;
+    }
+    if !is_valid_dot_string(local, international) && !is_valid_quoted_string(local, international) {
+        return false;
+    }
+    if domain.starts_with('[') {
+        is_valid_address_literal(domain)
+    } else if international {
+        is_valid_idn_hostname(domain)
+    } else {
+        is_valid_hostname(domain)
+    }
+}
+
+const MAX_HOSTNAME_LENGTH: usize = 253;
+const MAX_LABEL_LENGTH: usize = 63;
+
+/// Host name as defined by RFC 1123, A-labels must be valid IDNA2008 labels.
+fn is_valid_hostname(value: &str) -> bool {
+    // A single trailing dot denotes the root label
+    let hostname = value.strip_suffix('.').unwrap_or(value);
+    if hostname.is_empty() || hostname.len() > MAX_HOSTNAME_LENGTH {
+        return false;
+    }
+    let mut has_a_label = false;
+    for label in hostname.split('.') {
+        if label.is_empty()
+            || label.len() > MAX_LABEL_LENGTH
+            || label.starts_with('-')
+            || label.ends_with('-')
+            || !label
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-')
+        {
+            return false;
+        }
+        if label.get(2..4) == Some("--") {
+            // Only A-labels may contain hyphens in the 3rd & 4th positions
+            if !label[..2].eq_ignore_ascii_case("xn") {
+                return false;
+            }
+            has_a_label = true;
+        }
+    }
+    !has_a_label || is_valid_idn_hostname(hostname)
+}
+
+/// Internationalized host name as defined by RFC 5890.
+///
+/// The UTS #46 processing handles mapping, Punycode, `CONTEXTJ` & Bidi rules, while the
+/// `CONTEXTO` rules and exceptions from RFC 5892 are checked separately.
+fn is_valid_idn_hostname(value: &str) -> bool {
+    if value.is_empty() || value.chars().any(is_disallowed_exception) {
+        return false;
+    }
+    let Ok(ascii) = idna::domain_to_ascii_strict(value) else {
+        return false;
+    };
+    let (unicode, result) = idna::domain_to_unicode(&ascii);
+    result.is_ok() && unicode.split('.').all(is_valid_context)
+}
+
+/// Code points that are `DISALLOWED` by RFC 5892, Section 2.6.
+fn is_disallowed_exception(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{0640}' | '\u{07FA}' | '\u{302E}' | '\u{302F}' | '\u{3031}'..='\u{3035}' | '\u{303B}'
+    )
+}
+
+fn is_greek(ch: char) -> bool {
+    matches!(ch, '\u{0370}'..='\u{03FF}' | '\u{1F00}'..='\u{1FFF}')
+}
+
+fn is_hebrew(ch: char) -> bool {
+    matches!(ch, '\u{0590}'..='\u{05FF}' | '\u{FB1D}'..='\u{FB4F}')
+}
+
+fn is_hiragana_katakana_han(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{3040}'..='\u{309F}'
+            | '\u{30A0}'..='\u{30FA}'
+            | '\u{30FC}'..='\u{30FF}'
+            | '\u{31F0}'..='\u{31FF}'
+            | '\u{3005}'
+            | '\u{3007}'
+            | '\u{3021}'..='\u{3029}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FF66}'..='\u{FF9F}'
+            | '\u{20000}'..='\u{3134F}'
+    )
+}
+
+/// `CONTEXTO` rules from RFC 5892, Appendix A.
+fn is_valid_context(label: &str) -> bool {
+    let chars: Vec<char> = label.chars().collect();
+    let mut has_arabic_indic = false;
+    let mut has_extended_arabic_indic = false;
+    for (idx, ch) in chars.iter().enumerate() {
+        let before = idx.checked_sub(1).map(|idx| chars[idx]);
+        let after = chars.get(idx + 1).copied();
+        let is_valid = match ch {
+            // MIDDLE DOT
+            '\u{00B7}' => before == Some('l') && after == Some('l'),
+            // GREEK LOWER NUMERAL SIGN (KERAIA)
+            '\u{0375}' => after.is_some_and(is_greek),
+            // HEBREW PUNCTUATION GERESH & GERSHAYIM
+            '\u{05F3}' | '\u{05F4}' => before.is_some_and(is_hebrew),
+            // KATAKANA MIDDLE DOT
+            '\u{30FB}' => chars.iter().copied().any(is_hiragana_katakana_han),
+            '\u{0660}'..='\u{0669}' => {
+                has_arabic_indic = true;
+                true
+            }
+            '\u{06F0}'..='\u{06F9}' => {
+                has_extended_arabic_indic = true;
+                true
+            }
+            _ => true,
+        };
+        if !is_valid {
+            return false;
+        }
+    }
+    // ARABIC-INDIC DIGITS can not be mixed with EXTENDED ARABIC-INDIC DIGITS
+    !(has_arabic_indic && has_extended_arabic_indic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("joe.bloggs@example.com", true)]
+    #[test_case("te~st@example.com", true)]
+    #[test_case("~test@example.com", true)]
+    #[test_case("\"joe bloggs\"@example.com", true)]
+    #[test_case("\"joe..bloggs\"@example.com", true)]
+    #[test_case("joe.bloggs@[127.0.0.1]", true)]
+    #[test_case("joe.bloggs@[IPv6:::1]", true)]
+    #[test_case("2962", false)]
+    #[test_case(".test@example.com", false)]
+    #[test_case("test.@example.com", false)]
+    #[test_case("te..st@example.com", false)]
+    #[test_case("joe.bloggs@invalid=domain.com", false)]
+    #[test_case("joe.bloggs@[127.0.0.300]", false)]
+    #[test_case("실례@실례.테스트", false)]
+    fn test_email(value: &str, expected: bool) {
+        assert_eq!(is_valid_email(value, false), expected);
+    }
+
+    #[test_case("실례@실례.테스트", true)]
+    #[test_case("joe.bloggs@example.com", true)]
+    #[test_case("2962", false)]
+    fn test_idn_email(value: &str, expected: bool) {
+        assert_eq!(is_valid_email(value, true), expected);
+    }
+
+    #[test_case("www.example.com", true)]
+    #[test_case("xn--4gbwdl.xn--wgbh1c", true)]
+    #[test_case("hostname", true)]
+    #[test_case("h0stn4me", true)]
+    #[test_case("1host", true)]
+    #[test_case("example.com.", true)]
+    #[test_case("", false)]
+    #[test_case(".", false)]
+    #[test_case("-a-host-name-that-starts-with--", false)]
+    #[test_case("not_a_valid_host_name", false)]
+    #[test_case("a-vvvvvvvvvvvvvvvveeeeeeeeeeeeeeeerrrrrrrrrrrrrrrryyyyyyyyyyyyyyyy-long-host-name-component", false)]
+    #[test_case("www..example.com", false)]
+    #[test_case("XN--aa---o47jg78q", false)]
+    #[test_case("xn--X", false)]
+    #[test_case("실례.테스트", false)]
+    fn test_hostname(value: &str, expected: bool) {
+        assert_eq!(is_valid_hostname(value), expected);
+    }
+
+    #[test_case("실례.테스트", true)]
+    #[test_case("www.example.com", true)]
+    #[test_case("l·l", true)]
+    #[test_case("a·l", false)]
+    #[test_case("\u{03B1}\u{0375}\u{03B2}", true)]
+    #[test_case("\u{03B1}\u{0375}S", false)]
+    #[test_case("א׳ב", true)]
+    #[test_case("a׳b", false)]
+    #[test_case("・ぁ", true)]
+    #[test_case("def・abc", false)]
+    #[test_case("\u{0628}\u{0660}\u{06F0}", false)]
+    #[test_case("\u{0628}\u{0660}", true)]
+    #[test_case("\u{0915}\u{094D}\u{200D}\u{0937}", true)]
+    #[test_case("\u{0915}\u{200D}\u{0937}", false)]
+    #[test_case("\u{0628}\u{064A}\u{200C}\u{0628}\u{064A}", true)]
+    #[test_case("\u{0640}\u{07FA}", false)]
+    #[test_case("\u{302E}\u{302F}", false)]
+    #[test_case("\u{0903}hello", false)]
+    #[test_case("〮실례.테스트", false)]
+    fn test_idn_hostname(value: &str, expected: bool) {
+        assert_eq!(is_valid_idn_hostname(value), expected);
+    }
+
+    #[test_case("192.168.0.1", true)]
+    #[test_case("127.0.0.0.1", false)]
+    #[test_case("256.256.256.256", false)]
+    #[test_case("087.10.0.1", false)]
+    #[test_case("1২7.0.0.1", false)]
+    #[test_case("192.168.1.0/24", false)]
+    fn test_ipv4(value: &str, expected: bool) {
+        assert_eq!(Ipv4.is_valid(value), expected);
+    }
+
+    #[test_case("::1", true)]
+    #[test_case("::ffff:192.168.0.1", true)]
+    #[test_case("1:2:3:4:5:6:7:8", true)]
+    #[test_case("12345::", false)]
+    #[test_case("::1%eth0", false)]
+    #[test_case(" ::1", false)]
+    #[test_case("1:2:3:4:5:6:7:8:9", false)]
+    #[test_case("1:2:3:4:5:৪:7:8", false)]
+    fn test_ipv6(value: &str, expected: bool) {
+        assert_eq!(Ipv6.is_valid(value), expected);
+    }
+}
@@ -0,0 +1,70 @@
+//! Formats backed by syntax parsers: JSON Pointers and regular expressions.
+use std::sync::Arc;
+
+use jpointer::{JsonPointer, RelativeJsonPointer};
+
+use super::Format;
+use crate::RegexEngine;
+
+pub(crate) struct JsonPointerFormat;
+
+impl Format for JsonPointerFormat {
+    fn is_valid(&self, value: &str) -> bool {
+        value.parse::<JsonPointer>().is_ok()
+    }
+}
+
+pub(crate) struct RelativeJsonPointerFormat;
+
+impl Format for RelativeJsonPointerFormat {
+    fn is_valid(&self, value: &str) -> bool {
+        value.parse::<RelativeJsonPointer>().is_ok()
+    }
+}
+
+/// Patterns are valid if the configured engine can compile them.
+pub(crate) struct Regex {
+    pub(crate) engine: Arc<dyn RegexEngine>,
+}
+
+impl Format for Regex {
+    fn is_valid(&self, value: &str) -> bool {
+        self.engine.compile(value).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FancyRegexEngine;
+    use test_case::test_case;
+
+    #[test_case("", true)]
+    #[test_case("/foo/0", true)]
+    #[test_case("/a~1b/m~0n", true)]
+    #[test_case("foo", false)]
+    #[test_case("/foo~2", false)]
+    fn test_json_pointer(value: &str, expected: bool) {
+        assert_eq!(JsonPointerFormat.is_valid(value), expected);
+    }
+
+    #[test_case("0", true)]
+    #[test_case("1/foo", true)]
+    #[test_case("2#", true)]
+    #[test_case("/foo", false)]
+    #[test_case("01", false)]
+    #[test_case("-1", false)]
+    fn test_relative_json_pointer(value: &str, expected: bool) {
+        assert_eq!(RelativeJsonPointerFormat.is_valid(value), expected);
+    }
+
+    #[test_case("([abc])+\\s+$", true)]
+    #[test_case("^(abc]", false)]
+    #[test_case("\\a", false)]
+    fn test_regex(value: &str, expected: bool) {
+        let format = Regex {
+            engine: Arc::new(FancyRegexEngine::new()),
+        };
+        assert_eq!(format.is_valid(value), expected);
+    }
+}
@@ -0,0 +1,387 @@
+//! URI & IRI formats as defined by RFC 3986 & RFC 3987, URI templates and UUIDs.
+use std::net::Ipv6Addr;
+
+use super::Format;
+
+pub(crate) struct Uri;
+
+impl Format for Uri {
+    fn is_valid(&self, value: &str) -> bool {
+        is_valid_reference(value, false, true)
+    }
+}
+
+pub(crate) struct UriReference;
+
+impl Format for UriReference {
+    fn is_valid(&self, value: &str) -> bool {
+        is_valid_reference(value, false, false)
+    }
+}
+
+pub(crate) struct Iri;
+
+impl Format for Iri {
+    fn is_valid(&self, value: &str) -> bool {
+        is_valid_reference(value, true, true)
+    }
+}
+
+pub(crate) struct IriReference;
+
+impl Format for IriReference {
+    fn is_valid(&self, value: &str) -> bool {
+        is_valid_reference(value, true, false)
+    }
+}
+
+pub(crate) struct UriTemplate;
+
+impl Format for UriTemplate {
+    fn is_valid(&self, value: &str) -> bool {
+        is_valid_uri_template(value)
+    }
+}
+
+pub(crate) struct Uuid;
+
+impl Format for Uuid {
+    fn is_valid(&self, value: &str) -> bool {
+        is_valid_uuid(value)
+    }
+}
+
+/// `ucschar` from RFC 3987.
+fn is_ucschar(ch: char) -> bool {
+    let code = u32::from(ch);
+    matches!(code, 0xA0..=0xD7FF | 0xF900..=0xFDCF | 0xFDF0..=0xFFEF | 0xE1000..=0xEFFFD)
+        || ((0x10000..0xE0000).contains(&code) && code & 0xFFFF <= 0xFFFD)
+}
+
+/// `iprivate` from RFC 3987, only allowed in queries.
+fn is_iprivate(ch: char) -> bool {
+    matches!(u32::from(ch), 0xE000..=0xF8FF | 0xF0000..=0xFFFFD | 0x100000..=0x10FFFD)
+}
+
+fn is_unreserved(ch: char, iri: bool) -> bool {
+    ch.is_ascii_alphanumeric() || matches!(ch, '-' | '.' | '_' | '~') || (iri && is_ucschar(ch))
+}
+
+fn is_sub_delim(ch: char) -> bool {
+    matches!(
+        ch,
+        '!' | '$' | '&' | '\'' | '(' | ')' | '*' | '+' | ',' | ';' | '='
+    )
+}
+
+/// Check that `value` consists of unreserved characters, sub-delimiters, percent-encoded octets
+/// and characters accepted by `is_extra`.
+fn is_valid_component(value: &str, iri: bool, is_extra: impl Fn(char) -> bool) -> bool {
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        let is_valid = if ch == '%' {
+            let (high, low) = (chars.next(), chars.next());
+            high.is_some_and(|ch| ch.is_ascii_hexdigit())
+                && low.is_some_and(|ch| ch.is_ascii_hexdigit())
+        } else {
+            is_unreserved(ch, iri) || is_sub_delim(ch) || is_extra(ch)
+        };
+        if !is_valid {
+            return false;
+        }
+    }
+    true
+}
+
+fn is_pchar_extra(ch: char) -> bool {
+    matches!(ch, ':' | '@')
+}
+
+/// `path` made of `segment`s separated by `/`.
+fn is_valid_path(value: &str, iri: bool) -> bool {
+    is_valid_component(value, iri, |ch| is_pchar_extra(ch) || ch == '/')
+}
+
+/// `scheme = ALPHA *( ALPHA / DIGIT / "+" / "-" / "." )`
+fn is_valid_scheme(value: &str) -> bool {
+    let mut chars = value.chars();
+    chars.next().is_some_and(|ch| ch.is_ascii_alphabetic())
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '+' | '-' | '.'))
+}
+
+/// `IP-literal = "[" ( IPv6address / IPvFuture ) "]"`
+fn is_valid_ip_literal(value: &str) -> bool {
+    if let Some(future) = value.strip_prefix(['v', 'V']) {
+        // IPvFuture = "v" 1*HEXDIG "." 1*( unreserved / sub-delims / ":" )
+        let Some((version, address)) = future.split_once('.') else {
+            return false;
+        };
+        !version.is_empty()
+            && version.chars().all(|ch| ch.is_ascii_hexdigit())
+            && !address.is_empty()
+            && address
+                .chars()
+                .all(|ch| is_unreserved(ch, false) || is_sub_delim(ch) || ch == ':')
+    } else {
+        value.parse::<Ipv6Addr>().is_ok()
+    }
+}
+
+/// `authority = [ userinfo "@" ] host [ ":" port ]`
+fn is_valid_authority(value: &str, iri: bool) -> bool {
+    let (userinfo, host_port) = match value.rsplit_once('@') {
+        Some((userinfo, host_port)) => (Some(userinfo), host_port),
+        None => (None, value),
+    };
+    if let Some(userinfo) = userinfo {
+        if !is_valid_component(userinfo, iri, |ch| ch == ':') {
+            return false;
+        }
+    }
+    let port = if let Some(literal) = host_port.strip_prefix('[') {
+        let Some((literal, rest)) = literal.split_once(']') else {
+            return false;
+        };
+        if !is_valid_ip_literal(literal) {
+            return false;
+        }
+        match rest.strip_prefix(':') {
+            Some(port) => port,
+            None if rest.is_empty() => "",
+            None => return false,
+        }
+    } else {
+        let (host, port) = host_port.split_once(':').unwrap_or((host_port, ""));
+        // `reg-name` also covers IPv4 addresses
+        if !is_valid_component(host, iri, |_| false) {
+            return false;
+        }
+        port
+    };
+    port.chars().all(|ch| ch.is_ascii_digit())
+}
+
+/// `URI` / `IRI` when `absolute` is set, otherwise `URI-reference` / `IRI-reference`.
+fn is_valid_reference(value: &str, iri: bool, absolute: bool) -> bool {
+    let (rest, fragment) = match value.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (value, None),
+    };
+    if let Some(fragment) = fragment {
+        if !is_valid_component(fragment, iri, |ch| {
+            is_pchar_extra(ch) || matches!(ch, '/' | '?')
+        }) {
+            return false;
+        }
+    }
+    let (rest, query) = match rest.split_once('?') {
+        Some((rest, query)) => (rest, Some(query)),
+        None => (rest, None),
+    };
+    if let Some(query) = query {
+        if !is_valid_component(query, iri, |ch| {
+            is_pchar_extra(ch) || matches!(ch, '/' | '?') || (iri && is_iprivate(ch))
+        }) {
+            return false;
+        }
+    }
+    // A colon before any slash either ends the scheme or makes the reference invalid, as the
+    // first segment of a relative path can not contain colons
+    let first_segment = rest.split('/').next().unwrap_or_default();
+    let hier_part = match first_segment.split_once(':') {
+        Some((scheme, _)) if is_valid_scheme(scheme) => &rest[scheme.len() + 1..],
+        Some(_) => return false,
+        None if absolute => return false,
+        None => rest,
+    };
+    match hier_part.strip_prefix("//") {
+        Some(authority_and_path) => {
+            let (authority, path) = match authority_and_path.find('/') {
+                Some(idx) => authority_and_path.split_at(idx),
+                None => (authority_and_path, ""),
+            };
+            is_valid_authority(authority, iri) && is_valid_path(path, iri)
+        }
+        None => is_valid_path(hier_part, iri),
+    }
+}
+
+/// `varchar = ALPHA / DIGIT / "_" / pct-encoded`
+fn is_valid_varname(value: &str) -> bool {
+    !value.is_empty()
+        && !value.starts_with('.')
+        && !value.ends_with('.')
+        && !value.contains("..")
+        && is_valid_component(value, false, |_| false)
+        && !value.contains([
+            '-', '~', '!', '$', '&', '\'', '(', ')', '*', '+', ',', ';', '=',
+        ])
+}
+
+/// `varspec = varname [ modifier-level4 ]`
+fn is_valid_varspec(value: &str) -> bool {
+    if let Some(name) = value.strip_suffix('*') {
+        return is_valid_varname(name);
+    }
+    match value.split_once(':') {
+        // max-length = %x31-39 0*3DIGIT
+        Some((name, length)) => {
+            is_valid_varname(name)
+                && (1..=4).contains(&length.len())
+                && !length.starts_with('0')
+                && length.chars().all(|ch| ch.is_ascii_digit())
+        }
+        None => is_valid_varname(value),
+    }
+}
+
+/// URI Template syntax as defined by RFC 6570.
+fn is_valid_uri_template(value: &str) -> bool {
+    let mut rest = value;
+    while !rest.is_empty() {
+        let literal_end = rest.find(['{', '}']).unwrap_or(rest.len());
+        let literal = &rest[..literal_end];
+        let is_valid_literal = is_valid_component(literal, true, |ch| {
+            !ch.is_ascii_control()
+                && !matches!(
+                    ch,
+                    ' ' | '"' | '\'' | '%' | '<' | '>' | '\\' | '^' | '`' | '|'
+                )
+        });
+        if !is_valid_literal {
+            return false;
+        }
+        rest = &rest[literal_end..];
+        let Some(expression) = rest.strip_prefix('{') else {
+            // Unbalanced closing brace
+            return rest.is_empty();
+        };
+        let Some((expression, after)) = expression.split_once('}') else {
+            return false;
+        };
+        let variables = expression
+            .strip_prefix(['+', '#', '.', '/', ';', '?', '&'])
+            .unwrap_or(expression);
+        if !variables.split(',').all(is_valid_varspec) {
+            return false;
+        }
+        rest = after;
+    }
+    true
+}
+
+/// `8-4-4-4-12` hexadecimal digits as defined by RFC 4122.
+fn is_valid_uuid(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() == 36
+        && bytes.iter().enumerate().all(|(idx, byte)| match idx {
+            8 | 13 | 18 | 23 => *byte == b'-',
+            _ => byte.is_ascii_hexdigit(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("http://foo.bar/?baz=qux#quux", true)]
+    #[test_case("http://foo.com/blah_(wikipedia)_blah#cite-1", true)]
+    #[test_case("http://foo.bar/?q=Test%20URL-encoded%20stuff", true)]
+    #[test_case("http://xn--nw2a.xn--j6w193g/", true)]
+    #[test_case("http://-.~_!$&'()*+,;=:%40:80%2f::::::@example.com", true)]
+    #[test_case("http://223.255.255.254", true)]
+    #[test_case("ftp://ftp.is.co.za/rfc/rfc1808.txt", true)]
+    #[test_case("http://www.ietf.org/rfc/rfc2396.txt", true)]
+    #[test_case("ldap://[2001:db8::7]/c=GB?objectClass?one", true)]
+    #[test_case("mailto:John.Doe@example.com", true)]
+    #[test_case("news:comp.infosystems.www.servers.unix", true)]
+    #[test_case("tel:+1-816-555-1212", true)]
+    #[test_case("urn:oasis:names:specification:docbook:dtd:xml:4.1.2", true)]
+    #[test_case("//foo.bar/?baz=qux#quux", false)]
+    #[test_case("/abc", false)]
+    #[test_case("\\\\WINDOWS\\fileshare", false)]
+    #[test_case("abc", false)]
+    #[test_case("http:// shouldfail.com", false)]
+    #[test_case(":// should fail", false)]
+    #[test_case("bar,baz:foo", false)]
+    #[test_case("https://[@example.org/test.txt", false)]
+    #[test_case("https://example.org/foobar\\.txt", false)]
+    #[test_case("https://example.org/foobar<>.txt", false)]
+    #[test_case("https://example.org/foobar{}.txt", false)]
+    #[test_case("https://example.org/foobar^.txt", false)]
+    #[test_case("https://example.org/foobar`.txt", false)]
+    #[test_case("https://example.org/foo bar.txt", false)]
+    #[test_case("https://example.org/foobar|.txt", false)]
+    #[test_case("http://ex%2.com", false)]
+    #[test_case("http://example.com/ü", false)]
+    fn test_uri(value: &str, expected: bool) {
+        assert_eq!(is_valid_reference(value, false, true), expected);
+    }
+
+    #[test_case("http://foo.bar/?baz=qux#quux", true)]
+    #[test_case("//foo.bar/?baz=qux#quux", true)]
+    #[test_case("/abc", true)]
+    #[test_case("#fragment", true)]
+    #[test_case("", true)]
+    #[test_case("\\\\WINDOWS\\filëßåré", false)]
+    #[test_case("#frag\\ment", false)]
+    fn test_uri_reference(value: &str, expected: bool) {
+        assert_eq!(is_valid_reference(value, false, false), expected);
+    }
+
+    #[test_case("http://ƒøø.ßår/?∂éœ=πîx#πîüx", true)]
+    #[test_case("http://ƒøø.com/blah_(wîkïpédiå)_blah#ßité-1", true ; "iri with parentheses")]
+    #[test_case("http://ƒøø.ßår/?q=Test%20URL-encoded%20stuff", true ; "iri with percent-encoded query")]
+    #[test_case("http://-.~_!$&'()*+,;=:%40:80%2f::::::@example.com", true)]
+    #[test_case("http://[2001:0db8:85a3:0000:0000:8a2e:0370:7334]", true)]
+    #[test_case("http://2001:0db8:85a3:0000:0000:8a2e:0370:7334", false)]
+    #[test_case("/abc", false)]
+    #[test_case("âππ", false)]
+    fn test_iri(value: &str, expected: bool) {
+        assert_eq!(is_valid_reference(value, true, true), expected);
+    }
+
+    #[test_case("http://ƒøø.ßår/?∂éœ=πîx#πîüx", true)]
+    #[test_case("//ƒøø.ßår/?∂éœ=πîx#πîüx", true ; "iri reference without scheme")]
+    #[test_case("/âππ", true)]
+    #[test_case("âππ", true)]
+    #[test_case("#ƒrägmênt", true ; "iri reference fragment")]
+    #[test_case("\\\\WINDOWS\\filëßåré", false)]
+    #[test_case("#ƒräg\\mênt", false ; "invalid iri reference fragment")]
+    fn test_iri_reference(value: &str, expected: bool) {
+        assert_eq!(is_valid_reference(value, true, false), expected);
+    }
+
+    #[test_case("http://example.com/dictionary/{term:1}/{term}", true)]
+    #[test_case("http://example.com/dictionary", true)]
+    #[test_case("dictionary/{term:1}/{term}", true)]
+    #[test_case("{/path*}{?x,y}{#frag}", true)]
+    #[test_case("{+var.name}", true)]
+    #[test_case("http://example.com/dictionary/{term:1}/{term", false)]
+    #[test_case("http://example.com/}", false)]
+    #[test_case("{term:0}", false)]
+    #[test_case("{term:10000}", false)]
+    #[test_case("{}", false)]
+    #[test_case("{te rm}", false)]
+    fn test_uri_template(value: &str, expected: bool) {
+        assert_eq!(is_valid_uri_template(value), expected);
+    }
+
+    #[test_case("2EB8AA08-AA98-11EA-B4AA-73B441D16380", true)]
+    #[test_case("2eb8aa08-aa98-11ea-b4aa-73b441d16380", true)]
+    #[test_case("2eb8aa08-AA98-11ea-B4Aa-73B441D16380", true)]
+    #[test_case("00000000-0000-0000-0000-000000000000", true)]
+    #[test_case("98d80576-482e-427f-8434-7f86890ab222", true)]
+    #[test_case("99c17cbb-656f-564a-940f-1a4568f03487", true)]
+    #[test_case("99c17cbb-656f-f64a-940f-1a4568f03487", true)]
+    #[test_case("2eb8aa08-aa98-11ea-b4aa-73b441d1638", false)]
+    #[test_case("2eb8aa08-aa98-11ea-73b441d16380", false)]
+    #[test_case("2eb8aa08-aa98-11ea-b4ga-73b441d16380", false)]
+    #[test_case("2eb8aa08aa9811eab4aa73b441d16380", false)]
+    #[test_case("2eb8aa08aa98-11ea-b4aa73b441d16380", false)]
+    #[test_case("2eb8-aa08-aa98-11ea-b4aa73b44-1d16380", false)]
+    fn test_uuid(value: &str, expected: bool) {
+        assert_eq!(is_valid_uuid(value), expected);
+    }
+}
@@ -1,20 +1,109 @@
-mod node;
+//! Compiled schemas stored as contiguous keyword arrays.
+//!
+//! Keywords of each schema occupy a single range of one shared array and refer to other schemas by
+//! their index, so validation walks slices instead of chasing per-keyword links. References
+//! point to schemas by index as well, which lets them form cycles.
+use core::ops::Range;
 
-pub(crate) use node::{Node, NodeId};
+/// Index of a compiled schema within a graph.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct SchemaId(u32);
+
+impl SchemaId {
+    /// The schema the validator was built from, reserved before compilation starts.
+    pub(crate) const ROOT: SchemaId = SchemaId(0);
+    /// A schema without keywords, e.g. `true` or `{}`.
+    pub(crate) const TRUE: SchemaId = SchemaId(1);
+
+    pub(crate) fn new(index: u32) -> SchemaId {
+        SchemaId(index)
+    }
+    pub(crate) fn get(self) -> u32 {
+        self.0
+    }
+    #[inline]
+    fn index(self) -> usize {
+        self.0 as usize
+    }
+}
 
 #[derive(Debug, Clone)]
 pub(crate) struct Graph<T> {
-    nodes: Vec<Node<T>>,
+    keywords: Vec<T>,
+    /// Range of keywords for each schema.
+    schemas: Vec<(u32, u32)>,
 }
 
 impl<T> Graph<T> {
     pub(crate) fn new() -> Graph<T> {
-        Graph { nodes: Vec::new() }
+        Graph {
+            keywords: Vec::new(),
+            // Slots for the root & the empty schema
+            schemas: vec![(0, 0), (0, 0)],
+        }
+    }
+
+    /// Number of schemas, including the reserved ones.
+    pub(crate) fn len(&self) -> usize {
+        self.schemas.len()
+    }
+
+    /// Store keywords of a schema and return its id.
+    pub(crate) fn push_schema(&mut self, keywords: Vec<T>) -> SchemaId {
+        if keywords.is_empty() {
+            return SchemaId::TRUE;
+        }
+        let id = self.reserve_schema();
+        self.set_schema(id, keywords);
+        id
     }
 
-    pub(super) fn push_node(&mut self, node: T) -> NodeId {
-        let next_index = self.nodes.len();
-        self.nodes.push(Node::new(node));
-        NodeId::new(next_index)
+    /// Reserve an id for a schema that is compiled later, e.g. a reference target.
+    pub(crate) fn reserve_schema(&mut self) -> SchemaId {
+        let id = u32::try_from(self.schemas.len()).expect("Too many schemas");
+        self.schemas.push((0, 0));
+        SchemaId(id)
+    }
+
+    /// Store keywords of a schema with a reserved id.
+    pub(crate) fn set_schema(&mut self, id: SchemaId, keywords: Vec<T>) {
+        let start = self.keywords.len();
+        self.keywords.extend(keywords);
+        let end = self.keywords.len();
+        self.schemas[id.index()] = (
+            u32::try_from(start).expect("Too many keywords"),
+            u32::try_from(end).expect("Too many keywords"),
+        );
+    }
+
+    #[inline]
+    fn range(&self, id: SchemaId) -> Range<usize> {
+        let (start, end) = self.schemas[id.index()];
+        start as usize..end as usize
+    }
+
+    /// Keywords of the given schema, in evaluation order.
+    #[inline]
+    pub(crate) fn keywords(&self, id: SchemaId) -> &[T] {
+        &self.keywords[self.range(id)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Graph, SchemaId};
+
+    #[test]
+    fn test_reserved_schemas() {
+        let mut graph = Graph::new();
+        assert_eq!(graph.push_schema(Vec::new()), SchemaId::TRUE);
+        let child = graph.push_schema(vec![1, 2]);
+        let target = graph.reserve_schema();
+        graph.set_schema(SchemaId::ROOT, vec![3]);
+        graph.set_schema(target, vec![4, 5, 6]);
+        assert_eq!(graph.keywords(SchemaId::ROOT), [3]);
+        assert!(graph.keywords(SchemaId::TRUE).is_empty());
+        assert_eq!(graph.keywords(child), [1, 2]);
+        assert_eq!(graph.keywords(target), [4, 5, 6]);
     }
 }
@@ -50,6 +50,25 @@
 //!     for error in validator.iter_errors(&instance) {
 //!         println!("{}", error);
 //!     }
+//!     // Validate newline-delimited JSON from any `Read`, errors come with their line number
+//!     for (line, error) in validator.validate_ndjson(std::io::stdin()) {
+//!         println!("{}: {}", line, error);
+//!     }
+//!     // Validate a document from pull-parser events, without building it in memory
+//!     let mut stream = validator.streaming()?;
+//!     stream.push(jsonschema::Event::Number("1"))?;
+//!     for error in stream.finish()? {
+//!         println!("{}", error);
+//!     }
+//!     // Validate a batch in parallel, errors come with the index of their instance (requires the
+//!     // `rayon` feature)
+//!     #[cfg(feature = "rayon")]
+//!     {
+//!         let batch = vec![instance.clone(), serde_json::json!(1)];
+//!         for (index, error) in validator.validate_batch(&batch) {
+//!             println!("{}: {}", index, error);
+//!         }
+//!     }
 //!
 //!     // Collecting validation results into a struct conforming to the JSON Schema "Hierarchical" output format
 //!     let output = validator.evaluate(&instance).hierarchical();
@@ -87,7 +106,7 @@
 //!     }
 //!
 //!     impl<J: Json> jsonschema::Keyword<J> for AsciiKeyword {
-//!         fn is_valid(&self, instance: &J) -> bool {
+//!         fn is_valid(&self, ctx: &jsonschema::KeywordContext<'_, J>, instance: &J) -> bool {
 //!             if let Some(string) = instance.as_string().map(AsRef::as_ref) {
 //!                 if string.is_ascii() {
 //!                     return string.len() <= self.max_size;
@@ -118,6 +137,8 @@
 //!             }
 //!         )
 //!         .keyword("also-ascii", ascii_keyword_factory)
+//!         // Linear-time matching for untrusted schemas
+//!         .regex_engine(jsonschema::LinearRegexEngine::new().size_limit(1 << 20))
 //!         .build(&schema)
 //!         .await?;
 //!     let validator = jsonschema::blocking::ValidatorBuilder::default()
@@ -132,35 +153,60 @@
 //! ```
 pub mod blocking;
 mod compiler;
+mod content;
 mod drafts;
+mod ecma;
 mod error;
 mod format;
 mod graph;
 mod maybe_owned;
 pub mod output;
+mod regex_engine;
 mod resolver;
+mod snapshot;
 mod validation;
+mod value;
 mod vocabulary;
 
 pub use crate::{
+    compiler::CompilationCache,
+    content::{ContentEncoding, ContentMediaType},
     drafts::Draft,
-    error::{BuildError, ValidationError},
-    format::Format,
+    error::{BuildError, ValidationError, ValidationErrorKind},
+    format::{AsyncFormat, Format, UnknownFormatPolicy},
     output::Output,
+    regex_engine::{FancyRegexEngine, LinearRegexEngine, Regex, RegexEngine},
     resolver::ReferenceResolver,
     validation::{
         builder::{validator_for, ValidatorBuilder},
         evaluate, is_valid,
         iter::ValidationErrorIter,
-        iter_errors, try_evaluate, try_is_valid, try_iter_errors, validate, Validator,
+        iter_errors,
+        ndjson::NdjsonErrors,
+        stream::{Event, StreamingValidator},
+        try_evaluate, try_is_valid, try_iter_errors, validate, Validator,
+    },
+    vocabulary::{
+        AsyncKeyword, CompileContext, CustomVocabulary, Keyword, KeywordContext, PrimitiveType,
+        SchemaRef, Vocabulary,
     },
-    vocabulary::Keyword,
 };
-pub use jsonlike::Json;
+pub use jsonlike::{Json, JsonMut};
+#[cfg(feature = "tokio")]
+pub use validation::ndjson::AsyncNdjsonErrors;
+
+use core::{future::Future, pin::Pin};
 
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+pub type BoxedAsyncFormat = Box<dyn AsyncFormat>;
+pub type BoxedAsyncKeyword<J> = Box<dyn AsyncKeyword<J>>;
+pub type BoxedContentEncoding = Box<dyn ContentEncoding>;
+pub type BoxedContentMediaType<J> = Box<dyn ContentMediaType<J>>;
 pub type BoxedFormat = Box<dyn Format>;
 pub type BoxedKeyword<J> = Box<dyn Keyword<J>>;
+pub type BoxedRegex = Box<dyn Regex>;
 pub type BuildResult<T> = Result<T, BuildError>;
+pub type ErrorIterator<'a> = Box<dyn Iterator<Item = ValidationError> + 'a>;
 
 #[cfg(test)]
 mod tests {
@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 
-use crate::{maybe_owned::MaybeOwned, Validator};
+use crate::{maybe_owned::MaybeOwned, vocabulary::Annotation, ValidationError, Validator};
 use jpointer::JsonPointer;
 use jsonlike::Json;
 
@@ -24,8 +24,32 @@ impl<'v, 'i, J: Json> Output<'v, 'i, J> {
             valid: self.validator.is_valid(self.instance),
         }
     }
+    /// Flat list of output units for all errors or, if the instance is valid, all annotations.
     pub fn list(&self) -> List<J> {
-        todo!()
+        let nested: Vec<_> = self
+            .validator
+            .iter_errors(self.instance)
+            .map(|error| OutputUnit::from_error(&error))
+            .collect();
+        if !nested.is_empty() {
+            return List {
+                valid: false,
+                nested,
+            };
+        }
+        match self.validator.annotations(self.instance) {
+            Ok(annotations) => List {
+                valid: true,
+                nested: annotations
+                    .into_iter()
+                    .map(OutputUnit::from_annotation)
+                    .collect(),
+            },
+            Err(error) => List {
+                valid: false,
+                nested: vec![OutputUnit::from_error(&error)],
+            },
+        }
     }
     pub fn hierarchical(&self) -> Hierarchical<J> {
         todo!()
@@ -60,3 +84,39 @@ pub struct OutputUnit<J: Json> {
 }
 
 pub type Hierarchical<J> = OutputUnit<J>;
+
+impl<J: Json> OutputUnit<J> {
+    fn from_error(error: &ValidationError) -> OutputUnit<J> {
+        let keyword = error
+            .evaluation_path()
+            .last()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        OutputUnit {
+            valid: false,
+            evaluation_path: error.evaluation_path().clone(),
+            schema_location: format!("#{}", error.evaluation_path()),
+            instance_location: error.instance_location().clone(),
+            nested: None,
+            annotations: None,
+            dropped_annotations: None,
+            errors: Some(BTreeMap::from([(keyword, error.to_string())])),
+        }
+    }
+    fn from_annotation(annotation: Annotation) -> OutputUnit<J> {
+        let mut annotations = BTreeMap::new();
+        if let Ok(value) = annotation.value.to_json() {
+            annotations.insert(annotation.keyword.into(), value);
+        }
+        OutputUnit {
+            valid: true,
+            schema_location: format!("#{}", annotation.evaluation_path),
+            evaluation_path: annotation.evaluation_path,
+            instance_location: annotation.instance_location,
+            nested: None,
+            annotations: Some(annotations),
+            dropped_annotations: None,
+            errors: None,
+        }
+    }
+}
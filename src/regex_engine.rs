@@ -0,0 +1,366 @@
+//! Regular expression engines for `pattern`, `patternProperties` and the `regex` format.
+use std::sync::{Arc, OnceLock};
+
+use crate::{
+    ecma,
+    error::BuildError,
+    snapshot::{Reader, Writer},
+    BoxedRegex, BuildResult,
+};
+
+/// Compiles ECMA-262 patterns used by `pattern`, `patternProperties` and the `regex` format.
+pub trait RegexEngine: Send + Sync + 'static {
+    /// Compile the pattern, failing for invalid patterns or ones exceeding the engine limits.
+    fn compile(&self, pattern: &str) -> BuildResult<BoxedRegex>;
+}
+
+/// The engine of builders that do not configure one, shared so they can share compiled schemas.
+pub(crate) fn default_engine() -> Arc<dyn RegexEngine> {
+    static ENGINE: OnceLock<Arc<dyn RegexEngine>> = OnceLock::new();
+    Arc::clone(ENGINE.get_or_init(|| Arc::new(FancyRegexEngine::default())))
+}
+
+/// A compiled regular expression.
+pub trait Regex: Send + Sync + core::fmt::Debug + 'static {
+    /// Whether the regular expression matches anywhere in the string.
+    fn is_match(&self, string: &str) -> bool;
+}
+
+/// The default engine supporting all ECMA-262 features.
+///
+/// Patterns are matched in linear time by `regex`, unless they contain lookarounds or
+/// backreferences, which are executed by the backtracking `fancy-regex` engine. A match that
+/// exceeds the backtrack limit is treated as no match.
+#[derive(Debug, Clone, Default)]
+pub struct FancyRegexEngine {
+    size_limit: Option<usize>,
+    dfa_size_limit: Option<usize>,
+    backtrack_limit: Option<usize>,
+    identity_escapes: bool,
+}
+
+impl FancyRegexEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Approximate size limit in bytes of a compiled regular expression.
+    pub fn size_limit(mut self, limit: usize) -> Self {
+        self.size_limit = Some(limit);
+        self
+    }
+    /// Approximate size limit in bytes of the cache used by the lazy DFA.
+    pub fn dfa_size_limit(mut self, limit: usize) -> Self {
+        self.dfa_size_limit = Some(limit);
+        self
+    }
+    /// Maximum number of backtracking steps for a single match.
+    pub fn backtrack_limit(mut self, limit: usize) -> Self {
+        self.backtrack_limit = Some(limit);
+        self
+    }
+    /// Accept escapes of characters without a special meaning, e.g. `\a` matching `a`.
+    ///
+    /// Such escapes are valid without the `u` flag, though not in the ECMA-262 dialect of JSON
+    /// Schema.
+    pub fn identity_escapes(mut self, yes: bool) -> Self {
+        self.identity_escapes = yes;
+        self
+    }
+}
+
+impl RegexEngine for FancyRegexEngine {
+    fn compile(&self, pattern: &str) -> BuildResult<BoxedRegex> {
+        let translation = translate(pattern, self.identity_escapes)?;
+        if translation.backtracking.is_none() {
+            return compile_linear(
+                pattern,
+                &translation.pattern,
+                self.size_limit,
+                self.dfa_size_limit,
+            );
+        }
+        let mut builder = fancy_regex::RegexBuilder::new(&translation.pattern);
+        if let Some(limit) = self.size_limit {
+            builder.delegate_size_limit(limit);
+        }
+        if let Some(limit) = self.dfa_size_limit {
+            builder.delegate_dfa_size_limit(limit);
+        }
+        if let Some(limit) = self.backtrack_limit {
+            builder.backtrack_limit(limit);
+        }
+        let regex = builder
+            .build()
+            .map_err(|error| BuildError::invalid_regex(pattern, error))?;
+        Ok(Box::new(BacktrackingRegex(regex)))
+    }
+}
+
+/// An engine that guarantees matching in linear time, suitable for untrusted schemas.
+///
+/// Patterns with lookarounds or backreferences are rejected.
+#[derive(Debug, Clone, Default)]
+pub struct LinearRegexEngine {
+    size_limit: Option<usize>,
+    dfa_size_limit: Option<usize>,
+    identity_escapes: bool,
+}
+
+impl LinearRegexEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Approximate size limit in bytes of a compiled regular expression.
+    pub fn size_limit(mut self, limit: usize) -> Self {
+        self.size_limit = Some(limit);
+        self
+    }
+    /// Approximate size limit in bytes of the cache used by the lazy DFA.
+    pub fn dfa_size_limit(mut self, limit: usize) -> Self {
+        self.dfa_size_limit = Some(limit);
+        self
+    }
+    /// Accept escapes of characters without a special meaning, e.g. `\a` matching `a`.
+    ///
+    /// Such escapes are valid without the `u` flag, though not in the ECMA-262 dialect of JSON
+    /// Schema.
+    pub fn identity_escapes(mut self, yes: bool) -> Self {
+        self.identity_escapes = yes;
+        self
+    }
+}
+
+impl RegexEngine for LinearRegexEngine {
+    fn compile(&self, pattern: &str) -> BuildResult<BoxedRegex> {
+        let translation = translate(pattern, self.identity_escapes)?;
+        if let Some(error) = translation.backtracking {
+            return Err(BuildError::unsupported_regex(pattern, error));
+        }
+        compile_linear(
+            pattern,
+            &translation.pattern,
+            self.size_limit,
+            self.dfa_size_limit,
+        )
+    }
+}
+
+fn translate(pattern: &str, identity_escapes: bool) -> BuildResult<ecma::Translation> {
+    let translation = ecma::translate(pattern, identity_escapes)
+        .map_err(|error| BuildError::invalid_regex(pattern, error))?;
+    match translation.unsupported {
+        Some(error) => Err(BuildError::unsupported_regex(pattern, error)),
+        None => Ok(translation),
+    }
+}
+
+fn compile_linear(
+    pattern: &str,
+    translated: &str,
+    size_limit: Option<usize>,
+    dfa_size_limit: Option<usize>,
+) -> BuildResult<BoxedRegex> {
+    let mut builder = regex::RegexBuilder::new(translated);
+    if let Some(limit) = size_limit {
+        builder.size_limit(limit);
+    }
+    if let Some(limit) = dfa_size_limit {
+        builder.dfa_size_limit(limit);
+    }
+    let regex = builder
+        .build()
+        .map_err(|error| BuildError::invalid_regex(pattern, error))?;
+    Ok(Box::new(LinearRegex(regex)))
+}
+
+#[derive(Debug)]
+struct LinearRegex(regex::Regex);
+
+impl Regex for LinearRegex {
+    fn is_match(&self, string: &str) -> bool {
+        self.0.is_match(string)
+    }
+}
+
+#[derive(Debug)]
+struct BacktrackingRegex(fancy_regex::Regex);
+
+impl Regex for BacktrackingRegex {
+    fn is_match(&self, string: &str) -> bool {
+        self.0.is_match(string).unwrap_or(false)
+    }
+}
+
+/// A compiled regular expression along with its source pattern.
+#[derive(Debug, Clone)]
+pub(crate) struct CompiledRegex {
+    pattern: Box<str>,
+    regex: Arc<dyn Regex>,
+}
+
+impl CompiledRegex {
+    pub(crate) fn new(engine: &dyn RegexEngine, pattern: &str) -> BuildResult<CompiledRegex> {
+        Ok(CompiledRegex {
+            pattern: pattern.into(),
+            regex: Arc::from(engine.compile(pattern)?),
+        })
+    }
+    /// Only the pattern is stored, it is compiled again by the engine of the loading builder.
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.str(&self.pattern);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<CompiledRegex> {
+        let engine = reader.regex_engine.as_ref();
+        CompiledRegex::new(engine, reader.str()?)
+    }
+    pub(crate) fn as_str(&self) -> &str {
+        &self.pattern
+    }
+    pub(crate) fn is_match(&self, string: &str) -> bool {
+        self.regex.is_match(string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("^\\d+$", "123", true)]
+    #[test_case("^\\d+$", "\u{0661}\u{0662}", false; "non-ascii digits")]
+    #[test_case("^\\w+$", "abc_1", true)]
+    #[test_case("^\\w+$", "\u{e9}t\u{e9}", false; "non-ascii word")]
+    #[test_case("^\\s$", "\u{feff}", true; "bom is whitespace")]
+    #[test_case("^\\S$", "a", true)]
+    #[test_case("^[\\D]$", "a", true)]
+    #[test_case("^[^\\d\\s]+$", "ab", true)]
+    #[test_case("^\\p{Letter}+$", "\u{e9}t\u{e9}", true; "unicode property")]
+    #[test_case("^.$", "\n", false)]
+    #[test_case("^.$", "\u{1F600}", true; "astral dot")]
+    #[test_case("a\\b", "a b", true)]
+    #[test_case("a\\b", "a\u{e9}", true; "ascii word boundary")]
+    #[test_case("^[^]$", "x", true)]
+    #[test_case("[]", "x", false)]
+    #[test_case("^[a-]+$", "a-a", true)]
+    #[test_case("^\\x41\\u0042\\u{43}$", "ABC", true)]
+    #[test_case("^\\/\\.\\*$", "/.*", true)]
+    #[test_case("^a b#c$", "a b#c", true)]
+    #[test_case("^a\\-b$", "a-b", true)]
+    #[test_case("^(?<word>\\w+) \\k<word>$", "hi hi", true)]
+    #[test_case("^(?<word>\\w+) \\k<word>$", "hi ho", false)]
+    #[test_case("^(a)(b)\\2\\1$", "abba", true)]
+    #[test_case("^(?!foo)\\w+$", "foobar", false)]
+    #[test_case("^(?!foo)\\w+$", "barfoo", true)]
+    #[test_case("(?<=\\$)\\d+", "$42", true)]
+    #[test_case("(?<!\\$)\\b\\d+", "$42", false)]
+    #[test_case("(?=a)\\Bb", "ab", false)]
+    #[test_case("(?!x)\u{e9}\\B\u{e9}", "\u{e9}\u{e9}", true; "ascii non word boundary")]
+    fn test_match(pattern: &str, string: &str, expected: bool) {
+        let regex = FancyRegexEngine::new()
+            .compile(pattern)
+            .expect("Valid pattern");
+        assert_eq!(regex.is_match(string), expected);
+    }
+
+    #[test_case(
+        "\\uD800",
+        "'\\uD800' is not supported: Lone surrogates are not supported at position 0"
+    )]
+    #[test_case(
+        "a[",
+        "'a[' is not a valid regular expression: Unterminated character class at position 1"
+    )]
+    fn test_build_error(pattern: &str, expected: &str) {
+        let error = FancyRegexEngine::new()
+            .compile(pattern)
+            .expect_err("Should fail");
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[test]
+    fn test_identity_escapes() {
+        assert!(FancyRegexEngine::new().compile("\\a").is_err());
+        assert!(LinearRegexEngine::new().compile("\\a").is_err());
+        let regex = FancyRegexEngine::new()
+            .identity_escapes(true)
+            .compile("^\\a\\_$")
+            .expect("Valid pattern");
+        assert!(regex.is_match("a_"));
+        let regex = LinearRegexEngine::new()
+            .identity_escapes(true)
+            .compile("^[\\a-\\e]$")
+            .expect("Valid pattern");
+        assert!(regex.is_match("b"));
+    }
+
+    #[test_case("^\\d+\\b", true)]
+    #[test_case("(a)\\1", false)]
+    #[test_case("(?=a)", false)]
+    fn test_linear(pattern: &str, expected: bool) {
+        assert_eq!(LinearRegexEngine::new().compile(pattern).is_ok(), expected);
+    }
+
+    #[test]
+    fn test_linear_error() {
+        let error = LinearRegexEngine::new()
+            .compile("x(?!y)")
+            .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "'x(?!y)' is not supported: Lookarounds are not supported at position 1"
+        );
+    }
+
+    #[test]
+    fn test_size_limit() {
+        assert!(FancyRegexEngine::new().compile("\\w{1000}").is_ok());
+        assert!(FancyRegexEngine::new()
+            .size_limit(100)
+            .compile("\\w{1000}")
+            .is_err());
+        assert!(LinearRegexEngine::new()
+            .size_limit(100)
+            .compile("\\w{1000}")
+            .is_err());
+    }
+
+    #[test]
+    fn test_backtrack_limit() {
+        let regex = FancyRegexEngine::new()
+            .backtrack_limit(10)
+            .compile("^(?=a)(a+)+$")
+            .expect("Valid pattern");
+        assert!(!regex.is_match("aaaaaaaaaaaaaaaaaaaaaaaab"));
+        assert!(regex.is_match("a"));
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test_case(&serde_json::json!({"pattern": "(?=a)"}))]
+    #[test_case(&serde_json::json!({"patternProperties": {"(a)\\1": true}}))]
+    #[test_case(&serde_json::json!({"additionalProperties": false, "patternProperties": {"(?!a)": true}}))]
+    fn test_builder_engine(schema: &serde_json::Value) {
+        assert!(crate::blocking::validator_for(schema).is_ok());
+        assert!(crate::blocking::ValidatorBuilder::default()
+            .regex_engine(LinearRegexEngine::new())
+            .build(schema)
+            .is_err());
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn test_builder_engine_format() {
+        let schema = serde_json::json!({"format": "regex"});
+        let instance = serde_json::json!("(?<=a)b");
+        let validator = crate::blocking::ValidatorBuilder::default()
+            .draft(crate::Draft::Draft07)
+            .build(&schema)
+            .expect("Valid schema");
+        assert!(validator.is_valid(&instance));
+        let validator = crate::blocking::ValidatorBuilder::default()
+            .draft(crate::Draft::Draft07)
+            .regex_engine(LinearRegexEngine::new())
+            .build(&schema)
+            .expect("Valid schema");
+        assert!(!validator.is_valid(&instance));
+    }
+}
@@ -13,7 +13,7 @@ pub trait ReferenceResolver<J: Json>: Send + Sync {
 pub(crate) struct DefaultResolver;
 
 impl<J: Json> ReferenceResolver<J> for DefaultResolver {
-    async fn resolve_external(&self, url: &str) -> BuildResult<J> {
+    async fn resolve_external(&self, _url: &str) -> BuildResult<J> {
         Ok(J::from_str("{}")?)
     }
 }
@@ -0,0 +1,210 @@
+//! Binary snapshots of compiled validators.
+//!
+//! A snapshot starts with a magic number and the version of this crate, followed by the draft,
+//! all compiled schemas and their locations within the root schema. It is loaded only by the same
+//! crate version, as the layout follows the internal representation. Keywords refer to other
+//! schemas by their id, so references are restored as they are. Regular expressions and built-in
+//! formats are stored by their source and created anew when loading.
+use std::sync::Arc;
+
+use crate::{graph::SchemaId, BuildError, BuildResult, Draft, RegexEngine};
+
+const MAGIC: &[u8; 4] = b"JSVS";
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug, Default)]
+pub(crate) struct Writer {
+    buffer: Vec<u8>,
+}
+
+impl Writer {
+    pub(crate) fn new(draft: Draft) -> Writer {
+        let mut writer = Writer::default();
+        writer.buffer.extend_from_slice(MAGIC);
+        writer.str(VERSION);
+        writer.draft(draft);
+        writer
+    }
+    pub(crate) fn finish(self) -> Vec<u8> {
+        self.buffer
+    }
+    pub(crate) fn u8(&mut self, value: u8) {
+        self.buffer.push(value);
+    }
+    pub(crate) fn bool(&mut self, value: bool) {
+        self.u8(u8::from(value));
+    }
+    /// Unsigned LEB128, most lengths & ids fit into a single byte.
+    pub(crate) fn u64(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buffer.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.buffer.push(value as u8);
+    }
+    pub(crate) fn usize(&mut self, value: usize) {
+        self.u64(value as u64);
+    }
+    pub(crate) fn f64(&mut self, value: f64) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+    pub(crate) fn str(&mut self, value: &str) {
+        self.usize(value.len());
+        self.buffer.extend_from_slice(value.as_bytes());
+    }
+    fn draft(&mut self, draft: Draft) {
+        self.u8(match draft {
+            Draft::Draft04 => 0,
+            Draft::Draft06 => 1,
+            Draft::Draft07 => 2,
+            Draft::Draft201909 => 3,
+            Draft::Draft202012 => 4,
+        });
+    }
+    pub(crate) fn schema(&mut self, id: SchemaId) {
+        self.u64(u64::from(id.get()));
+    }
+    pub(crate) fn schemas(&mut self, ids: &[SchemaId]) {
+        self.usize(ids.len());
+        for id in ids {
+            self.schema(*id);
+        }
+    }
+    pub(crate) fn strings(&mut self, values: &[Box<str>]) {
+        self.usize(values.len());
+        for value in values {
+            self.str(value);
+        }
+    }
+    pub(crate) fn named_schemas(&mut self, values: &[(Box<str>, SchemaId)]) {
+        self.usize(values.len());
+        for (name, id) in values {
+            self.str(name);
+            self.schema(*id);
+        }
+    }
+}
+
+pub(crate) struct Reader<'b> {
+    bytes: &'b [u8],
+    /// Number of schemas in the snapshot, ids beyond it are rejected.
+    schemas: usize,
+    pub(crate) draft: Draft,
+    pub(crate) regex_engine: &'b Arc<dyn RegexEngine>,
+}
+
+impl<'b> Reader<'b> {
+    /// Check the header and read the draft.
+    pub(crate) fn new(
+        bytes: &'b [u8],
+        regex_engine: &'b Arc<dyn RegexEngine>,
+    ) -> BuildResult<Reader<'b>> {
+        let Some(bytes) = bytes.strip_prefix(MAGIC) else {
+            return Err(BuildError::invalid_snapshot());
+        };
+        let mut reader = Reader {
+            bytes,
+            schemas: 0,
+            draft: Draft::latest(),
+            regex_engine,
+        };
+        let version = reader.str()?;
+        if version != VERSION {
+            return Err(BuildError::incompatible_snapshot(version));
+        }
+        reader.draft = reader.read_draft()?;
+        Ok(reader)
+    }
+    /// Set the number of schemas in the graph being read and return the previous one.
+    pub(crate) fn set_schemas(&mut self, schemas: usize) -> usize {
+        core::mem::replace(&mut self.schemas, schemas)
+    }
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+    fn take(&mut self, length: usize) -> BuildResult<&'b [u8]> {
+        if length > self.bytes.len() {
+            return Err(BuildError::invalid_snapshot());
+        }
+        let (head, tail) = self.bytes.split_at(length);
+        self.bytes = tail;
+        Ok(head)
+    }
+    pub(crate) fn u8(&mut self) -> BuildResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+    pub(crate) fn bool(&mut self) -> BuildResult<bool> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(BuildError::invalid_snapshot()),
+        }
+    }
+    pub(crate) fn u64(&mut self) -> BuildResult<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(BuildError::invalid_snapshot())
+    }
+    pub(crate) fn usize(&mut self) -> BuildResult<usize> {
+        usize::try_from(self.u64()?).map_err(|_| BuildError::invalid_snapshot())
+    }
+    /// A length of a sequence, each item takes at least one byte.
+    pub(crate) fn len(&mut self) -> BuildResult<usize> {
+        let length = self.usize()?;
+        if length > self.bytes.len() {
+            return Err(BuildError::invalid_snapshot());
+        }
+        Ok(length)
+    }
+    pub(crate) fn f64(&mut self) -> BuildResult<f64> {
+        let bytes = self.take(8)?.try_into().expect("Eight bytes");
+        Ok(f64::from_le_bytes(bytes))
+    }
+    pub(crate) fn str(&mut self) -> BuildResult<&'b str> {
+        let length = self.len()?;
+        core::str::from_utf8(self.take(length)?).map_err(|_| BuildError::invalid_snapshot())
+    }
+    /// One of the given names, e.g. a keyword stored as a static string.
+    pub(crate) fn name(&mut self, names: &[&'static str]) -> BuildResult<&'static str> {
+        let name = self.str()?;
+        names
+            .iter()
+            .find(|candidate| **candidate == name)
+            .copied()
+            .ok_or_else(BuildError::invalid_snapshot)
+    }
+    fn read_draft(&mut self) -> BuildResult<Draft> {
+        match self.u8()? {
+            0 => Ok(Draft::Draft04),
+            1 => Ok(Draft::Draft06),
+            2 => Ok(Draft::Draft07),
+            3 => Ok(Draft::Draft201909),
+            4 => Ok(Draft::Draft202012),
+            _ => Err(BuildError::invalid_snapshot()),
+        }
+    }
+    pub(crate) fn schema(&mut self) -> BuildResult<SchemaId> {
+        let id = self.u64()?;
+        if id >= self.schemas as u64 {
+            return Err(BuildError::invalid_snapshot());
+        }
+        Ok(SchemaId::new(id as u32))
+    }
+    pub(crate) fn schemas(&mut self) -> BuildResult<Vec<SchemaId>> {
+        (0..self.len()?).map(|_| self.schema()).collect()
+    }
+    pub(crate) fn strings(&mut self) -> BuildResult<Vec<Box<str>>> {
+        (0..self.len()?).map(|_| Ok(self.str()?.into())).collect()
+    }
+    pub(crate) fn named_schemas(&mut self) -> BuildResult<Vec<(Box<str>, SchemaId)>> {
+        (0..self.len()?)
+            .map(|_| Ok((self.str()?.into(), self.schema()?)))
+            .collect()
+    }
+}
@@ -3,11 +3,19 @@ use std::{collections::HashMap, sync::Arc};
 use jsonlike::Json;
 
 use crate::{
-    compiler,
+    compiler::{self, CompilationCache},
+    content::{self, ContentEncodingFactory, ContentMediaTypeFactory},
     drafts::{draft_from_schema, Draft},
-    format::FormatFactory,
+    format::{
+        AsyncFormatFactory, ErasedAsyncFormatFactory, ErasedFormatFactory, FormatFactory,
+        UnknownFormatPolicy,
+    },
+    regex_engine::{self, RegexEngine},
     resolver::DefaultResolver,
-    vocabulary::KeywordFactory,
+    vocabulary::{
+        custom::{AsyncKeywordFactory, Erased, ErasedAsyncKeywordFactory, ErasedKeywordFactory},
+        CustomVocabulary, KeywordFactory,
+    },
     BuildResult, ReferenceResolver, Validator,
 };
 
@@ -16,11 +24,28 @@ pub async fn validator_for<J: Json>(schema: &J) -> BuildResult<Validator<J>> {
     ValidatorBuilder::default().draft(draft).build(schema).await
 }
 
+/// Configures how schemas are compiled into [`Validator`]s.
+///
+/// Schemas are borrowed for the builder's lifetime `'a`, because custom keywords, formats,
+/// vocabularies and content handlers are created from values within them. Validators do not
+/// borrow the schema, but it has to outlive the builder, so bind it to a variable instead of
+/// passing a temporary like `&json!({..})`.
 pub struct ValidatorBuilder<'a, J: Json> {
     pub(crate) draft: Draft,
     pub(crate) resolver: Arc<dyn ReferenceResolver<J>>,
-    pub(crate) formats: HashMap<String, Arc<dyn FormatFactory<'a, J>>>,
-    pub(crate) keywords: HashMap<String, Arc<dyn KeywordFactory<'a, J>>>,
+    pub(crate) formats: HashMap<String, Arc<dyn ErasedFormatFactory<'a, J>>>,
+    pub(crate) async_formats: HashMap<String, Arc<dyn ErasedAsyncFormatFactory<'a, J>>>,
+    pub(crate) keywords: HashMap<String, Arc<dyn ErasedKeywordFactory<'a, J>>>,
+    pub(crate) async_keywords: HashMap<String, Arc<dyn ErasedAsyncKeywordFactory<'a, J>>>,
+    pub(crate) vocabularies: HashMap<String, CustomVocabulary<'a, J>>,
+    pub(crate) meta_schemas: HashMap<String, J>,
+    pub(crate) validate_formats: Option<bool>,
+    pub(crate) unknown_formats: UnknownFormatPolicy,
+    pub(crate) validate_content: Option<bool>,
+    pub(crate) content_encodings: HashMap<String, Arc<dyn ContentEncodingFactory<'a, J>>>,
+    pub(crate) content_media_types: HashMap<String, Arc<dyn ContentMediaTypeFactory<'a, J>>>,
+    pub(crate) regex_engine: Arc<dyn RegexEngine>,
+    pub(crate) cache: Option<Arc<CompilationCache<J>>>,
 }
 
 impl<'a, J: Json> Default for ValidatorBuilder<'a, J> {
@@ -29,15 +54,37 @@ impl<'a, J: Json> Default for ValidatorBuilder<'a, J> {
             draft: Draft::latest(),
             resolver: Arc::new(DefaultResolver),
             formats: HashMap::default(),
+            async_formats: HashMap::default(),
             keywords: HashMap::default(),
+            async_keywords: HashMap::default(),
+            vocabularies: HashMap::default(),
+            meta_schemas: HashMap::default(),
+            validate_formats: None,
+            unknown_formats: UnknownFormatPolicy::default(),
+            validate_content: None,
+            content_encodings: HashMap::default(),
+            content_media_types: HashMap::default(),
+            regex_engine: regex_engine::default_engine(),
+            cache: None,
         }
     }
 }
 
 impl<'a, J: Json> ValidatorBuilder<'a, J> {
-    pub async fn build(&self, schema: &J) -> BuildResult<Validator<J>> {
+    pub async fn build(&self, schema: &'a J) -> BuildResult<Validator<J>> {
         // TODO: Resolve references
-        compiler::compile::<J>(schema, self.draft)
+        compiler::compile::<J>(schema, self)
+    }
+    /// Build a validator for a subschema, e.g. `#/$defs/Address`, or an anchor.
+    ///
+    /// The rest of the schema is still available to references from the subschema, which are
+    /// resolved against the base URI of the root.
+    pub async fn build_at(&self, schema: &'a J, reference: &str) -> BuildResult<Validator<J>> {
+        compiler::compile_at::<J>(schema, self, Some(reference))
+    }
+    /// Load a validator from a snapshot, compiling regular expressions with the configured engine.
+    pub fn from_bytes(&self, bytes: &[u8]) -> BuildResult<Validator<J>> {
+        Validator::decode(bytes, &self.regex_engine)
     }
     pub fn draft(&mut self, draft: Draft) -> &mut ValidatorBuilder<'a, J> {
         self.draft = draft;
@@ -47,18 +94,130 @@ impl<'a, J: Json> ValidatorBuilder<'a, J> {
         self.resolver = Arc::new(resolver);
         self
     }
-    pub fn format<F>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
+    pub fn format<F, M>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
+    where
+        F: FormatFactory<'a, J, M>,
+        M: 'a,
+    {
+        self.formats
+            .insert(name.into(), Arc::new(Erased::new(factory)));
+        self
+    }
+    /// Register a format that is checked asynchronously, see [`Validator::validate_async`].
+    ///
+    /// It takes precedence over synchronous and built-in formats of the same name. Synchronous
+    /// validation methods fail if they reach such a format, see [`Validator::is_valid`].
+    pub fn async_format<F, M>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
+    where
+        F: AsyncFormatFactory<'a, J, M>,
+        M: 'a,
+    {
+        self.async_formats
+            .insert(name.into(), Arc::new(Erased::new(factory)));
+        self
+    }
+    /// Whether `format` should fail validation or only produce annotations.
+    ///
+    /// By default, formats are asserted in Draft 4, 6 & 7 and only annotated in later drafts
+    /// unless the meta-schema enables the format-assertion vocabulary, which always asserts.
+    pub fn validate_formats(&mut self, yes: bool) -> &mut Self {
+        self.validate_formats = Some(yes);
+        self
+    }
+    /// How to handle formats without a registered implementation.
+    pub fn unknown_formats(&mut self, policy: UnknownFormatPolicy) -> &mut Self {
+        self.unknown_formats = policy;
+        self
+    }
+    /// Whether `contentEncoding`, `contentMediaType` and `contentSchema` should fail validation
+    /// or only produce annotations.
+    ///
+    /// By default, they are asserted in Draft 7 and only annotated in later drafts.
+    pub fn validate_content(&mut self, yes: bool) -> &mut Self {
+        self.validate_content = Some(yes);
+        self
+    }
+    /// Register a decoder for `contentEncoding`, overriding the built-in one.
+    ///
+    /// Encoding names are case-insensitive.
+    pub fn content_encoding<F>(&mut self, name: impl AsRef<str>, factory: F) -> &mut Self
     where
-        F: FormatFactory<'a, J>,
+        F: ContentEncodingFactory<'a, J>,
     {
-        self.formats.insert(name.into(), Arc::new(factory));
+        self.content_encodings
+            .insert(name.as_ref().to_ascii_lowercase(), Arc::new(factory));
         self
     }
-    pub fn keyword<F>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
+    /// Register a parser for `contentMediaType`, overriding the built-in one.
+    ///
+    /// The parsed document is validated against `contentSchema`. Media type parameters like
+    /// `charset` are ignored when looking up the parser.
+    pub fn content_media_type<F>(&mut self, name: impl AsRef<str>, factory: F) -> &mut Self
     where
-        F: KeywordFactory<'a, J>,
+        F: ContentMediaTypeFactory<'a, J>,
     {
-        self.keywords.insert(name.into(), Arc::new(factory));
+        self.content_media_types
+            .insert(content::essence(name.as_ref()), Arc::new(factory));
+        self
+    }
+    /// The engine for `pattern`, `patternProperties` and the `regex` format.
+    ///
+    /// Defaults to [`crate::FancyRegexEngine`], which supports all ECMA-262 features.
+    pub fn regex_engine(&mut self, engine: impl RegexEngine) -> &mut Self {
+        self.regex_engine = Arc::new(engine);
+        self
+    }
+    /// Share compiled reference targets with other validators using the same cache.
+    pub fn cache(&mut self, cache: Arc<CompilationCache<J>>) -> &mut Self {
+        self.cache = Some(cache);
+        self
+    }
+    /// Register a custom keyword.
+    ///
+    /// Custom keywords take precedence over keywords of custom vocabularies and over built-in
+    /// keywords of the same name, regardless of the vocabularies enabled by the meta-schema.
+    /// Replacing a built-in keyword does not change how its siblings are compiled, e.g.
+    /// `unevaluatedProperties` does not see properties evaluated by a replaced `properties`.
+    /// In drafts where `$ref` overrides its siblings, custom keywords next to it are ignored too,
+    /// unless the custom keyword is `$ref` itself.
+    pub fn keyword<F, M>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
+    where
+        F: KeywordFactory<'a, J, M>,
+        M: 'a,
+    {
+        self.keywords
+            .insert(name.into(), Arc::new(Erased::new(factory)));
+        self
+    }
+    /// Register a custom keyword that is checked asynchronously, see
+    /// [`Validator::validate_async`].
+    ///
+    /// It takes precedence over all other keywords of the same name. Synchronous validation
+    /// methods fail if they reach such a keyword, see [`Validator::is_valid`].
+    pub fn async_keyword<F, M>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
+    where
+        F: AsyncKeywordFactory<'a, J, M>,
+        M: 'a,
+    {
+        self.async_keywords
+            .insert(name.into(), Arc::new(Erased::new(factory)));
+        self
+    }
+    /// Register a custom vocabulary.
+    ///
+    /// Its keywords are enabled for schemas whose meta-schema lists `uri` in `$vocabulary`.
+    pub fn vocabulary(
+        &mut self,
+        uri: impl Into<String>,
+        vocabulary: CustomVocabulary<'a, J>,
+    ) -> &mut Self {
+        self.vocabularies.insert(uri.into(), vocabulary);
+        self
+    }
+    /// Register a meta-schema, so its `$vocabulary` is taken into account for schemas that
+    /// reference it via `$schema`.
+    pub fn meta_schema(&mut self, uri: impl Into<String>, meta_schema: J) -> &mut Self {
+        self.meta_schemas.insert(uri.into(), meta_schema);
         self
     }
 }
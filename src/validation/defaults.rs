@@ -0,0 +1,166 @@
+//! Insertion of `default` values into instances.
+//!
+//! Defaults of absent properties are inserted before descending into property values, so nested
+//! defaults fill inserted objects too. Branches of `allOf`, `oneOf` and `if`-`then`-`else` apply
+//! only if the instance, as it is when they are reached, is valid against them.
+use jsonlike::prelude::*;
+
+use crate::{
+    graph::SchemaId,
+    vocabulary::{self, KeywordValue, SchemaGraph},
+};
+
+pub(crate) fn apply<J: JsonMut>(graph: &SchemaGraph<J>, schema: SchemaId, instance: &mut J) {
+    for keyword in graph.keywords(schema) {
+        match keyword {
+            KeywordValue::Ref(keyword) => apply(graph, keyword.schema, instance),
+            KeywordValue::Shared(keyword) => apply(&keyword.graph, SchemaId::ROOT, instance),
+            KeywordValue::AllOf(keyword) => {
+                for schema in &keyword.schemas {
+                    if vocabulary::is_valid(graph, *schema, instance) {
+                        apply(graph, *schema, instance);
+                    }
+                }
+            }
+            KeywordValue::OneOf(keyword) => {
+                let mut valid = keyword
+                    .schemas
+                    .iter()
+                    .filter(|schema| vocabulary::is_valid(graph, **schema, instance));
+                if let (Some(schema), None) = (valid.next().copied(), valid.next()) {
+                    apply(graph, schema, instance);
+                }
+            }
+            KeywordValue::IfThenElse(keyword) => {
+                let branch = if vocabulary::is_valid(graph, keyword.condition, instance) {
+                    keyword.then
+                } else {
+                    keyword.otherwise
+                };
+                if vocabulary::is_valid(graph, branch, instance) {
+                    apply(graph, branch, instance);
+                }
+            }
+            KeywordValue::Properties(keyword) => {
+                let Some(object) = instance.as_object_mut() else {
+                    continue;
+                };
+                for (name, default) in &keyword.defaults {
+                    if !object.contains_key(name) {
+                        // Values that the instance type can not represent are left out
+                        if let Ok(value) = default.to_json() {
+                            object.insert(name, value);
+                        }
+                    }
+                }
+                for (name, schema) in &keyword.properties {
+                    if let Some(value) = object.get_mut(name) {
+                        apply(graph, *schema, value);
+                    }
+                }
+            }
+            KeywordValue::PatternProperties(keyword) => {
+                let Some(object) = instance.as_object_mut() else {
+                    continue;
+                };
+                for key in keys(object) {
+                    for (regex, schema) in &keyword.patterns {
+                        if regex.is_match(&key) {
+                            if let Some(value) = object.get_mut(&key) {
+                                apply(graph, *schema, value);
+                            }
+                        }
+                    }
+                }
+            }
+            KeywordValue::AdditionalProperties(keyword) if !keyword.forbidden => {
+                let Some(object) = instance.as_object_mut() else {
+                    continue;
+                };
+                for key in keys(object) {
+                    if keyword.is_additional(&key) {
+                        if let Some(value) = object.get_mut(&key) {
+                            apply(graph, keyword.schema, value);
+                        }
+                    }
+                }
+            }
+            KeywordValue::PrefixItems(keyword) => {
+                let Some(array) = instance.as_array_mut() else {
+                    continue;
+                };
+                for (idx, schema) in keyword.schemas.iter().enumerate() {
+                    let Some(item) = array.get_mut(idx) else {
+                        break;
+                    };
+                    apply(graph, *schema, item);
+                }
+            }
+            KeywordValue::Items(keyword) => {
+                let Some(array) = instance.as_array_mut() else {
+                    continue;
+                };
+                let mut idx = keyword.skip;
+                while let Some(item) = array.get_mut(idx) {
+                    apply(graph, keyword.schema, item);
+                    idx += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Keys are copied upfront, as values are modified while iterating.
+fn keys<O: JsonObject>(object: &O) -> Vec<String> {
+    object
+        .iter()
+        .filter_map(|(key, _)| Some(key.ok()?.as_ref().to_owned()))
+        .collect()
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use crate::{blocking::validator_for, Validator};
+
+    #[test_case(&json!({"properties": {"server": {"default": {}, "properties": {"port": {"default": 8080}, "host": {"default": "localhost"}}}}}), json!({}), &json!({"server": {"port": 8080, "host": "localhost"}}))]
+    #[test_case(&json!({"properties": {"server": {"default": {}, "properties": {"port": {"default": 8080}}}}}), json!({"server": {"port": 1}}), &json!({"server": {"port": 1}}))]
+    #[test_case(&json!({"properties": {"port": {"default": 8080}}}), json!([]), &json!([]))]
+    #[test_case(&json!({"allOf": [{"properties": {"a": {"default": 1}}}, {"required": ["x"], "properties": {"b": {"default": 2}}}]}), json!({}), &json!({"a": 1}))]
+    #[test_case(&json!({"oneOf": [{"properties": {"kind": {"const": "a"}, "x": {"default": 1}}, "required": ["kind"]}, {"properties": {"kind": {"const": "b"}, "y": {"default": 2}}, "required": ["kind"]}]}), json!({"kind": "b"}), &json!({"kind": "b", "y": 2}))]
+    #[test_case(&json!({"oneOf": [{"properties": {"x": {"default": 1}}}, {"properties": {"y": {"default": 2}}}]}), json!({}), &json!({}))]
+    #[test_case(&json!({"if": {"properties": {"tls": {"const": true}}, "required": ["tls"]}, "then": {"properties": {"port": {"default": 443}}}, "else": {"properties": {"port": {"default": 80}}}}), json!({"tls": true}), &json!({"tls": true, "port": 443}))]
+    #[test_case(&json!({"if": {"properties": {"tls": {"const": true}}, "required": ["tls"]}, "then": {"properties": {"port": {"default": 443}}}, "else": {"properties": {"port": {"default": 80}}}}), json!({}), &json!({"port": 80}))]
+    #[test_case(&json!({"items": {"$ref": "#/$defs/item"}, "$defs": {"item": {"properties": {"a": {"default": [1]}}}}}), json!([{}, {"a": 2}, 1]), &json!([{"a": [1]}, {"a": 2}, 1]))]
+    #[test_case(&json!({"prefixItems": [{"properties": {"a": {"default": null}}}], "items": {"properties": {"b": {"default": "x"}}}}), json!([{}, {}]), &json!([{"a": null}, {"b": "x"}]))]
+    #[test_case(&json!({"patternProperties": {"^x": {"properties": {"a": {"default": 1}}}}, "additionalProperties": {"properties": {"b": {"default": 2.5}}}}), json!({"xy": {}, "z": {}}), &json!({"xy": {"a": 1}, "z": {"b": 2.5}}))]
+    fn test_apply_defaults(schema: &Value, mut instance: Value, expected: &Value) {
+        let validator = validator_for(schema).expect("Invalid schema");
+        let loaded =
+            Validator::<Value>::from_bytes(&validator.to_bytes().expect("Serializable validator"))
+                .expect("Valid snapshot");
+        let mut copy = instance.clone();
+        // Branches that do not apply may leave the instance invalid
+        let valid = validator.is_valid(expected);
+        assert_eq!(validator.apply_defaults(&mut instance).is_ok(), valid);
+        assert_eq!(&instance, expected);
+        assert_eq!(loaded.apply_defaults(&mut copy).is_ok(), valid);
+        assert_eq!(&copy, expected);
+    }
+
+    #[test]
+    fn test_invalid_default() {
+        let schema = json!({"properties": {"port": {"type": "integer", "default": "80"}}});
+        let validator = validator_for(&schema).expect("Invalid schema");
+        let mut instance = json!({});
+        let error = validator
+            .apply_defaults(&mut instance)
+            .expect_err("Invalid default");
+        assert_eq!(instance, json!({"port": "80"}));
+        assert_eq!(error.instance_location().to_string(), "/port");
+        assert_eq!(error.to_string(), "Value is not of type 'integer'");
+    }
+}
@@ -0,0 +1,176 @@
+//! Asynchronous keywords and formats.
+//!
+//! Built-in keywords are synchronous, so instead of duplicating the whole validation logic,
+//! asynchronous checks are deferred: a synchronous pass treats checks without a known result as
+//! valid and records them, then they are awaited and the pass is repeated with their results.
+//! A repeated pass may take other branches, e.g. in `anyOf`, and record new checks, so it stops
+//! once all checks it reaches are known.
+//!
+//! Synchronous entry points run a single pass without awaiting anything. A check can not be
+//! decided there, and assuming any result would be wrong in negated contexts like `not`, so
+//! reaching one makes them fail with [`crate::ValidationErrorKind::RequiresAsync`].
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use jsonlike::prelude::*;
+
+use crate::{AsyncFormat, AsyncKeyword};
+
+/// An asynchronous check, identified by its index in the validator.
+pub(crate) enum Deferred<J: Json> {
+    Keyword(Arc<dyn AsyncKeyword<J>>),
+    Format(Arc<dyn AsyncFormat>),
+}
+
+impl<J: Json> Clone for Deferred<J> {
+    fn clone(&self) -> Self {
+        match self {
+            Deferred::Keyword(keyword) => Deferred::Keyword(Arc::clone(keyword)),
+            Deferred::Format(format) => Deferred::Format(Arc::clone(format)),
+        }
+    }
+}
+
+impl<J: Json> core::fmt::Debug for Deferred<J> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Deferred::Keyword(keyword) => f.debug_tuple("Keyword").field(keyword).finish(),
+            Deferred::Format(_) => f.write_str("Format"),
+        }
+    }
+}
+
+impl<J: Json> Deferred<J> {
+    async fn is_valid(&self, instance: &J) -> bool {
+        match self {
+            Deferred::Keyword(keyword) => keyword.is_valid(instance).await,
+            Deferred::Format(format) => match instance.as_string() {
+                Some(string) => format.is_valid(string.as_ref()).await,
+                None => true,
+            },
+        }
+    }
+}
+
+/// A check index and the address of the instance it applies to.
+type Key = (usize, usize);
+
+#[derive(Default)]
+struct State {
+    results: HashMap<Key, bool>,
+    pending: HashSet<Key>,
+}
+
+thread_local! {
+    /// Set only during a synchronous pass of an asynchronous validation.
+    static STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+}
+
+/// Result of a deferred check, `true` if it is not known yet.
+///
+/// # Panics
+///
+/// Panics if called outside of a pass.
+pub(crate) fn is_valid<J: Json>(index: usize, instance: &J) -> bool {
+    STATE.with_borrow_mut(|state| {
+        let state = state
+            .as_mut()
+            .expect("Deferred checks are only reached within a pass");
+        let key = (index, instance as *const J as usize);
+        match state.results.get(&key) {
+            Some(valid) => *valid,
+            None => {
+                state.pending.insert(key);
+                true
+            }
+        }
+    })
+}
+
+/// Clears the state even if the pass panics.
+struct Reset;
+
+impl Drop for Reset {
+    fn drop(&mut self) {
+        STATE.set(None);
+    }
+}
+
+/// Run a synchronous pass with the known results and return the checks it could not decide.
+fn run<R>(results: &mut HashMap<Key, bool>, pass: impl FnOnce() -> R) -> (R, HashSet<Key>) {
+    let _reset = Reset;
+    STATE.set(Some(State {
+        results: std::mem::take(results),
+        pending: HashSet::new(),
+    }));
+    let output = pass();
+    let state = STATE.take().expect("State is set for the pass");
+    *results = state.results;
+    (output, state.pending)
+}
+
+/// Run the pass of a synchronous entry point, `None` if it reaches a deferred check.
+///
+/// Within an asynchronous validation the pass is a part of it and runs as is.
+pub(crate) fn run_sync<J: Json, R>(checks: &[Deferred<J>], pass: impl FnOnce() -> R) -> Option<R> {
+    if checks.is_empty() || STATE.with_borrow(Option::is_some) {
+        return Some(pass());
+    }
+    let (output, pending) = run(&mut HashMap::new(), pass);
+    pending.is_empty().then_some(output)
+}
+
+/// Collect all values of the instance by their address.
+fn collect<'i, J: Json>(instance: &'i J, values: &mut HashMap<usize, &'i J>) {
+    values.insert(instance as *const J as usize, instance);
+    if let Some(object) = instance.as_object() {
+        for (_, value) in object.iter() {
+            collect(value, values);
+        }
+    } else if let Some(array) = instance.as_array() {
+        for item in array.iter().filter_map(Result::ok) {
+            collect(item, values);
+        }
+    }
+}
+
+/// Repeat the synchronous pass until all deferred checks it depends on are resolved.
+pub(crate) async fn resolve<J: Json, R>(
+    checks: &[Deferred<J>],
+    instance: &J,
+    mut pass: impl FnMut() -> R,
+) -> R {
+    if checks.is_empty() {
+        return pass();
+    }
+    let mut results = HashMap::new();
+    let mut values = None;
+    loop {
+        let (output, pending) = run(&mut results, &mut pass);
+        if pending.is_empty() {
+            return output;
+        }
+        let values = values.get_or_insert_with(|| {
+            let mut values = HashMap::new();
+            collect(instance, &mut values);
+            values
+        });
+        // Asynchronous checks are rejected within `contentSchema` at build time, so only values
+        // that custom keywords create themselves are outside of the instance. These are created
+        // anew on each pass, so checks against them can not be resolved and stay valid
+        let pending: Vec<_> = pending
+            .into_iter()
+            .filter_map(|(index, address)| Some((index, address, *values.get(&address)?)))
+            .collect();
+        if pending.is_empty() {
+            return output;
+        }
+        for (index, address, value) in pending {
+            let valid = checks[index].is_valid(value).await;
+            results.insert((index, address), valid);
+        }
+    }
+}
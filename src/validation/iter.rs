@@ -1,9 +1,12 @@
-use crate::{maybe_owned::MaybeOwned, ValidationError, Validator};
+use crate::{maybe_owned::MaybeOwned, vocabulary, ValidationError, Validator};
+use jpointer::JsonPointerNode;
 use jsonlike::Json;
 
 pub struct ValidationErrorIter<'v, 'i, J: Json> {
     validator: MaybeOwned<'v, Validator<J>>,
     instance: &'i J,
+    /// Errors are collected on the first call to `next`.
+    errors: Option<std::vec::IntoIter<ValidationError>>,
 }
 
 impl<'v, 'i, J: Json> ValidationErrorIter<'v, 'i, J> {
@@ -14,6 +17,7 @@ impl<'v, 'i, J: Json> ValidationErrorIter<'v, 'i, J> {
         ValidationErrorIter {
             validator,
             instance,
+            errors: None,
         }
     }
 }
@@ -22,6 +26,26 @@ impl<'v, 'i, J: Json> Iterator for ValidationErrorIter<'v, 'i, J> {
     type Item = ValidationError;
 
     fn next(&mut self) -> Option<Self::Item> {
-        None
+        let validator = &self.validator;
+        let instance = self.instance;
+        self.errors
+            .get_or_insert_with(|| {
+                validator
+                    .sync(|| {
+                        let mut errors = Vec::new();
+                        vocabulary::validate(
+                            validator.graph(),
+                            validator.root(),
+                            instance,
+                            &JsonPointerNode::new(),
+                            &JsonPointerNode::new(),
+                            &mut errors,
+                        );
+                        errors
+                    })
+                    .unwrap_or_else(|error| vec![error])
+                    .into_iter()
+            })
+            .next()
     }
 }
@@ -1,12 +1,28 @@
-use jsonlike::Json;
+use jpointer::JsonPointerNode;
+use jsonlike::{Json, JsonMut};
 pub(crate) mod builder;
+pub(crate) mod defaults;
+pub(crate) mod deferred;
 pub(crate) mod iter;
+pub(crate) mod ndjson;
+pub(crate) mod patch;
+pub(crate) mod stream;
+use std::{collections::HashMap, sync::Arc};
+
 use crate::{
-    graph, maybe_owned::MaybeOwned, output::Output, vocabulary::KeywordValue, BuildResult,
-    ValidationError,
+    graph,
+    maybe_owned::MaybeOwned,
+    output::Output,
+    regex_engine,
+    snapshot::{Reader, Writer},
+    vocabulary::{self, Annotation, KeywordValue, Subschema},
+    BuildError, BuildResult, Draft, RegexEngine, ValidationError,
 };
 use builder::validator_for;
+use deferred::Deferred;
 use iter::ValidationErrorIter;
+use ndjson::NdjsonErrors;
+use stream::StreamingValidator;
 
 pub async fn is_valid<J: Json>(schema: &J, instance: &J) -> bool {
     try_is_valid(schema, instance)
@@ -31,8 +47,8 @@ pub async fn try_validate<J: Json>(
     Ok(validator_for(schema).await?.validate(instance))
 }
 
-pub async fn iter_errors<'s, 'i, J: Json>(
-    schema: &'s J,
+pub async fn iter_errors<'i, J: Json + 'static>(
+    schema: &J,
     instance: &'i J,
 ) -> ValidationErrorIter<'static, 'i, J> {
     try_iter_errors(schema, instance)
@@ -40,21 +56,24 @@ pub async fn iter_errors<'s, 'i, J: Json>(
         .expect("Invalid schema")
 }
 
-pub async fn try_iter_errors<'s, 'i, J: Json>(
-    schema: &'s J,
+pub async fn try_iter_errors<'i, J: Json + 'static>(
+    schema: &J,
     instance: &'i J,
 ) -> BuildResult<ValidationErrorIter<'static, 'i, J>> {
     let validator = validator_for(schema).await?;
     Ok(validator.iter_errors_once(instance))
 }
 
-pub async fn evaluate<'i, J: Json>(instance: &'i J, schema: &J) -> Output<'static, 'i, J> {
+pub async fn evaluate<'i, J: Json + 'static>(
+    instance: &'i J,
+    schema: &J,
+) -> Output<'static, 'i, J> {
     try_evaluate(instance, schema)
         .await
         .expect("Invalid schema")
 }
 
-pub async fn try_evaluate<'i, J: Json>(
+pub async fn try_evaluate<'i, J: Json + 'static>(
     instance: &'i J,
     schema: &J,
 ) -> BuildResult<Output<'static, 'i, J>> {
@@ -64,22 +83,154 @@ pub async fn try_evaluate<'i, J: Json>(
 #[derive(Debug, Clone)]
 pub struct Validator<J: Json> {
     graph: graph::Graph<KeywordValue<J>>,
+    draft: Draft,
+    /// Asynchronous keywords & formats, referenced by their index from the graph.
+    deferred: Vec<Deferred<J>>,
+    /// Compiled schemas by their JSON Pointer or anchor within the root schema.
+    locations: HashMap<Box<str>, Subschema>,
 }
 
 impl<J: Json> Validator<J> {
-    pub(crate) fn new(graph: graph::Graph<KeywordValue<J>>) -> Self {
-        Self { graph }
+    pub(crate) fn new(
+        graph: graph::Graph<KeywordValue<J>>,
+        draft: Draft,
+        deferred: Vec<Deferred<J>>,
+    ) -> Self {
+        Self {
+            graph,
+            draft,
+            deferred,
+            locations: HashMap::new(),
+        }
+    }
+    pub(crate) fn with_locations(mut self, locations: HashMap<Box<str>, Subschema>) -> Self {
+        self.locations = locations;
+        self
+    }
+    pub(crate) fn graph(&self) -> &graph::Graph<KeywordValue<J>> {
+        &self.graph
+    }
+    pub(crate) fn root(&self) -> Subschema {
+        graph::SchemaId::ROOT
+    }
+
+    pub(crate) fn annotations(&self, instance: &J) -> Result<Vec<Annotation>, ValidationError> {
+        self.sync(|| {
+            let mut annotations = Vec::new();
+            vocabulary::annotate(
+                &self.graph,
+                self.root(),
+                instance,
+                &JsonPointerNode::new(),
+                &JsonPointerNode::new(),
+                &mut annotations,
+            );
+            annotations
+        })
+    }
+    /// Run a pass of a synchronous method, failing if it reaches an asynchronous check.
+    pub(crate) fn sync<R>(&self, pass: impl FnOnce() -> R) -> Result<R, ValidationError> {
+        deferred::run_sync(&self.deferred, pass).ok_or_else(ValidationError::requires_async)
+    }
+    /// Whether the schema has keywords or formats registered via
+    /// [`crate::ValidatorBuilder::async_keyword`] or [`crate::ValidatorBuilder::async_format`].
+    ///
+    /// Synchronous methods can not check them, see [`Validator::is_valid`].
+    pub fn requires_async(&self) -> bool {
+        !self.deferred.is_empty()
+    }
+
+    /// Serialize the validator into a snapshot that can be loaded by the same crate version.
+    ///
+    /// Validators with custom keywords, formats or content handlers can not be serialized.
+    pub fn to_bytes(&self) -> BuildResult<Vec<u8>> {
+        let mut writer = Writer::new(self.draft);
+        vocabulary::encode_graph(&self.graph, &mut writer)?;
+        // Sorted, so equal validators produce equal snapshots
+        let mut locations: Vec<_> = self
+            .locations
+            .iter()
+            .map(|(location, id)| (location.clone(), *id))
+            .collect();
+        locations.sort_unstable_by(|(left, _), (right, _)| left.cmp(right));
+        writer.named_schemas(&locations);
+        Ok(writer.finish())
+    }
+    /// Load a validator from a snapshot created by [`Validator::to_bytes`].
+    ///
+    /// Regular expressions are compiled with the default engine, use
+    /// [`crate::ValidatorBuilder::from_bytes`] to pick another one.
+    pub fn from_bytes(bytes: &[u8]) -> BuildResult<Validator<J>> {
+        Validator::decode(bytes, &regex_engine::default_engine())
+    }
+    pub(crate) fn decode(
+        bytes: &[u8],
+        regex_engine: &Arc<dyn RegexEngine>,
+    ) -> BuildResult<Validator<J>> {
+        let mut reader = Reader::new(bytes, regex_engine)?;
+        let graph = vocabulary::decode_graph(&mut reader)?;
+        reader.set_schemas(graph.len());
+        let locations = reader.named_schemas()?.into_iter().collect();
+        if !reader.is_empty() {
+            return Err(BuildError::invalid_snapshot());
+        }
+        Ok(Validator::new(graph, reader.draft, Vec::new()).with_locations(locations))
     }
 
+    /// Check whether the instance is valid.
+    ///
+    /// Keywords and formats registered via [`crate::ValidatorBuilder::async_keyword`] or
+    /// [`crate::ValidatorBuilder::async_format`] are only checked by [`Validator::is_valid_async`]
+    /// and the other `*_async` methods. If the validation reaches one, this method returns `false`
+    /// and other synchronous methods fail with [`crate::ValidationErrorKind::RequiresAsync`].
     pub fn is_valid(&self, instance: &J) -> bool {
-        true
+        self.sync(|| vocabulary::is_valid(&self.graph, self.root(), instance))
+            .unwrap_or(false)
     }
     pub fn validate(&self, instance: &J) -> Result<(), ValidationError> {
+        // Valid instances do not need to collect errors
+        if self.is_valid(instance) {
+            return Ok(());
+        }
         match self.iter_errors(instance).next() {
             None => Ok(()),
             Some(error) => Err(error),
         }
     }
+    /// Validate an instance against a subschema, given as a JSON Pointer or an anchor within the
+    /// root schema, e.g. `#/$defs/Address`.
+    ///
+    /// Only compiled subschemas can be found, i.e. those of known keywords, reference targets and
+    /// entries of `$defs` or `definitions`.
+    pub fn validate_at(
+        &self,
+        schema_pointer: &str,
+        instance: &J,
+    ) -> BuildResult<Result<(), ValidationError>> {
+        let location = schema_pointer.strip_prefix('#').unwrap_or(schema_pointer);
+        let location = percent_encoding::percent_decode_str(location).decode_utf8_lossy();
+        let Some(schema) = self.locations.get(location.as_ref()).copied() else {
+            return Err(BuildError::unresolvable_reference(schema_pointer));
+        };
+        let errors = self.sync(|| {
+            let mut errors = Vec::new();
+            if !vocabulary::is_valid(&self.graph, schema, instance) {
+                vocabulary::validate(
+                    &self.graph,
+                    schema,
+                    instance,
+                    &JsonPointerNode::new(),
+                    &JsonPointerNode::new(),
+                    &mut errors,
+                );
+            }
+            errors
+        });
+        Ok(match errors {
+            Ok(errors) => errors.into_iter().next().map_or(Ok(()), Err),
+            Err(error) => Err(error),
+        })
+    }
     pub fn iter_errors<'v, 'i>(&'v self, instance: &'i J) -> ValidationErrorIter<'v, 'i, J> {
         ValidationErrorIter::new(MaybeOwned::Borrowed(self), instance)
     }
@@ -92,24 +243,514 @@ impl<J: Json> Validator<J> {
     pub(crate) fn evaluate_once(self, instance: &J) -> Output<'static, '_, J> {
         Output::new(MaybeOwned::Owned(self), instance)
     }
+
+    /// Like [`Validator::is_valid`], but also awaits asynchronous keywords & formats.
+    pub async fn is_valid_async(&self, instance: &J) -> bool {
+        deferred::resolve(&self.deferred, instance, || self.is_valid(instance)).await
+    }
+    /// Like [`Validator::validate`], but also awaits asynchronous keywords & formats.
+    pub async fn validate_async(&self, instance: &J) -> Result<(), ValidationError> {
+        match self.iter_errors_async(instance).await.next() {
+            None => Ok(()),
+            Some(error) => Err(error),
+        }
+    }
+    /// Like [`Validator::iter_errors`], but also awaits asynchronous keywords & formats.
+    ///
+    /// Asynchronous checks are awaited one by one and only if the validation reaches them.
+    pub async fn iter_errors_async(&self, instance: &J) -> std::vec::IntoIter<ValidationError> {
+        deferred::resolve(&self.deferred, instance, || {
+            let mut errors = Vec::new();
+            vocabulary::validate(
+                &self.graph,
+                self.root(),
+                instance,
+                &JsonPointerNode::new(),
+                &JsonPointerNode::new(),
+                &mut errors,
+            );
+            errors
+        })
+        .await
+        .into_iter()
+    }
+
+    /// Insert `default` values of absent properties into the instance, then validate it.
+    ///
+    /// Defaults are taken from subschemas of `properties` at any depth, but only from branches of
+    /// `allOf`, `oneOf` and `if`-`then`-`else` the instance is valid against. If picking a branch
+    /// reaches an asynchronous check, defaults inserted up to that point are kept.
+    pub fn apply_defaults(&self, instance: &mut J) -> Result<(), ValidationError>
+    where
+        J: JsonMut,
+    {
+        self.sync(|| defaults::apply(&self.graph, self.root(), instance))?;
+        self.validate(instance)
+    }
+
+    /// Errors of an instance produced by applying a JSON Patch (RFC 6902) to a previous one.
+    ///
+    /// `previous` holds all errors of the previous instance, e.g. from
+    /// [`Validator::iter_errors`]. Subschemas applied to properties & items the patch does not
+    /// touch are skipped and their errors taken from `previous`, while other keywords are
+    /// evaluated on the whole value they belong to, e.g. `unevaluatedProperties` or `anyOf`.
+    /// Patches that are not a list of operations lead to a full validation.
+    pub fn revalidate(
+        &self,
+        instance: &J,
+        previous: &[ValidationError],
+        patch: &J,
+    ) -> Vec<ValidationError> {
+        self.sync(|| patch::revalidate(&self.graph, instance, previous, patch))
+            .unwrap_or_else(|error| Some(vec![error]))
+            .unwrap_or_else(|| self.iter_errors(instance).collect())
+    }
+
+    /// Validate newline-delimited JSON line by line, without reading the whole input upfront.
+    ///
+    /// Yields errors with their line number, see [`NdjsonErrors`] for how lines are handled.
+    pub fn validate_ndjson<R: std::io::Read>(&self, reader: R) -> NdjsonErrors<'_, J, R> {
+        NdjsonErrors::new(self, reader)
+    }
+    /// Validate a document given as parser events, without building it in memory.
+    ///
+    /// Fails for schemas with keywords that need a whole array or object at once: `uniqueItems`,
+    /// `unevaluatedProperties`, `unevaluatedItems`, custom keywords and `const` or `enum` with
+    /// arrays or objects.
+    pub fn streaming(&self) -> BuildResult<StreamingValidator<'_, J>> {
+        StreamingValidator::new(self)
+    }
+    /// Like [`Validator::validate_ndjson`], but reads asynchronously and also awaits
+    /// asynchronous keywords & formats.
+    #[cfg(feature = "tokio")]
+    pub fn validate_ndjson_async<R: tokio::io::AsyncRead + Unpin>(
+        &self,
+        reader: R,
+    ) -> ndjson::AsyncNdjsonErrors<'_, J, R> {
+        ndjson::AsyncNdjsonErrors::new(self, reader)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<J: Json + Sync> Validator<J> {
+    /// Validate instances in parallel and return the first error of each invalid instance with
+    /// its index in the batch, ordered by index.
+    ///
+    /// Each instance is evaluated on a single thread, including large arrays within it.
+    pub fn validate_batch(&self, instances: &[J]) -> Vec<(usize, ValidationError)> {
+        use rayon::prelude::*;
+
+        instances
+            .par_iter()
+            .enumerate()
+            .filter_map(|(index, instance)| Some((index, self.validate(instance).err()?)))
+            .collect()
+    }
+    /// Collect all errors of instances in parallel, each with the index of its instance.
+    ///
+    /// Errors of the same instance are in order, while instances are processed in any order.
+    pub fn par_iter_errors<'a>(
+        &'a self,
+        instances: &'a [J],
+    ) -> impl rayon::iter::ParallelIterator<Item = (usize, ValidationError)> + 'a {
+        use rayon::prelude::*;
+
+        instances
+            .par_iter()
+            .enumerate()
+            .flat_map_iter(move |(index, instance)| {
+                // Valid instances do not need to collect errors
+                let errors = (!self.is_valid(instance)).then(|| self.iter_errors(instance));
+                errors
+                    .into_iter()
+                    .flatten()
+                    .map(move |error| (index, error))
+            })
+    }
 }
 
 #[cfg(all(test, feature = "serde_json"))]
 mod tests {
-    use serde_json::json;
+    use crate::{
+        AsyncFormat, AsyncKeyword, BoxFuture, BoxedAsyncFormat, BoxedAsyncKeyword, BuildResult,
+        Draft, ValidationErrorKind, ValidatorBuilder,
+    };
+    use serde_json::{json, Value};
+    use test_case::test_case;
 
     #[tokio::test]
     async fn test_validator_for() {
         let schema = json!({"type": "integer"});
-        let validator = crate::validator_for(&schema).await.expect("Invalid schema");
+        let _validator = crate::validator_for(&schema).await.expect("Invalid schema");
     }
 
     #[tokio::test]
     async fn test_builder() {
         let schema = json!({"type": "integer"});
-        let validator = crate::ValidatorBuilder::default()
+        let _validator = crate::ValidatorBuilder::default()
             .build(&schema)
             .await
             .expect("Invalid schema");
     }
+
+    const TENANTS: &[&str] = &["acme", "globex"];
+
+    /// Looks up tenants in a "directory".
+    async fn tenant_exists(name: &str) -> bool {
+        tokio::task::yield_now().await;
+        TENANTS.contains(&name)
+    }
+
+    #[derive(Debug)]
+    struct TenantKeyword;
+
+    impl AsyncKeyword<Value> for TenantKeyword {
+        fn is_valid<'i>(&'i self, instance: &'i Value) -> BoxFuture<'i, bool> {
+            Box::pin(async move {
+                match instance.get("tenant").and_then(Value::as_str) {
+                    Some(tenant) => tenant_exists(tenant).await,
+                    None => true,
+                }
+            })
+        }
+    }
+
+    struct TenantFormat;
+
+    impl AsyncFormat for TenantFormat {
+        fn is_valid<'v>(&'v self, value: &'v str) -> BoxFuture<'v, bool> {
+            Box::pin(tenant_exists(value))
+        }
+    }
+
+    async fn build(schema: &Value) -> crate::Validator<Value> {
+        ValidatorBuilder::default()
+            .draft(Draft::Draft07)
+            .async_keyword(
+                "tenantScoped",
+                |_: &Value| -> BuildResult<BoxedAsyncKeyword<Value>> {
+                    Ok(Box::new(TenantKeyword))
+                },
+            )
+            .async_format("tenant-id", |_: &Value| -> BuildResult<BoxedAsyncFormat> {
+                Ok(Box::new(TenantFormat))
+            })
+            .build(schema)
+            .await
+            .expect("Invalid schema")
+    }
+
+    #[test_case(&json!({"tenantScoped": true}), &json!({"tenant": "acme"}), true)]
+    #[test_case(&json!({"tenantScoped": true}), &json!({"tenant": "initech"}), false)]
+    #[test_case(&json!({"items": {"format": "tenant-id"}}), &json!(["acme", "globex"]), true)]
+    #[test_case(&json!({"items": {"format": "tenant-id"}}), &json!(["acme", "initech"]), false)]
+    #[test_case(&json!({"not": {"format": "tenant-id"}}), &json!("initech"), true)]
+    #[test_case(&json!({"not": {"format": "tenant-id"}}), &json!("acme"), false)]
+    #[test_case(&json!({"anyOf": [{"format": "tenant-id"}, {"const": "admin"}]}), &json!("admin"), true)]
+    #[test_case(&json!({"if": {"tenantScoped": true}, "then": {"required": ["id"]}}), &json!({"tenant": "initech"}), true)]
+    #[test_case(&json!({"if": {"format": "tenant-id"}, "then": {"maxLength": 3}}), &json!("acme"), false)]
+    #[tokio::test]
+    async fn test_async_validation(schema: &Value, instance: &Value, expected: bool) {
+        let validator = build(schema).await;
+        assert_eq!(validator.is_valid_async(instance).await, expected);
+        assert_eq!(validator.validate_async(instance).await.is_ok(), expected);
+        assert_eq!(
+            validator.iter_errors_async(instance).await.next().is_none(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_async_errors() {
+        let schema =
+            json!({"properties": {"a": {"format": "tenant-id"}, "b": {"tenantScoped": true}}});
+        let validator = build(&schema).await;
+        let instance = json!({"a": "initech", "b": {"tenant": "initech"}});
+        let errors: Vec<_> = validator
+            .iter_errors_async(&instance)
+            .await
+            .map(|error| (error.to_string(), error.evaluation_path().to_string()))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    "Value is not a valid 'tenant-id'".to_string(),
+                    "/properties/a/format".to_string()
+                ),
+                (
+                    "Value is not valid under the 'tenantScoped' keyword".to_string(),
+                    "/properties/b/tenantScoped".to_string()
+                ),
+            ]
+        );
+    }
+
+    struct RejectingFormat;
+
+    impl AsyncFormat for RejectingFormat {
+        fn is_valid<'v>(&'v self, _: &'v str) -> BoxFuture<'v, bool> {
+            Box::pin(async { false })
+        }
+    }
+
+    #[test_case(&json!({"contentMediaType": "application/json", "contentSchema": {"format": "rejected"}}))]
+    #[test_case(&json!({"contentMediaType": "application/json", "contentSchema": {"items": {"format": "rejected"}}}))]
+    #[test_case(&json!({"contentMediaType": "application/json", "contentSchema": {"$ref": "#/$defs/a"}, "$defs": {"a": {"format": "rejected"}}}))]
+    #[test_case(&json!({"properties": {"a": {"$ref": "#/$defs/a"}}, "contentMediaType": "application/json", "contentSchema": {"$ref": "#/$defs/a"}, "$defs": {"a": {"format": "rejected"}}}))]
+    #[tokio::test]
+    async fn test_async_checks_within_content_schema(schema: &Value) {
+        let error = ValidatorBuilder::default()
+            .draft(Draft::Draft202012)
+            .validate_formats(true)
+            .validate_content(true)
+            .async_format("rejected", |_: &Value| -> BuildResult<BoxedAsyncFormat> {
+                Ok(Box::new(RejectingFormat))
+            })
+            .build(schema)
+            .await
+            .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "'format' is not supported: asynchronous checks can not be used within `contentSchema`"
+        );
+    }
+
+    #[test_case(&json!({"format": "tenant-id"}), &json!("initech"))]
+    #[test_case(&json!({"not": {"tenantScoped": true}}), &json!({"tenant": "initech"}))]
+    #[test_case(&json!({"anyOf": [{"type": "string"}, {"tenantScoped": true}]}), &json!({}))]
+    #[tokio::test]
+    async fn test_async_checks_in_sync_validation(schema: &Value, instance: &Value) {
+        let validator = build(schema).await;
+        assert!(validator.requires_async());
+        assert!(!validator.is_valid(instance));
+        let error = validator.validate(instance).expect_err("Should fail");
+        assert_eq!(error.kind(), &ValidationErrorKind::RequiresAsync);
+        let errors: Vec<_> = validator.iter_errors(instance).collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind(), &ValidationErrorKind::RequiresAsync);
+        let patch = json!([{"op": "replace", "path": "", "value": instance}]);
+        let errors = validator.revalidate(instance, &[], &patch);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind(), &ValidationErrorKind::RequiresAsync);
+        let errors: Vec<_> = validator
+            .validate_ndjson(format!("{instance}\n").as_bytes())
+            .collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].1.kind(), &ValidationErrorKind::RequiresAsync);
+        let error = validator
+            .apply_defaults(&mut instance.clone())
+            .expect_err("Should fail");
+        assert_eq!(error.kind(), &ValidationErrorKind::RequiresAsync);
+    }
+
+    #[tokio::test]
+    async fn test_sync_validation_without_async_checks() {
+        let validator = build(&json!({"properties": {"a": {"format": "tenant-id"}}})).await;
+        assert!(validator.requires_async());
+        assert!(validator.is_valid(&json!({"b": 1})));
+        assert!(validator.is_valid(&json!({"a": 1})));
+        assert!(validator.validate(&json!({"a": 1})).is_ok());
+        let validator = build(&json!({"type": "string"})).await;
+        assert!(!validator.requires_async());
+    }
+
+    #[test_case(&json!({"$ref": "#/$defs/node", "$defs": {"node": {"type": "object", "properties": {"next": {"$ref": "#/$defs/node"}}}}}), &json!({"next": {"next": {}}}), &json!({"next": {"next": 1}}))]
+    #[test_case(&json!({"patternProperties": {"^a+$": {"minimum": 2.5}}, "additionalProperties": false}), &json!({"aa": 3}), &json!({"ab": 3}))]
+    #[test_case(&json!({"items": {"format": "ipv4"}, "uniqueItems": true}), &json!(["127.0.0.1"]), &json!(["127.0.0.1", "256.0.0.1"]))]
+    #[test_case(&json!({"const": {"a": [1, null, true, "b"]}}), &json!({"a": [1, null, true, "b"]}), &json!({"a": [1]}))]
+    #[test_case(&json!({"contentMediaType": "application/json", "contentEncoding": "base64"}), &json!("eyJhIjogMX0="), &json!("eyJhIjog"))]
+    #[test_case(&json!({"if": {"properties": {"a": {"const": 1}}}, "then": {"enum": [{"a": 1}]}, "unevaluatedProperties": false}), &json!({"a": 1}), &json!({"b": 1}))]
+    #[tokio::test]
+    async fn test_snapshot_roundtrip(schema: &Value, valid: &Value, invalid: &Value) {
+        let validator = ValidatorBuilder::default()
+            .validate_formats(true)
+            .validate_content(true)
+            .build(schema)
+            .await
+            .expect("Invalid schema");
+        let bytes = validator.to_bytes().expect("Serializable validator");
+        let loaded = crate::Validator::<Value>::from_bytes(&bytes).expect("Valid snapshot");
+        assert!(loaded.is_valid(valid));
+        assert!(!loaded.is_valid(invalid));
+        let errors = |validator: &crate::Validator<Value>| {
+            validator
+                .iter_errors(invalid)
+                .map(|error| (error.to_string(), error.evaluation_path().to_string()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(errors(&loaded), errors(&validator));
+        assert_eq!(loaded.to_bytes().expect("Serializable validator"), bytes);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_errors() {
+        let validator = crate::validator_for(&json!({"minLength": 2}))
+            .await
+            .expect("Invalid schema");
+        let bytes = validator.to_bytes().expect("Serializable validator");
+        let error = crate::Validator::<Value>::from_bytes(&bytes[..bytes.len() - 1])
+            .expect_err("Truncated snapshot");
+        assert_eq!(error.to_string(), "Invalid validator snapshot");
+        let error = crate::Validator::<Value>::from_bytes(b"JSVS\x050.0.0\x04")
+            .expect_err("Incompatible snapshot");
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Snapshot was created by version 0.0.0, expected {}",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+        let validator = build(&json!({"tenantScoped": true})).await;
+        let error = validator.to_bytes().expect_err("Custom keyword");
+        assert_eq!(error.to_string(), "'tenantScoped' can not be serialized");
+    }
+
+    fn addresses() -> Value {
+        json!({
+            "$id": "https://example.com/customer.json",
+            "type": "object",
+            "properties": {"name": {"type": "string"}, "address": {"$ref": "#/$defs/Address"}},
+            "required": ["address"],
+            "$defs": {
+                "Address": {
+                    "$anchor": "address",
+                    "properties": {"street": {"$ref": "#/$defs/Street"}, "owner": {"$ref": "#"}},
+                    "required": ["street"]
+                },
+                "Street": {"type": "string", "minLength": 1},
+                "Unused": {"$defs": {"a b": {"maximum": 3}}}
+            }
+        })
+    }
+
+    #[test_case("#/$defs/Address", &json!({"street": "Main"}), None)]
+    #[test_case("#/$defs/Address", &json!({"street": ""}), Some("/properties/street/$ref/minLength"))]
+    #[test_case("#address", &json!({"street": 1}), Some("/properties/street/$ref/type"))]
+    #[test_case("#/$defs/Address", &json!({"street": "Main", "owner": {}}), Some("/properties/owner/$ref/required"))]
+    #[test_case("/properties/name", &json!(1), Some("/type"))]
+    #[test_case("#/$defs/Unused/$defs/a%20b", &json!(4), Some("/maximum"))]
+    #[test_case("#", &json!({}), Some("/required"))]
+    #[tokio::test]
+    async fn test_validate_at(pointer: &str, instance: &Value, expected: Option<&str>) {
+        let schema = addresses();
+        let validator = crate::validator_for(&schema).await.expect("Invalid schema");
+        let loaded = crate::Validator::<Value>::from_bytes(
+            &validator.to_bytes().expect("Serializable validator"),
+        )
+        .expect("Valid snapshot");
+        for validator in [validator, loaded] {
+            let result = validator
+                .validate_at(pointer, instance)
+                .expect("Known location");
+            assert_eq!(
+                result
+                    .err()
+                    .map(|error| error.evaluation_path().to_string())
+                    .as_deref(),
+                expected
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_at_unknown_location() {
+        let schema = addresses();
+        let validator = crate::validator_for(&schema).await.expect("Invalid schema");
+        let error = validator
+            .validate_at("#/$defs/Missing", &json!({}))
+            .expect_err("Unknown location");
+        assert_eq!(
+            error.to_string(),
+            "Unresolvable reference: '#/$defs/Missing'"
+        );
+    }
+
+    #[test_case("#/$defs/Address", &json!({"street": "Main"}), &json!({"street": ""}))]
+    #[test_case("#address", &json!({"street": "Main", "owner": {"address": {"street": "Side"}}}), &json!({"street": "Main", "owner": {}}))]
+    #[test_case("https://example.com/customer.json#/$defs/Street", &json!("Main"), &json!(1))]
+    #[test_case("#/properties/name", &json!("Alice"), &json!(1))]
+    #[tokio::test]
+    async fn test_build_at(reference: &str, valid: &Value, invalid: &Value) {
+        let schema = addresses();
+        let validator = ValidatorBuilder::default()
+            .build_at(&schema, reference)
+            .await
+            .expect("Invalid schema");
+        assert!(validator.is_valid(valid));
+        assert!(!validator.is_valid(invalid));
+        let blocking = crate::blocking::ValidatorBuilder::default()
+            .build_at(&schema, reference)
+            .expect("Invalid schema");
+        assert_eq!(
+            validator
+                .iter_errors(invalid)
+                .map(|error| error.evaluation_path().to_string())
+                .collect::<Vec<_>>(),
+            blocking
+                .iter_errors(invalid)
+                .map(|error| error.evaluation_path().to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_build_at_unresolvable() {
+        let schema = addresses();
+        let error = ValidatorBuilder::default()
+            .build_at(&schema, "#/$defs/Missing")
+            .await
+            .expect_err("Unresolvable reference");
+        assert_eq!(
+            error.to_string(),
+            "Unresolvable reference: '#/$defs/Missing'"
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_validate_batch() {
+        use rayon::iter::ParallelIterator;
+
+        let schema = json!({"items": {"type": "integer"}, "maxItems": 2});
+        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        let batch: Vec<_> = (0..1000)
+            .map(|index| match index % 250 {
+                3 => json!([1, "a", "b"]),
+                7 => json!(["a"]),
+                _ => json!([index]),
+            })
+            .collect();
+        let errors: Vec<_> = validator
+            .validate_batch(&batch)
+            .into_iter()
+            .map(|(index, error)| (index, error.to_string()))
+            .collect();
+        let expected: Vec<_> = (0..4)
+            .flat_map(|chunk| {
+                [
+                    (chunk * 250 + 3, "Value has more than 2 items".to_string()),
+                    (
+                        chunk * 250 + 7,
+                        "Value is not of type 'integer'".to_string(),
+                    ),
+                ]
+            })
+            .collect();
+        assert_eq!(errors, expected);
+        let mut errors: Vec<_> = validator
+            .par_iter_errors(&batch)
+            .map(|(index, error)| (index, error.instance_location().to_string()))
+            .collect();
+        errors.sort();
+        assert_eq!(errors.len(), 16);
+        assert_eq!(
+            errors[..3],
+            [
+                (3, String::new()),
+                (3, "/1".to_string()),
+                (3, "/2".to_string())
+            ]
+        );
+    }
 }
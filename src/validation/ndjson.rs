@@ -0,0 +1,260 @@
+//! Validation of newline-delimited JSON, one instance per line.
+use std::io::{self, BufRead, BufReader, Read};
+
+use jpointer::JsonPointerNode;
+use jsonlike::Json;
+
+use crate::{ValidationError, ValidationErrorKind, Validator};
+
+/// Errors of newline-delimited JSON instances, each with its line number.
+///
+/// Lines are read one at a time, blank lines are skipped and line numbers start at 1. Lines that
+/// are not UTF-8 encoded JSON documents produce [`ValidationErrorKind::InvalidJson`] errors, a
+/// failed read produces a single [`ValidationErrorKind::Read`] error and ends the input.
+pub struct NdjsonErrors<'v, J: Json, R> {
+    validator: &'v Validator<J>,
+    reader: BufReader<R>,
+    buffer: Vec<u8>,
+    line: usize,
+    errors: std::vec::IntoIter<ValidationError>,
+    done: bool,
+}
+
+impl<'v, J: Json, R: Read> NdjsonErrors<'v, J, R> {
+    pub(crate) fn new(validator: &'v Validator<J>, reader: R) -> NdjsonErrors<'v, J, R> {
+        NdjsonErrors {
+            validator,
+            reader: BufReader::new(reader),
+            buffer: Vec::new(),
+            line: 0,
+            errors: Vec::new().into_iter(),
+            done: false,
+        }
+    }
+}
+
+impl<J: Json, R: Read> Iterator for NdjsonErrors<'_, J, R> {
+    type Item = (usize, ValidationError);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(error) = self.errors.next() {
+                return Some((self.line, error));
+            }
+            if self.done {
+                return None;
+            }
+            self.buffer.clear();
+            let read = self.reader.read_until(b'\n', &mut self.buffer);
+            self.line += 1;
+            let errors = match read {
+                Ok(0) => {
+                    self.done = true;
+                    return None;
+                }
+                Ok(_) => match parse::<J>(&self.buffer) {
+                    None => continue,
+                    Some(Ok(instance)) => {
+                        // Valid instances do not need to collect errors
+                        if self.validator.is_valid(&instance) {
+                            continue;
+                        }
+                        self.validator.iter_errors(&instance).collect()
+                    }
+                    Some(Err(error)) => vec![error],
+                },
+                Err(error) => {
+                    self.done = true;
+                    vec![read_error(error)]
+                }
+            };
+            self.errors = errors.into_iter();
+        }
+    }
+}
+
+/// Errors of newline-delimited JSON instances read asynchronously, each with its line number.
+///
+/// Asynchronous keywords & formats are awaited like in [`Validator::iter_errors_async`].
+#[cfg(feature = "tokio")]
+pub struct AsyncNdjsonErrors<'v, J: Json, R> {
+    validator: &'v Validator<J>,
+    reader: tokio::io::BufReader<R>,
+    buffer: Vec<u8>,
+    line: usize,
+    errors: std::vec::IntoIter<ValidationError>,
+    done: bool,
+}
+
+#[cfg(feature = "tokio")]
+impl<'v, J: Json, R: tokio::io::AsyncRead + Unpin> AsyncNdjsonErrors<'v, J, R> {
+    pub(crate) fn new(validator: &'v Validator<J>, reader: R) -> AsyncNdjsonErrors<'v, J, R> {
+        AsyncNdjsonErrors {
+            validator,
+            reader: tokio::io::BufReader::new(reader),
+            buffer: Vec::new(),
+            line: 0,
+            errors: Vec::new().into_iter(),
+            done: false,
+        }
+    }
+    /// The next error with its line number, or `None` at the end of the input.
+    pub async fn next(&mut self) -> Option<(usize, ValidationError)> {
+        use tokio::io::AsyncBufReadExt;
+
+        loop {
+            if let Some(error) = self.errors.next() {
+                return Some((self.line, error));
+            }
+            if self.done {
+                return None;
+            }
+            self.buffer.clear();
+            let read = self.reader.read_until(b'\n', &mut self.buffer).await;
+            self.line += 1;
+            self.errors = match read {
+                Ok(0) => {
+                    self.done = true;
+                    return None;
+                }
+                Ok(_) => match parse::<J>(&self.buffer) {
+                    None => continue,
+                    Some(Ok(instance)) => self.validator.iter_errors_async(&instance).await,
+                    Some(Err(error)) => vec![error].into_iter(),
+                },
+                Err(error) => {
+                    self.done = true;
+                    vec![read_error(error)].into_iter()
+                }
+            };
+        }
+    }
+}
+
+/// Parse a line, `None` if it is blank. A line that is not UTF-8 is reported like invalid JSON.
+fn parse<J: Json>(line: &[u8]) -> Option<Result<J, ValidationError>> {
+    let line = match std::str::from_utf8(line) {
+        Ok(line) => line.trim(),
+        Err(error) => return Some(Err(invalid_json(error))),
+    };
+    if line.is_empty() {
+        return None;
+    }
+    Some(J::from_str(line).map_err(invalid_json))
+}
+
+fn invalid_json(error: impl ToString) -> ValidationError {
+    error_at_root(ValidationErrorKind::InvalidJson {
+        message: error.to_string(),
+    })
+}
+
+fn read_error(error: io::Error) -> ValidationError {
+    error_at_root(ValidationErrorKind::Read {
+        message: error.to_string(),
+    })
+}
+
+fn error_at_root(kind: ValidationErrorKind) -> ValidationError {
+    ValidationError::new(kind, &JsonPointerNode::new(), &JsonPointerNode::new())
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use std::io::{self, Read};
+
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use crate::{ValidationErrorKind, Validator};
+
+    fn validator() -> Validator<Value> {
+        crate::blocking::validator_for(
+            &json!({"required": ["id"], "properties": {"id": {"type": "integer"}}}),
+        )
+        .expect("Invalid schema")
+    }
+
+    fn errors(input: &[u8]) -> Vec<(usize, String)> {
+        validator()
+            .validate_ndjson(input)
+            .map(|(line, error)| (line, error.to_string()))
+            .collect()
+    }
+
+    #[test_case(b"{\"id\": 1}\n{\"id\": 2}\n", &[]; "valid")]
+    #[test_case(b"{\"id\": 1}\r\n\n  \n{\"id\": \"2\"}", &[(4, "Value is not of type 'integer'")]; "blank lines")]
+    #[test_case(b"{}\n{\"id\": 1", &[(1, "'id' is a required property"), (2, "Invalid JSON: EOF while parsing an object at line 1 column 8")]; "invalid json")]
+    #[test_case(b"{\"id\": \"\xff\"}\n{\"id\": true}\n", &[(1, "Invalid JSON: invalid utf-8 sequence of 1 bytes from index 8"), (2, "Value is not of type 'integer'")]; "invalid utf-8")]
+    fn test_validate_ndjson(input: &[u8], expected: &[(usize, &str)]) {
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(line, message)| (*line, message.to_string()))
+            .collect();
+        assert_eq!(errors(input), expected);
+    }
+
+    #[test]
+    fn test_read_error() {
+        struct Failing<'a>(&'a [u8]);
+
+        impl Read for Failing<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.0.is_empty() {
+                    return Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken pipe"));
+                }
+                self.0.read(buf)
+            }
+        }
+
+        let validator = validator();
+        let errors: Vec<_> = validator
+            .validate_ndjson(Failing(b"{}\n{\"id\": 1}\n"))
+            .map(|(line, error)| (line, error.kind().clone()))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    1,
+                    ValidationErrorKind::Required {
+                        property: "id".to_string()
+                    }
+                ),
+                (
+                    3,
+                    ValidationErrorKind::Read {
+                        message: "broken pipe".to_string()
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_validate_ndjson_async() {
+        let validator = validator();
+        let input: &[u8] = b"{}\nnull\n\n[\n\xff\n{\"id\": true}\n";
+        let mut errors = validator.validate_ndjson_async(input);
+        let mut collected = Vec::new();
+        while let Some((line, error)) = errors.next().await {
+            collected.push((line, error.to_string()));
+        }
+        assert_eq!(
+            collected,
+            vec![
+                (1, "'id' is a required property".to_string()),
+                (
+                    4,
+                    "Invalid JSON: EOF while parsing a list at line 1 column 1".to_string()
+                ),
+                (
+                    5,
+                    "Invalid JSON: invalid utf-8 sequence of 1 bytes from index 0".to_string()
+                ),
+                (6, "Value is not of type 'integer'".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,406 @@
+//! Revalidation of an instance after a JSON Patch (RFC 6902).
+//!
+//! Evaluation follows the changed pointers from the root: subschemas applied to unchanged
+//! properties & items are skipped and their previous errors reused, all other keywords are
+//! evaluated as usual on the value they belong to.
+use jpointer::{JsonPointer, JsonPointerNode, Segment};
+use jsonlike::prelude::*;
+
+use crate::{
+    graph::SchemaId,
+    vocabulary::{self, KeywordValue, SchemaGraph},
+    ValidationError,
+};
+
+/// Errors of the instance, `None` if the patch is not a list of operations.
+pub(crate) fn revalidate<J: Json>(
+    graph: &SchemaGraph<J>,
+    instance: &J,
+    previous: &[ValidationError],
+    patch: &J,
+) -> Option<Vec<ValidationError>> {
+    let changed = changed_pointers(instance, patch)?;
+    let changed: Vec<_> = changed.iter().map(Vec::as_slice).collect();
+    let mut revalidation = Revalidation {
+        previous,
+        errors: Vec::new(),
+    };
+    revalidation.evaluate(
+        graph,
+        SchemaId::ROOT,
+        instance,
+        &JsonPointerNode::new(),
+        &JsonPointerNode::new(),
+        &changed,
+    );
+    Some(revalidation.errors)
+}
+
+struct Revalidation<'p> {
+    previous: &'p [ValidationError],
+    errors: Vec<ValidationError>,
+}
+
+impl Revalidation<'_> {
+    /// Collect errors like [`vocabulary::validate`], given pointers to changed values relative to
+    /// the instance.
+    fn evaluate<J: Json>(
+        &mut self,
+        graph: &SchemaGraph<J>,
+        schema: SchemaId,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        changed: &[&[Segment]],
+    ) {
+        if changed.is_empty() {
+            return self.reuse(location, path);
+        }
+        if changed.iter().any(|pointer| pointer.is_empty()) {
+            return vocabulary::validate(graph, schema, instance, location, path, &mut self.errors);
+        }
+        for keyword in graph.keywords(schema) {
+            match keyword {
+                KeywordValue::Ref(keyword) => {
+                    let path = path.push(keyword.keyword);
+                    self.evaluate(graph, keyword.schema, instance, location, &path, changed);
+                }
+                KeywordValue::Shared(keyword) => {
+                    let graph = &keyword.graph;
+                    self.evaluate(graph, SchemaId::ROOT, instance, location, path, changed);
+                }
+                KeywordValue::AllOf(keyword) => {
+                    let path = path.push("allOf");
+                    for (idx, schema) in keyword.schemas.iter().enumerate() {
+                        self.evaluate(graph, *schema, instance, location, &path.push(idx), changed);
+                    }
+                }
+                KeywordValue::Properties(keyword) => {
+                    let Some(object) = instance.as_object() else {
+                        continue;
+                    };
+                    let path = path.push("properties");
+                    for (name, schema) in &keyword.properties {
+                        if let Some(value) = object.get(name) {
+                            let segment = Segment::from(name.as_ref());
+                            self.evaluate(
+                                graph,
+                                *schema,
+                                value,
+                                &location.push(name.as_ref()),
+                                &path.push(name.as_ref()),
+                                &nested(changed, &segment),
+                            );
+                        }
+                    }
+                }
+                KeywordValue::PatternProperties(keyword) => {
+                    let Some(object) = instance.as_object() else {
+                        continue;
+                    };
+                    let path = path.push("patternProperties");
+                    for (key, value) in object.iter() {
+                        let Ok(key) = key else {
+                            continue;
+                        };
+                        let changed = nested(changed, &Segment::from(key.as_ref()));
+                        for (regex, schema) in &keyword.patterns {
+                            if regex.is_match(key.as_ref()) {
+                                self.evaluate(
+                                    graph,
+                                    *schema,
+                                    value,
+                                    &location.push(key.as_ref()),
+                                    &path.push(regex.as_str()),
+                                    &changed,
+                                );
+                            }
+                        }
+                    }
+                }
+                // Forbidden properties are reported together and need no subschema
+                KeywordValue::AdditionalProperties(keyword) if !keyword.forbidden => {
+                    let Some(object) = instance.as_object() else {
+                        continue;
+                    };
+                    let path = path.push("additionalProperties");
+                    for (key, value) in object.iter() {
+                        let Ok(key) = key else {
+                            continue;
+                        };
+                        if keyword.is_additional(key.as_ref()) {
+                            self.evaluate(
+                                graph,
+                                keyword.schema,
+                                value,
+                                &location.push(key.as_ref()),
+                                &path,
+                                &nested(changed, &Segment::from(key.as_ref())),
+                            );
+                        }
+                    }
+                }
+                KeywordValue::PrefixItems(keyword) => {
+                    let Some(array) = instance.as_array() else {
+                        continue;
+                    };
+                    let path = path.push(keyword.keyword);
+                    for (idx, (item, schema)) in array.iter().zip(&keyword.schemas).enumerate() {
+                        if let Ok(item) = item {
+                            self.evaluate(
+                                graph,
+                                *schema,
+                                item,
+                                &location.push(idx),
+                                &path.push(idx),
+                                &nested(changed, &Segment::Index(idx)),
+                            );
+                        }
+                    }
+                }
+                KeywordValue::Items(keyword) => {
+                    let Some(array) = instance.as_array() else {
+                        continue;
+                    };
+                    let path = path.push(keyword.keyword);
+                    for (idx, item) in array.iter().enumerate().skip(keyword.skip) {
+                        if let Ok(item) = item {
+                            self.evaluate(
+                                graph,
+                                keyword.schema,
+                                item,
+                                &location.push(idx),
+                                &path,
+                                &nested(changed, &Segment::Index(idx)),
+                            );
+                        }
+                    }
+                }
+                _ => keyword.validate(graph, schema, instance, location, path, &mut self.errors),
+            }
+        }
+    }
+
+    /// Take over previous errors of an unchanged value evaluated at the given path.
+    fn reuse(&mut self, location: &JsonPointerNode, path: &JsonPointerNode) {
+        let (location, path) = (location.to_vec(), path.to_vec());
+        self.errors.extend(
+            self.previous
+                .iter()
+                .filter(|error| {
+                    error.instance_location().segments().starts_with(&location)
+                        && error.evaluation_path().segments().starts_with(&path)
+                })
+                .cloned(),
+        );
+    }
+}
+
+/// Changed pointers within the property or item at the given segment.
+fn nested<'c>(changed: &[&'c [Segment]], segment: &Segment) -> Vec<&'c [Segment]> {
+    changed
+        .iter()
+        .filter_map(|pointer| {
+            let (first, rest) = pointer.split_first()?;
+            same_segment(first, segment).then_some(rest)
+        })
+        .collect()
+}
+
+/// Parsed pointers only contain keys, which also refer to array items.
+fn same_segment(left: &Segment, right: &Segment) -> bool {
+    match (left, right) {
+        (Segment::Key(left), Segment::Key(right)) => left == right,
+        (Segment::Index(left), Segment::Index(right)) => left == right,
+        (Segment::Key(key), Segment::Index(idx)) | (Segment::Index(idx), Segment::Key(key)) => {
+            key.parse() == Ok(*idx)
+        }
+    }
+}
+
+/// Pointers to values that may differ from the previous instance.
+fn changed_pointers<J: Json>(instance: &J, patch: &J) -> Option<Vec<Vec<Segment>>> {
+    let mut changed = Vec::new();
+    for operation in patch.as_array()?.iter() {
+        let operation = operation.ok()?.as_object()?;
+        let pointer = |name: &str| {
+            operation
+                .get(name)?
+                .as_string()?
+                .as_ref()
+                .parse::<JsonPointer>()
+                .ok()
+        };
+        match operation.get("op")?.as_string()?.as_ref() {
+            "replace" => changed.push(pointer("path")?.segments().to_vec()),
+            "add" | "remove" | "copy" => changed.push(resized(instance, &pointer("path")?)),
+            "move" => {
+                changed.push(resized(instance, &pointer("from")?));
+                changed.push(resized(instance, &pointer("path")?));
+            }
+            "test" => {}
+            _ => return None,
+        }
+    }
+    Some(changed)
+}
+
+/// Adding or removing array items shifts the following ones, so the whole array is changed.
+fn resized<J: Json>(instance: &J, pointer: &JsonPointer) -> Vec<Segment> {
+    let segments = pointer.segments();
+    let Some((_, parent)) = segments.split_last() else {
+        return Vec::new();
+    };
+    if resolve(instance, parent).is_some_and(Json::is_object) {
+        segments.to_vec()
+    } else {
+        parent.to_vec()
+    }
+}
+
+fn resolve<'i, J: Json>(instance: &'i J, segments: &[Segment]) -> Option<&'i J> {
+    segments
+        .iter()
+        .try_fold(instance, |value, segment| match segment {
+            Segment::Key(key) => match value.as_object() {
+                Some(object) => object.get(key),
+                None => value.as_array()?.get(key.parse().ok()?),
+            },
+            Segment::Index(idx) => value.as_array()?.get(*idx),
+        })
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use crate::{
+        blocking::ValidatorBuilder, BoxedKeyword, BuildResult, Keyword, KeywordContext,
+        ValidationError, Validator,
+    };
+
+    fn errors(errors: &[ValidationError]) -> Vec<(String, String, String)> {
+        errors
+            .iter()
+            .map(|error| {
+                (
+                    error.instance_location().to_string(),
+                    error.evaluation_path().to_string(),
+                    error.to_string(),
+                )
+            })
+            .collect()
+    }
+
+    fn apply(instance: &Value, patch: &Value) -> Value {
+        let mut instance = instance.clone();
+        for operation in patch.as_array().into_iter().flatten() {
+            let pointer = |name: &str| operation[name].as_str().unwrap_or_default().to_string();
+            let path = pointer("path");
+            if path.is_empty() {
+                instance = operation["value"].clone();
+                continue;
+            }
+            let (parent, key) = path.rsplit_once('/').expect("Not the root");
+            let value = match operation["op"].as_str() {
+                Some("move") => remove(&mut instance, &pointer("from")),
+                Some("copy") => instance.pointer(&pointer("from")).cloned(),
+                Some("remove") => {
+                    remove(&mut instance, &pointer("path"));
+                    None
+                }
+                _ => Some(operation["value"].clone()),
+            };
+            let Some(value) = value else {
+                continue;
+            };
+            let target = instance.pointer_mut(parent).expect("Existing parent");
+            match target {
+                Value::Array(items) if operation["op"] == "replace" => {
+                    items[key.parse::<usize>().expect("Index")] = value;
+                }
+                Value::Array(items) if key == "-" => items.push(value),
+                Value::Array(items) => items.insert(key.parse().expect("Index"), value),
+                Value::Object(object) => {
+                    object.insert(key.to_string(), value);
+                }
+                _ => unreachable!("Scalar parent"),
+            }
+        }
+        instance
+    }
+
+    fn remove(instance: &mut Value, pointer: &str) -> Option<Value> {
+        let (parent, key) = pointer.rsplit_once('/').expect("Not the root");
+        match instance.pointer_mut(parent)? {
+            Value::Array(items) => Some(items.remove(key.parse().ok()?)),
+            Value::Object(object) => object.remove(key),
+            _ => None,
+        }
+    }
+
+    fn assert_revalidation(validator: &Validator<Value>, instance: &Value, patch: &Value) {
+        let previous: Vec<_> = validator.iter_errors(instance).collect();
+        let patched = apply(instance, patch);
+        let expected: Vec<_> = validator.iter_errors(&patched).collect();
+        let actual = validator.revalidate(&patched, &previous, patch);
+        assert_eq!(errors(&actual), errors(&expected));
+    }
+
+    #[test_case(&json!({"properties": {"a": {"properties": {"b": {"type": "integer"}}}, "c": {"type": "string"}}}), &json!({"a": {"b": "x"}, "c": 1}), &json!([{"op": "replace", "path": "/a/b", "value": 1}]))]
+    #[test_case(&json!({"items": {"minimum": 2}}), &json!([1, 2, 3, 0]), &json!([{"op": "add", "path": "/1", "value": 1}]))]
+    #[test_case(&json!({"items": {"minimum": 2}}), &json!([1, 2, 3, 0]), &json!([{"op": "remove", "path": "/0"}, {"op": "add", "path": "/-", "value": -1}]))]
+    #[test_case(&json!({"prefixItems": [{"type": "string"}], "items": {"type": "integer"}}), &json!(["a", "b", 1]), &json!([{"op": "replace", "path": "/0", "value": 0}]))]
+    #[test_case(&json!({"if": {"properties": {"kind": {"const": "a"}}}, "then": {"properties": {"value": {"type": "string"}}}, "else": {"properties": {"value": {"type": "integer"}}}}), &json!({"kind": "a", "value": 1}), &json!([{"op": "replace", "path": "/kind", "value": "b"}]))]
+    #[test_case(&json!({"properties": {"a": true}, "unevaluatedProperties": {"type": "integer"}}), &json!({"a": "x", "b": "y"}), &json!([{"op": "add", "path": "/c", "value": "z"}]))]
+    #[test_case(&json!({"required": ["a"], "properties": {"a": {"type": "integer"}}, "additionalProperties": false}), &json!({"a": "x", "b": 1}), &json!([{"op": "move", "from": "/a", "path": "/c"}]))]
+    #[test_case(&json!({"patternProperties": {"^x": {"$ref": "#/$defs/list"}}, "additionalProperties": {"$ref": "#/$defs/list"}, "$defs": {"list": {"allOf": [{"type": "array"}, {"items": {"type": "null"}}]}}}), &json!({"xa": [1], "b": [null, 2]}), &json!([{"op": "copy", "from": "/xa", "path": "/b/0"}]))]
+    #[test_case(&json!({"properties": {"a": {"type": "integer"}}}), &json!({"a": "x"}), &json!([{"op": "test", "path": "/a", "value": "x"}]))]
+    #[test_case(&json!({"type": "object"}), &json!({"a": 1}), &json!([{"op": "replace", "path": "", "value": []}]))]
+    #[test_case(&json!({"type": "object"}), &json!({"a": 1}), &json!({"op": "add"}))]
+    fn test_revalidate(schema: &Value, instance: &Value, patch: &Value) {
+        let validator = crate::blocking::validator_for(schema).expect("Invalid schema");
+        assert_revalidation(&validator, instance, patch);
+    }
+
+    #[derive(Debug)]
+    struct Counted(Arc<AtomicUsize>);
+
+    impl Keyword<Value> for Counted {
+        fn is_valid(&self, _: &KeywordContext<'_, Value>, instance: &Value) -> bool {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            instance.as_u64().is_none_or(|value| value < 10)
+        }
+    }
+
+    #[test]
+    fn test_unchanged_items_are_skipped() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let schema = json!({"items": {"counted": true}});
+        let validator = ValidatorBuilder::default()
+            .keyword(
+                "counted",
+                move |_: &Value| -> BuildResult<BoxedKeyword<Value>> {
+                    Ok(Box::new(Counted(Arc::clone(&counter))))
+                },
+            )
+            .build(&schema)
+            .expect("Invalid schema");
+        let instance: Value = (0..100).map(|idx| json!(idx)).collect();
+        let patch = json!([{"op": "replace", "path": "/50", "value": 5}]);
+        let previous: Vec<_> = validator.iter_errors(&instance).collect();
+        let patched = apply(&instance, &patch);
+        calls.store(0, Ordering::Relaxed);
+        let errors = validator.revalidate(&patched, &previous, &patch);
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        assert_eq!(errors.len(), 89);
+        assert_revalidation(&validator, &instance, &patch);
+    }
+}
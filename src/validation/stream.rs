@@ -0,0 +1,905 @@
+//! Validation of a document given as parser events, without building it in memory.
+//!
+//! Each open container holds the evaluations of schemas that apply to it. Keys & items are routed
+//! to the subschemas of these evaluations, scalars are validated as usual and keywords that depend
+//! on the whole container, e.g. `required` or `anyOf`, are checked when it ends. Keywords that
+//! need to see a container at once are rejected upfront.
+use std::collections::HashSet;
+
+use jpointer::{JsonPointerNode, Segment};
+use jsonlike::Json;
+
+use crate::{
+    error::ValidationErrorKind,
+    graph::SchemaId,
+    value::string_to_json,
+    vocabulary::{self, KeywordValue, SchemaGraph},
+    BuildError, BuildResult, ValidationError, Validator,
+};
+
+/// A single token of a JSON document, as produced by pull parsers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event<'a> {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    /// Key of the next object property.
+    Key(&'a str),
+    String(&'a str),
+    /// A number as written in the document.
+    Number(&'a str),
+    Bool(bool),
+    Null,
+}
+
+/// Validates a document event by event, see [`Validator::streaming`].
+///
+/// Only the keys of open objects are kept in memory, errors are reported by
+/// [`StreamingValidator::finish`] in no particular order.
+pub struct StreamingValidator<'v, J: Json> {
+    /// Open containers, the innermost last.
+    stack: Vec<Frame<'v, J>>,
+    /// Evaluation of the whole document until it starts.
+    root: Option<Pending<'v, J>>,
+    errors: Vec<ValidationError>,
+    empty_object: J,
+    empty_array: J,
+}
+
+/// How the result of an evaluation is used by the one it belongs to.
+#[derive(Debug, Clone, Copy)]
+enum Fold {
+    /// Errors are reported as they are.
+    Merge,
+    /// Kept for the keyword at the given index until the container ends, e.g. `anyOf` branches.
+    Outcome { keyword: usize, tag: usize },
+    /// Valid results are counted for the `contains` keyword at the given index.
+    Count { keyword: usize },
+}
+
+/// The evaluation that receives the result of another one.
+#[derive(Debug, Clone, Copy)]
+enum Parent {
+    /// Errors of the document.
+    Root,
+    /// An evaluation of the same container, e.g. for `allOf`.
+    Local(usize, Fold),
+    /// An evaluation of the enclosing container, e.g. for `properties`.
+    Enclosing(usize, Fold),
+}
+
+/// A schema to evaluate against the next value.
+struct Pending<'v, J: Json> {
+    graph: &'v SchemaGraph<J>,
+    schema: SchemaId,
+    path: Vec<Segment>,
+    /// Whether errors are collected or only validity is checked, e.g. in `anyOf` branches.
+    collect: bool,
+    parent: Parent,
+}
+
+/// A schema evaluated against an open container.
+struct Eval<'v, J: Json> {
+    graph: &'v SchemaGraph<J>,
+    schema: SchemaId,
+    path: Vec<Segment>,
+    collect: bool,
+    parent: Parent,
+    valid: bool,
+    errors: Vec<ValidationError>,
+    outcomes: Vec<Outcome>,
+    /// Numbers of valid items for `contains` keywords by their index.
+    counts: Vec<(usize, u64)>,
+}
+
+struct Outcome {
+    keyword: usize,
+    tag: usize,
+    valid: bool,
+    errors: Vec<ValidationError>,
+}
+
+struct Frame<'v, J: Json> {
+    location: Vec<Segment>,
+    /// Keys read so far, in order, `None` for arrays.
+    keys: Option<Vec<Box<str>>>,
+    /// Number of items read so far.
+    items: usize,
+    /// Whether the value of the last key is expected.
+    has_key: bool,
+    evals: Vec<Eval<'v, J>>,
+    /// Evaluations of the value of the last key.
+    next: Vec<Pending<'v, J>>,
+}
+
+impl<'v, J: Json> StreamingValidator<'v, J> {
+    pub(crate) fn new(validator: &'v Validator<J>) -> BuildResult<StreamingValidator<'v, J>> {
+        check(validator.graph())?;
+        Ok(StreamingValidator {
+            stack: Vec::new(),
+            root: Some(Pending {
+                graph: validator.graph(),
+                schema: validator.root(),
+                path: Vec::new(),
+                collect: true,
+                parent: Parent::Root,
+            }),
+            errors: Vec::new(),
+            empty_object: J::from_str("{}")?,
+            empty_array: J::from_str("[]")?,
+        })
+    }
+
+    /// Consume the next event of the document.
+    ///
+    /// Events that can not appear at this point, e.g. a key within an array, produce a
+    /// [`ValidationErrorKind::InvalidJson`] error and are ignored.
+    pub fn push(&mut self, event: Event<'_>) -> Result<(), ValidationError> {
+        match event {
+            Event::StartObject => self.start(false),
+            Event::EndObject => self.end(false),
+            Event::StartArray => self.start(true),
+            Event::EndArray => self.end(true),
+            Event::Key(key) => self.key(key),
+            Event::String(value) => {
+                let value = string_to_json::<J>(value).map_err(|error| self.invalid(error))?;
+                self.scalar(&value)
+            }
+            Event::Number(number) => {
+                let value = J::from_str(number)
+                    .ok()
+                    .filter(Json::is_number)
+                    .ok_or_else(|| self.invalid(format!("'{number}' is not a number")))?;
+                self.scalar(&value)
+            }
+            Event::Bool(value) => {
+                let value = J::from_str(if value { "true" } else { "false" })
+                    .map_err(|error| self.invalid(error))?;
+                self.scalar(&value)
+            }
+            Event::Null => {
+                let value = J::from_str("null").map_err(|error| self.invalid(error))?;
+                self.scalar(&value)
+            }
+        }
+    }
+
+    /// Errors of the document, failing if it is incomplete.
+    pub fn finish(self) -> Result<Vec<ValidationError>, ValidationError> {
+        if self.root.is_some() || !self.stack.is_empty() {
+            return Err(self.invalid("Unexpected end of the document"));
+        }
+        Ok(self.errors)
+    }
+
+    fn invalid(&self, message: impl ToString) -> ValidationError {
+        let location = self
+            .stack
+            .last()
+            .map_or(&[][..], |frame| frame.location.as_slice());
+        let kind = ValidationErrorKind::InvalidJson {
+            message: message.to_string(),
+        };
+        with_pointer(location, |location| {
+            ValidationError::new(kind, location, &JsonPointerNode::new())
+        })
+    }
+
+    fn key(&mut self, key: &str) -> Result<(), ValidationError> {
+        match self.stack.last_mut() {
+            Some(frame) if frame.keys.is_some() && !frame.has_key => {
+                frame.next = frame.route(&Segment::from(key));
+                frame.keys.as_mut().expect("Object").push(key.into());
+                frame.has_key = true;
+                Ok(())
+            }
+            _ => Err(self.invalid(format!("Unexpected key '{key}'"))),
+        }
+    }
+
+    /// Evaluations of the value starting with the current event, with its location.
+    fn value(&mut self) -> Result<(Vec<Pending<'v, J>>, Vec<Segment>), ValidationError> {
+        let Some(frame) = self.stack.last_mut() else {
+            return match self.root.take() {
+                Some(root) => Ok((vec![root], Vec::new())),
+                None => Err(self.invalid("Unexpected value after the end of the document")),
+            };
+        };
+        let segment = match &frame.keys {
+            Some(keys) if frame.has_key => {
+                frame.has_key = false;
+                Segment::from(keys.last().expect("Key was read").as_ref())
+            }
+            Some(_) => return Err(self.invalid("Expected a key")),
+            None => {
+                let segment = Segment::Index(frame.items);
+                frame.items += 1;
+                frame.next = frame.route(&segment);
+                segment
+            }
+        };
+        let mut location = frame.location.clone();
+        location.push(segment);
+        Ok((std::mem::take(&mut frame.next), location))
+    }
+
+    fn scalar(&mut self, instance: &J) -> Result<(), ValidationError> {
+        let (targets, location) = self.value()?;
+        for target in targets {
+            let (valid, errors) = evaluate(
+                target.graph,
+                target.schema,
+                instance,
+                &location,
+                &target.path,
+                target.collect,
+            );
+            self.fold(target.parent, valid, errors);
+        }
+        Ok(())
+    }
+
+    fn start(&mut self, array: bool) -> Result<(), ValidationError> {
+        let (targets, location) = self.value()?;
+        let mut frame = Frame {
+            location,
+            keys: (!array).then(Vec::new),
+            items: 0,
+            has_key: false,
+            evals: Vec::new(),
+            next: Vec::new(),
+        };
+        let empty = if array {
+            &self.empty_array
+        } else {
+            &self.empty_object
+        };
+        for target in targets {
+            frame.expand(target, empty);
+        }
+        self.stack.push(frame);
+        Ok(())
+    }
+
+    fn end(&mut self, array: bool) -> Result<(), ValidationError> {
+        match self.stack.last() {
+            Some(frame) if frame.keys.is_none() == array && !frame.has_key => {}
+            _ if array => return Err(self.invalid("Unexpected end of array")),
+            _ => return Err(self.invalid("Unexpected end of object")),
+        }
+        let mut frame = self.stack.pop().expect("Container is open");
+        let mut evals = std::mem::take(&mut frame.evals);
+        // Evaluations only belong to ones that precede them
+        while let Some(eval) = evals.pop() {
+            let parent = eval.parent;
+            let (valid, errors) = eval.finish(&frame);
+            match parent {
+                Parent::Local(index, fold) => evals[index].fold(fold, valid, errors),
+                parent => self.fold(parent, valid, errors),
+            }
+        }
+        Ok(())
+    }
+
+    fn fold(&mut self, parent: Parent, valid: bool, errors: Vec<ValidationError>) {
+        match parent {
+            Parent::Root => self.errors.extend(errors),
+            Parent::Enclosing(index, fold) => self
+                .stack
+                .last_mut()
+                .expect("Enclosing container is open")
+                .evals[index]
+                .fold(fold, valid, errors),
+            Parent::Local(..) => unreachable!("Resolved within the container"),
+        }
+    }
+}
+
+impl<'v, J: Json> Frame<'v, J> {
+    /// Start evaluating a schema and the subschemas it applies to the same container.
+    fn expand(&mut self, pending: Pending<'v, J>, empty: &J) {
+        let Pending {
+            graph,
+            schema,
+            path,
+            collect,
+            parent,
+        } = pending;
+        let mut eval = Eval {
+            graph,
+            schema,
+            path,
+            collect,
+            parent,
+            valid: true,
+            errors: Vec::new(),
+            outcomes: Vec::new(),
+            counts: Vec::new(),
+        };
+        let keywords = graph.keywords(schema);
+        for keyword in keywords {
+            // Keywords that do not depend on the container content
+            if matches!(
+                keyword,
+                KeywordValue::FalseSchema
+                    | KeywordValue::Type(_)
+                    | KeywordValue::Const(_)
+                    | KeywordValue::Enum(_)
+            ) {
+                eval.report(&self.location, None, |location, path, errors| {
+                    keyword.validate(graph, schema, empty, location, path, errors);
+                });
+            }
+        }
+        let path = eval.path.clone();
+        let index = self.evals.len();
+        self.evals.push(eval);
+        let local = |fold| Parent::Local(index, fold);
+        for (idx, keyword) in keywords.iter().enumerate() {
+            let mut apply = |graph, schema, path, collect, fold| {
+                let parent = local(fold);
+                let pending = Pending {
+                    graph,
+                    schema,
+                    path,
+                    collect,
+                    parent,
+                };
+                self.expand(pending, empty);
+            };
+            let outcome = |tag| Fold::Outcome { keyword: idx, tag };
+            match keyword {
+                KeywordValue::Ref(keyword) => {
+                    let path = join(&path, [keyword.keyword.into()]);
+                    apply(graph, keyword.schema, path, collect, Fold::Merge);
+                }
+                KeywordValue::Shared(keyword) => {
+                    apply(
+                        &keyword.graph,
+                        SchemaId::ROOT,
+                        path.clone(),
+                        collect,
+                        Fold::Merge,
+                    );
+                }
+                KeywordValue::AllOf(keyword) => {
+                    for (tag, schema) in keyword.schemas.iter().enumerate() {
+                        let path = join(&path, ["allOf".into(), tag.into()]);
+                        apply(graph, *schema, path, collect, Fold::Merge);
+                    }
+                }
+                KeywordValue::AnyOf(keyword) => {
+                    for (tag, schema) in keyword.schemas.iter().enumerate() {
+                        apply(graph, *schema, Vec::new(), false, outcome(tag));
+                    }
+                }
+                KeywordValue::OneOf(keyword) => {
+                    for (tag, schema) in keyword.schemas.iter().enumerate() {
+                        apply(graph, *schema, Vec::new(), false, outcome(tag));
+                    }
+                }
+                KeywordValue::Not(keyword) => {
+                    apply(graph, keyword.schema, Vec::new(), false, outcome(0));
+                }
+                KeywordValue::IfThenElse(keyword) => {
+                    // Both branches are evaluated until the condition is known
+                    apply(graph, keyword.condition, Vec::new(), false, outcome(0));
+                    let then = join(&path, ["then".into()]);
+                    apply(graph, keyword.then, then, collect, outcome(1));
+                    let otherwise = join(&path, ["else".into()]);
+                    apply(graph, keyword.otherwise, otherwise, collect, outcome(2));
+                }
+                KeywordValue::DependentSchemas(keyword) => {
+                    for (tag, (property, schema)) in keyword.schemas.iter().enumerate() {
+                        let path =
+                            join(&path, ["dependentSchemas".into(), property.as_ref().into()]);
+                        apply(graph, *schema, path, collect, outcome(tag));
+                    }
+                }
+                KeywordValue::Dependencies(keyword) => {
+                    for (tag, (property, schema)) in keyword.schemas.schemas.iter().enumerate() {
+                        let path = join(&path, ["dependencies".into(), property.as_ref().into()]);
+                        apply(graph, *schema, path, collect, outcome(tag));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Evaluations of the property or item at the given segment.
+    fn route(&mut self, segment: &Segment) -> Vec<Pending<'v, J>> {
+        let mut next = Vec::new();
+        for (index, eval) in self.evals.iter_mut().enumerate() {
+            if !eval.collect && !eval.valid {
+                continue;
+            }
+            let graph = eval.graph;
+            let (path, collect) = (eval.path.clone(), eval.collect);
+            let mut apply = |schema, path, collect, fold| {
+                next.push(Pending {
+                    graph,
+                    schema,
+                    path,
+                    collect,
+                    parent: Parent::Enclosing(index, fold),
+                });
+            };
+            for (idx, keyword) in graph.keywords(eval.schema).iter().enumerate() {
+                match (keyword, segment) {
+                    (KeywordValue::Properties(keyword), Segment::Key(key)) => {
+                        if let Some((name, schema)) =
+                            keyword.properties.iter().find(|(name, _)| name == key)
+                        {
+                            let path = join(&path, ["properties".into(), name.as_ref().into()]);
+                            apply(*schema, path, collect, Fold::Merge);
+                        }
+                    }
+                    (KeywordValue::PatternProperties(keyword), Segment::Key(key)) => {
+                        for (regex, schema) in &keyword.patterns {
+                            if regex.is_match(key) {
+                                let path = join(
+                                    &path,
+                                    ["patternProperties".into(), regex.as_str().into()],
+                                );
+                                apply(*schema, path, collect, Fold::Merge);
+                            }
+                        }
+                    }
+                    // Forbidden properties are reported together once the object ends
+                    (KeywordValue::AdditionalProperties(keyword), Segment::Key(key))
+                        if !keyword.forbidden && keyword.is_additional(key) =>
+                    {
+                        let path = join(&path, ["additionalProperties".into()]);
+                        apply(keyword.schema, path, collect, Fold::Merge);
+                    }
+                    (KeywordValue::PropertyNames(keyword), Segment::Key(key)) => {
+                        if let Ok(name) = string_to_json::<J>(key) {
+                            let location = join(&self.location, [segment.clone()]);
+                            let path = join(&path, ["propertyNames".into()]);
+                            let (valid, errors) =
+                                evaluate(graph, keyword.schema, &name, &location, &path, collect);
+                            eval.fold(Fold::Merge, valid, errors);
+                        }
+                    }
+                    (KeywordValue::PrefixItems(keyword), Segment::Index(item)) => {
+                        if let Some(schema) = keyword.schemas.get(*item) {
+                            let path = join(&path, [keyword.keyword.into(), (*item).into()]);
+                            apply(*schema, path, collect, Fold::Merge);
+                        }
+                    }
+                    (KeywordValue::Items(keyword), Segment::Index(item))
+                        if *item >= keyword.skip =>
+                    {
+                        let path = join(&path, [keyword.keyword.into()]);
+                        apply(keyword.schema, path, collect, Fold::Merge);
+                    }
+                    (KeywordValue::Contains(keyword), Segment::Index(_)) => {
+                        apply(
+                            keyword.schema,
+                            Vec::new(),
+                            false,
+                            Fold::Count { keyword: idx },
+                        );
+                    }
+                    _ => {}
+                }
+            }
+        }
+        next
+    }
+}
+
+impl<J: Json> Eval<'_, J> {
+    fn fold(&mut self, fold: Fold, valid: bool, errors: Vec<ValidationError>) {
+        match fold {
+            Fold::Merge => {
+                self.valid &= valid;
+                if self.collect {
+                    self.errors.extend(errors);
+                }
+            }
+            Fold::Outcome { keyword, tag } => self.outcomes.push(Outcome {
+                keyword,
+                tag,
+                valid,
+                errors,
+            }),
+            Fold::Count { keyword } => {
+                if valid {
+                    match self.counts.iter_mut().find(|(idx, _)| *idx == keyword) {
+                        Some((_, count)) => *count += 1,
+                        None => self.counts.push((keyword, 1)),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Run a check at the given location, with the path of the keyword if it does not add it.
+    fn report(
+        &mut self,
+        location: &[Segment],
+        keyword: Option<&str>,
+        check: impl FnOnce(&JsonPointerNode, &JsonPointerNode, &mut Vec<ValidationError>),
+    ) {
+        let mut errors = Vec::new();
+        with_pointer(location, |location| {
+            with_pointer(&self.path, |path| match keyword {
+                Some(keyword) => check(location, &path.push(keyword), &mut errors),
+                None => check(location, path, &mut errors),
+            });
+        });
+        self.fold(Fold::Merge, errors.is_empty(), errors);
+    }
+
+    /// Check keywords that depend on the whole container.
+    fn finish(mut self, frame: &Frame<'_, J>) -> (bool, Vec<ValidationError>) {
+        let keys: HashSet<&str> = frame.keys.iter().flatten().map(AsRef::as_ref).collect();
+        let contains = |key: &str| keys.contains(key);
+        let is_object = frame.keys.is_some();
+        let location = frame.location.as_slice();
+        let outcomes = std::mem::take(&mut self.outcomes);
+        let valid = |keyword: usize, tag: usize| {
+            outcomes
+                .iter()
+                .any(|outcome| outcome.keyword == keyword && outcome.tag == tag && outcome.valid)
+        };
+        let keywords = self.graph.keywords(self.schema);
+        for (idx, keyword) in keywords.iter().enumerate() {
+            match keyword {
+                KeywordValue::SizeLimit(keyword) => {
+                    let actual = match &frame.keys {
+                        Some(keys) if keyword.limits_properties() => keys.len(),
+                        None if keyword.limits_items() => frame.items,
+                        _ => continue,
+                    };
+                    self.report(location, Some(keyword.name()), |location, path, errors| {
+                        keyword.validate_size(actual as u64, location, path, errors);
+                    });
+                }
+                KeywordValue::Required(keyword) if is_object => {
+                    self.report(location, Some("required"), |location, path, errors| {
+                        keyword.validate_keys(contains, location, path, errors);
+                    });
+                }
+                KeywordValue::DependentRequired(keyword) if is_object => {
+                    self.report(
+                        location,
+                        Some("dependentRequired"),
+                        |location, path, errors| {
+                            keyword.validate_keys(contains, location, path, errors);
+                        },
+                    );
+                }
+                KeywordValue::Dependencies(keyword) if is_object => {
+                    self.report(location, Some("dependencies"), |location, path, errors| {
+                        keyword
+                            .required
+                            .validate_keys(contains, location, path, errors);
+                    });
+                }
+                KeywordValue::AdditionalProperties(keyword) if is_object && keyword.forbidden => {
+                    let unexpected: Vec<_> = frame
+                        .keys
+                        .iter()
+                        .flatten()
+                        .filter(|key| keyword.is_additional(key))
+                        .map(ToString::to_string)
+                        .collect();
+                    if !unexpected.is_empty() {
+                        let kind = ValidationErrorKind::AdditionalProperties { unexpected };
+                        self.report(
+                            location,
+                            Some("additionalProperties"),
+                            |location, path, errors| {
+                                errors.push(ValidationError::new(kind, location, path));
+                            },
+                        );
+                    }
+                }
+                KeywordValue::Contains(keyword) if !is_object => {
+                    let count = self
+                        .counts
+                        .iter()
+                        .find_map(|(keyword, count)| (*keyword == idx).then_some(*count))
+                        .unwrap_or(0);
+                    self.report(location, Some("contains"), |location, path, errors| {
+                        keyword.validate_count(count, location, path, errors);
+                    });
+                }
+                KeywordValue::AnyOf(keyword)
+                    if !(0..keyword.schemas.len()).any(|tag| valid(idx, tag)) =>
+                {
+                    self.report(location, Some("anyOf"), |location, path, errors| {
+                        errors.push(ValidationError::new(
+                            ValidationErrorKind::AnyOf,
+                            location,
+                            path,
+                        ));
+                    });
+                }
+                KeywordValue::OneOf(keyword) => {
+                    let kind = match (0..keyword.schemas.len())
+                        .filter(|tag| valid(idx, *tag))
+                        .count()
+                    {
+                        0 => ValidationErrorKind::OneOfNotValid,
+                        1 => continue,
+                        _ => ValidationErrorKind::OneOfMultipleValid,
+                    };
+                    self.report(location, Some("oneOf"), |location, path, errors| {
+                        errors.push(ValidationError::new(kind, location, path));
+                    });
+                }
+                KeywordValue::Not(_) if valid(idx, 0) => {
+                    self.report(location, Some("not"), |location, path, errors| {
+                        errors.push(ValidationError::new(
+                            ValidationErrorKind::Not,
+                            location,
+                            path,
+                        ));
+                    });
+                }
+                _ => {}
+            }
+        }
+        // Branches of `if` & dependent schemas that apply to the container
+        let applies: Vec<_> = outcomes
+            .iter()
+            .map(|outcome| match &keywords[outcome.keyword] {
+                KeywordValue::IfThenElse(_) => {
+                    outcome.tag == if valid(outcome.keyword, 0) { 1 } else { 2 }
+                }
+                KeywordValue::DependentSchemas(keyword) => {
+                    is_object && contains(&keyword.schemas[outcome.tag].0)
+                }
+                KeywordValue::Dependencies(keyword) => {
+                    is_object && contains(&keyword.schemas.schemas[outcome.tag].0)
+                }
+                _ => false,
+            })
+            .collect();
+        for (outcome, applies) in outcomes.into_iter().zip(applies) {
+            if applies {
+                self.fold(Fold::Merge, outcome.valid, outcome.errors);
+            }
+        }
+        (self.valid, self.errors)
+    }
+}
+
+/// Evaluate a schema against a complete value.
+fn evaluate<J: Json>(
+    graph: &SchemaGraph<J>,
+    schema: SchemaId,
+    instance: &J,
+    location: &[Segment],
+    path: &[Segment],
+    collect: bool,
+) -> (bool, Vec<ValidationError>) {
+    if !collect {
+        return (vocabulary::is_valid(graph, schema, instance), Vec::new());
+    }
+    let mut errors = Vec::new();
+    with_pointer(location, |location| {
+        with_pointer(path, |path| {
+            vocabulary::validate(graph, schema, instance, location, path, &mut errors);
+        });
+    });
+    (errors.is_empty(), errors)
+}
+
+fn join(path: &[Segment], segments: impl IntoIterator<Item = Segment>) -> Vec<Segment> {
+    let mut path = path.to_vec();
+    path.extend(segments);
+    path
+}
+
+/// Call `f` with the pointer made of the given segments.
+fn with_pointer<R>(segments: &[Segment], f: impl FnOnce(&JsonPointerNode) -> R) -> R {
+    fn build<R>(
+        node: &JsonPointerNode,
+        segments: &[Segment],
+        f: impl FnOnce(&JsonPointerNode) -> R,
+    ) -> R {
+        match segments.split_first() {
+            Some((segment, rest)) => build(&node.push(segment.clone()), rest, f),
+            None => f(node),
+        }
+    }
+    build(&JsonPointerNode::new(), segments, f)
+}
+
+/// Fail for keywords that need to see a whole container at once or an asynchronous check.
+fn check<J: Json>(graph: &SchemaGraph<J>) -> BuildResult<()> {
+    for id in 0..graph.len() {
+        for keyword in graph.keywords(SchemaId::new(id as u32)) {
+            match keyword {
+                KeywordValue::UniqueItems(_)
+                | KeywordValue::UnevaluatedProperties(_)
+                | KeywordValue::UnevaluatedItems(_)
+                | KeywordValue::Custom(_)
+                | KeywordValue::AsyncCustom(_) => {
+                    return Err(BuildError::requires_lookahead(keyword.name()))
+                }
+                KeywordValue::Format(format) if format.is_deferred() => {
+                    return Err(BuildError::requires_lookahead("format"))
+                }
+                KeywordValue::Const(value) if !value.is_scalar() => {
+                    return Err(BuildError::requires_lookahead("const"))
+                }
+                KeywordValue::Enum(options) if !options.is_scalar() => {
+                    return Err(BuildError::requires_lookahead("enum"))
+                }
+                KeywordValue::Shared(keyword) => check(&keyword.graph)?,
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::{Event, StreamingValidator};
+    use crate::{
+        blocking::ValidatorBuilder, AsyncFormat, AsyncKeyword, BoxFuture, BoxedAsyncFormat,
+        BoxedAsyncKeyword, BoxedKeyword, BuildResult, Draft, Keyword, KeywordContext,
+        ValidationError,
+    };
+
+    #[derive(Debug)]
+    struct Even;
+
+    impl Keyword<Value> for Even {
+        fn is_valid(&self, _: &KeywordContext<'_, Value>, instance: &Value) -> bool {
+            instance.as_u64().is_none_or(|value| value % 2 == 0)
+        }
+    }
+
+    #[derive(Debug)]
+    struct Remote;
+
+    impl AsyncKeyword<Value> for Remote {
+        fn is_valid<'i>(&'i self, _: &'i Value) -> BoxFuture<'i, bool> {
+            Box::pin(async { true })
+        }
+    }
+
+    impl AsyncFormat for Remote {
+        fn is_valid<'v>(&'v self, _: &'v str) -> BoxFuture<'v, bool> {
+            Box::pin(async { true })
+        }
+    }
+
+    fn feed(stream: &mut StreamingValidator<'_, Value>, value: &Value) {
+        let event = match value {
+            Value::Null => Event::Null,
+            Value::Bool(value) => Event::Bool(*value),
+            Value::Number(number) => {
+                let number = number.to_string();
+                return stream.push(Event::Number(&number)).expect("Valid event");
+            }
+            Value::String(value) => Event::String(value),
+            Value::Array(items) => {
+                stream.push(Event::StartArray).expect("Valid event");
+                for item in items {
+                    feed(stream, item);
+                }
+                Event::EndArray
+            }
+            Value::Object(object) => {
+                stream.push(Event::StartObject).expect("Valid event");
+                for (key, value) in object {
+                    stream.push(Event::Key(key)).expect("Valid event");
+                    feed(stream, value);
+                }
+                Event::EndObject
+            }
+        };
+        stream.push(event).expect("Valid event");
+    }
+
+    fn sorted(errors: impl IntoIterator<Item = ValidationError>) -> Vec<(String, String, String)> {
+        let mut errors: Vec<_> = errors
+            .into_iter()
+            .map(|error| {
+                (
+                    error.instance_location().to_string(),
+                    error.evaluation_path().to_string(),
+                    error.to_string(),
+                )
+            })
+            .collect();
+        errors.sort();
+        errors
+    }
+
+    #[test_case(Draft::Draft202012, &json!({"type": "integer", "minimum": 2}), &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"properties": {"a": {"type": "string"}, "b": {"properties": {"c": {"maxLength": 1}}, "required": ["d"]}}, "required": ["e"], "additionalProperties": false}), &json!({"a": 1, "b": {"c": "xy"}, "f": null, "g": []}))]
+    #[test_case(Draft::Draft202012, &json!({"prefixItems": [{"type": "null"}], "items": {"type": "integer"}, "minItems": 5, "contains": {"minimum": 3}, "maxContains": 1}), &json!([1, 2, 3, 4, "a"]))]
+    #[test_case(Draft::Draft202012, &json!({"contains": {"type": "object"}}), &json!([[], 1]))]
+    #[test_case(Draft::Draft202012, &json!({"anyOf": [{"required": ["a"]}, {"maxProperties": 0}], "oneOf": [{"type": "object"}, {"properties": {"b": {"type": "integer"}}}], "not": {"required": ["b"]}}), &json!({"b": 1}))]
+    #[test_case(Draft::Draft202012, &json!({"items": {"if": {"required": ["kind"]}, "then": {"properties": {"kind": {"enum": ["a", "b"]}}}, "else": {"minProperties": 2, "type": "object"}}}), &json!([{"kind": "c"}, {"kind": "a"}, {"other": 1}, 1]))]
+    #[test_case(Draft::Draft202012, &json!({"$ref": "#/$defs/node", "$defs": {"node": {"type": "object", "properties": {"value": {"type": "integer"}, "children": {"items": {"$ref": "#/$defs/node"}}}}}}), &json!({"value": 1, "children": [{"value": "a"}, {"children": [{"value": 2.5}, []]}]}))]
+    #[test_case(Draft::Draft202012, &json!({"dependentSchemas": {"a": {"required": ["b"]}}, "dependentRequired": {"c": ["d"]}, "propertyNames": {"maxLength": 2}, "patternProperties": {"^x": {"type": "string"}}, "maxProperties": 2}), &json!({"a": 1, "c": 1, "xyz": 1}))]
+    #[test_case(Draft::Draft202012, &json!({"allOf": [{"type": "array"}, {"const": 1}], "enum": [1, "a"]}), &json!({"a": [1, {}]}))]
+    #[test_case(Draft::Draft07, &json!({"items": [{"type": "string"}], "additionalItems": {"type": "boolean"}, "dependencies": {"a": ["b"], "c": {"required": ["d"]}}}), &json!(["a", "b", true, 1]))]
+    #[test_case(Draft::Draft07, &json!({"dependencies": {"a": ["b"], "c": {"required": ["d"]}}}), &json!({"a": 1, "c": 2}))]
+    #[test_case(Draft::Draft202012, &json!({"properties": {"a": {"type": "integer"}}, "required": ["a"]}), &json!({"a": 1}))]
+    fn test_streaming(draft: Draft, schema: &Value, instance: &Value) {
+        let validator = ValidatorBuilder::default()
+            .draft(draft)
+            .build(schema)
+            .expect("Invalid schema");
+        let mut stream = validator.streaming().expect("Streamable schema");
+        feed(&mut stream, instance);
+        let errors = stream.finish().expect("Complete document");
+        assert_eq!(errors.is_empty(), validator.is_valid(instance));
+        assert_eq!(sorted(errors), sorted(validator.iter_errors(instance)));
+    }
+
+    #[test_case(&json!({"uniqueItems": true}), "uniqueItems")]
+    #[test_case(&json!({"properties": {"a": {"$ref": "#/$defs/a"}}, "$defs": {"a": {"unevaluatedProperties": false}}}), "unevaluatedProperties")]
+    #[test_case(&json!({"items": {"const": [1]}}), "const")]
+    #[test_case(&json!({"enum": [1, {"a": 1}]}), "enum")]
+    #[test_case(&json!({"items": {"even": true}}), "even")]
+    #[test_case(&json!({"properties": {"a": {"remote": true}}}), "remote")]
+    #[test_case(&json!({"anyOf": [{"format": "remote-id"}]}), "format")]
+    fn test_requires_lookahead(schema: &Value, keyword: &str) {
+        let validator = ValidatorBuilder::default()
+            .keyword("even", |_: &Value| -> BuildResult<BoxedKeyword<Value>> {
+                Ok(Box::new(Even))
+            })
+            .async_keyword(
+                "remote",
+                |_: &Value| -> BuildResult<BoxedAsyncKeyword<Value>> { Ok(Box::new(Remote)) },
+            )
+            .async_format("remote-id", |_: &Value| -> BuildResult<BoxedAsyncFormat> {
+                Ok(Box::new(Remote))
+            })
+            .validate_formats(true)
+            .build(schema)
+            .expect("Invalid schema");
+        let error = validator.streaming().err().expect("Lookahead is needed");
+        assert_eq!(
+            error.to_string(),
+            format!("'{keyword}' can not be evaluated in a single pass")
+        );
+    }
+
+    #[test_case(&[Event::StartArray, Event::Key("a")], "Unexpected key 'a'", "")]
+    #[test_case(&[Event::StartObject, Event::Null], "Expected a key", "")]
+    #[test_case(&[Event::StartObject, Event::Key("a"), Event::StartArray, Event::EndObject], "Unexpected end of object", "/a")]
+    #[test_case(&[Event::StartObject, Event::Key("a"), Event::EndObject], "Unexpected end of object", "")]
+    #[test_case(&[Event::StartArray, Event::Number("1x")], "'1x' is not a number", "")]
+    #[test_case(&[Event::Null, Event::Null], "Unexpected value after the end of the document", "")]
+    fn test_invalid_events(events: &[Event<'_>], message: &str, location: &str) {
+        let validator = crate::blocking::validator_for(&json!({})).expect("Invalid schema");
+        let mut stream = validator.streaming().expect("Streamable schema");
+        let (last, events) = events.split_last().expect("Not empty");
+        for event in events {
+            stream.push(*event).expect("Valid event");
+        }
+        let error = stream.push(*last).expect_err("Invalid event");
+        assert_eq!(error.to_string(), format!("Invalid JSON: {message}"));
+        assert_eq!(error.instance_location().to_string(), location);
+    }
+
+    #[test]
+    fn test_incomplete_document() {
+        let validator = crate::blocking::validator_for(&json!({})).expect("Invalid schema");
+        let mut stream = validator.streaming().expect("Streamable schema");
+        stream.push(Event::StartArray).expect("Valid event");
+        let error = stream.finish().expect_err("Incomplete document");
+        assert_eq!(
+            error.to_string(),
+            "Invalid JSON: Unexpected end of the document"
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use core::fmt::{self, Write};
+
+use jsonlike::prelude::*;
+
+use crate::{
+    snapshot::{Reader, Writer},
+    BuildError, BuildResult,
+};
+
+/// Owned copy of a JSON value taken from a schema.
+///
+/// `Json` implementations are not required to be `Clone`, so keywords like `const` or `enum`
+/// keep their values in this representation instead.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(Box<str>),
+    Array(Vec<Value>),
+    Object(Vec<(Box<str>, Value)>),
+}
+
+impl Value {
+    pub(crate) fn from_json<J: Json>(value: &J) -> Value {
+        if let Some(object) = value.as_object() {
+            Value::Object(
+                object
+                    .iter()
+                    .filter_map(|(key, value)| {
+                        key.ok()
+                            .map(|key| (key.as_ref().into(), Value::from_json(value)))
+                    })
+                    .collect(),
+            )
+        } else if let Some(array) = value.as_array() {
+            Value::Array(
+                array
+                    .iter()
+                    .filter_map(Result::ok)
+                    .map(Value::from_json)
+                    .collect(),
+            )
+        } else if let Some(string) = value.as_string() {
+            Value::String(string.as_ref().into())
+        } else if let Some(number) = value.as_number() {
+            Value::Number(number.as_float().unwrap_or(f64::NAN))
+        } else if let Some(boolean) = value.as_boolean() {
+            Value::Bool(boolean)
+        } else {
+            Value::Null
+        }
+    }
+
+    pub(crate) fn is_scalar(&self) -> bool {
+        !matches!(self, Value::Array(_) | Value::Object(_))
+    }
+    /// Compare with an instance using JSON Schema equality rules, e.g. `1` is equal to `1.0`.
+    /// Convert to the given JSON representation.
+    pub(crate) fn to_json<J: Json>(&self) -> Result<J, JsonError> {
+        J::from_str(&self.to_string())
+    }
+    pub(crate) fn equals<J: Json>(&self, instance: &J) -> bool {
+        match self {
+            Value::Null => instance.is_null(),
+            Value::Bool(expected) => instance.as_boolean() == Some(*expected),
+            Value::Number(expected) => {
+                instance.as_number().and_then(JsonNumber::as_float) == Some(*expected)
+            }
+            Value::String(expected) => instance
+                .as_string()
+                .is_some_and(|string| string.as_ref() == expected.as_ref()),
+            Value::Array(expected) => instance.as_array().is_some_and(|array| {
+                let mut items = array.iter();
+                for value in expected {
+                    match items.next() {
+                        Some(Ok(item)) if value.equals(item) => {}
+                        _ => return false,
+                    }
+                }
+                items.next().is_none()
+            }),
+            Value::Object(expected) => instance.as_object().is_some_and(|object| {
+                object.iter().count() == expected.len()
+                    && expected
+                        .iter()
+                        .all(|(key, value)| object.get(key).is_some_and(|item| value.equals(item)))
+            }),
+        }
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        match self {
+            Value::Null => writer.u8(0),
+            Value::Bool(value) => {
+                writer.u8(1);
+                writer.bool(*value);
+            }
+            Value::Number(value) => {
+                writer.u8(2);
+                writer.f64(*value);
+            }
+            Value::String(value) => {
+                writer.u8(3);
+                writer.str(value);
+            }
+            Value::Array(items) => {
+                writer.u8(4);
+                writer.usize(items.len());
+                for item in items {
+                    item.encode(writer);
+                }
+            }
+            Value::Object(properties) => {
+                writer.u8(5);
+                writer.usize(properties.len());
+                for (key, value) in properties {
+                    writer.str(key);
+                    value.encode(writer);
+                }
+            }
+        }
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<Value> {
+        Ok(match reader.u8()? {
+            0 => Value::Null,
+            1 => Value::Bool(reader.bool()?),
+            2 => Value::Number(reader.f64()?),
+            3 => Value::String(reader.str()?.into()),
+            4 => Value::Array(
+                (0..reader.len()?)
+                    .map(|_| Value::decode(reader))
+                    .collect::<BuildResult<_>>()?,
+            ),
+            5 => Value::Object(
+                (0..reader.len()?)
+                    .map(|_| Ok((reader.str()?.into(), Value::decode(reader)?)))
+                    .collect::<BuildResult<_>>()?,
+            ),
+            _ => return Err(BuildError::invalid_snapshot()),
+        })
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Number(value) => write!(f, "{value}"),
+            Value::String(value) => write_string(f, value),
+            Value::Array(items) => {
+                f.write_char('[')?;
+                for (idx, item) in items.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_char(',')?;
+                    }
+                    item.fmt(f)?;
+                }
+                f.write_char(']')
+            }
+            Value::Object(items) => {
+                f.write_char('{')?;
+                for (idx, (key, value)) in items.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, key)?;
+                    f.write_char(':')?;
+                    value.fmt(f)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+/// Write a string as a quoted JSON string literal.
+pub(crate) fn write_string(f: &mut impl Write, value: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for ch in value.chars() {
+        match ch {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            ch if (ch as u32) < 0x20 => write!(f, "\\u{:04x}", ch as u32)?,
+            ch => f.write_char(ch)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Convert a string into a JSON value of the same type as the instance.
+pub(crate) fn string_to_json<J: Json>(value: &str) -> Result<J, JsonError> {
+    let mut buffer = String::with_capacity(value.len() + 2);
+    write_string(&mut buffer, value).expect("Writing to a string never fails");
+    J::from_str(&buffer)
+}
+
+/// Compare two instances using JSON Schema equality rules.
+pub(crate) fn equal<J: Json>(left: &J, right: &J) -> bool {
+    if let (Some(left), Some(right)) = (left.as_number(), right.as_number()) {
+        return left.as_float() == right.as_float();
+    }
+    if let (Some(left), Some(right)) = (left.as_array(), right.as_array()) {
+        let mut right = right.iter();
+        for item in left.iter() {
+            match (item, right.next()) {
+                (Ok(left), Some(Ok(right))) if equal(left, right) => {}
+                _ => return false,
+            }
+        }
+        return right.next().is_none();
+    }
+    if let (Some(left), Some(right)) = (left.as_object(), right.as_object()) {
+        return left.iter().count() == right.iter().count()
+            && left.iter().all(|(key, value)| {
+                key.ok()
+                    .and_then(|key| right.get(key.as_ref()))
+                    .is_some_and(|other| equal(value, other))
+            });
+    }
+    left.equal(right)
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::json;
+
+    use super::{equal, Value};
+
+    #[test]
+    fn test_numbers_are_equal_regardless_of_representation() {
+        assert!(equal(&json!([1, {"a": 2}]), &json!([1.0, {"a": 2.0}])));
+        assert!(Value::from_json(&json!(1.0)).equals(&json!(1)));
+    }
+
+    #[test]
+    fn test_display() {
+        let value = Value::from_json(&json!({"a": [1, "b\"", null, true]}));
+        assert_eq!(value.to_string(), r#"{"a":[1,"b\"",null,true]}"#);
+    }
+}
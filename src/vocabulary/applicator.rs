@@ -0,0 +1,1364 @@
+use std::collections::HashSet;
+
+use jpointer::JsonPointerNode;
+use jsonlike::prelude::*;
+
+use crate::{
+    compiler::Context,
+    error::ValidationErrorKind,
+    graph::SchemaId,
+    regex_engine::CompiledRegex,
+    snapshot::{Reader, Writer},
+    value::{string_to_json, Value},
+    vocabulary::{
+        annotate, evaluated_items, evaluated_properties, is_valid, validate,
+        validation::{as_string_list, compile_regex, DependentRequired},
+        Annotation, SchemaGraph, Subschema,
+    },
+    BuildError, BuildResult, ValidationError,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct AllOf {
+    pub(crate) schemas: Vec<Subschema>,
+}
+
+impl AllOf {
+    pub(crate) fn compile<'a, J: Json>(
+        ctx: &mut Context<'a, '_, J>,
+        value: &'a J,
+    ) -> BuildResult<AllOf> {
+        Ok(AllOf {
+            schemas: compile_schema_list(ctx, "allOf", value)?,
+        })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.schemas(&self.schemas);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<AllOf> {
+        Ok(AllOf {
+            schemas: reader.schemas()?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, graph: &SchemaGraph<J>, instance: &J) -> bool {
+        self.schemas
+            .iter()
+            .all(|schema| is_valid(graph, *schema, instance))
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        for (idx, schema) in self.schemas.iter().enumerate() {
+            validate(graph, *schema, instance, location, &path.push(idx), errors);
+        }
+    }
+    pub(crate) fn evaluated_properties<'i, J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &'i J,
+        evaluated: &mut HashSet<&'i str>,
+    ) {
+        for schema in &self.schemas {
+            evaluated_properties(graph, *schema, instance, evaluated);
+        }
+    }
+    pub(crate) fn evaluated_items<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        evaluated: &mut [bool],
+    ) {
+        for schema in &self.schemas {
+            evaluated_items(graph, *schema, instance, evaluated);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct AnyOf {
+    pub(crate) schemas: Vec<Subschema>,
+}
+
+impl AnyOf {
+    pub(crate) fn compile<'a, J: Json>(
+        ctx: &mut Context<'a, '_, J>,
+        value: &'a J,
+    ) -> BuildResult<AnyOf> {
+        Ok(AnyOf {
+            schemas: compile_schema_list(ctx, "anyOf", value)?,
+        })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.schemas(&self.schemas);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<AnyOf> {
+        Ok(AnyOf {
+            schemas: reader.schemas()?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, graph: &SchemaGraph<J>, instance: &J) -> bool {
+        self.schemas
+            .iter()
+            .any(|schema| is_valid(graph, *schema, instance))
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if !self.is_valid(graph, instance) {
+            errors.push(ValidationError::new(
+                ValidationErrorKind::AnyOf,
+                location,
+                path,
+            ));
+        }
+    }
+    pub(crate) fn evaluated_properties<'i, J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &'i J,
+        evaluated: &mut HashSet<&'i str>,
+    ) {
+        for schema in &self.schemas {
+            if is_valid(graph, *schema, instance) {
+                evaluated_properties(graph, *schema, instance, evaluated);
+            }
+        }
+    }
+    pub(crate) fn evaluated_items<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        evaluated: &mut [bool],
+    ) {
+        for schema in &self.schemas {
+            if is_valid(graph, *schema, instance) {
+                evaluated_items(graph, *schema, instance, evaluated);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct OneOf {
+    pub(crate) schemas: Vec<Subschema>,
+}
+
+impl OneOf {
+    pub(crate) fn compile<'a, J: Json>(
+        ctx: &mut Context<'a, '_, J>,
+        value: &'a J,
+    ) -> BuildResult<OneOf> {
+        Ok(OneOf {
+            schemas: compile_schema_list(ctx, "oneOf", value)?,
+        })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.schemas(&self.schemas);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<OneOf> {
+        Ok(OneOf {
+            schemas: reader.schemas()?,
+        })
+    }
+    fn valid_count<J: Json>(&self, graph: &SchemaGraph<J>, instance: &J) -> usize {
+        self.schemas
+            .iter()
+            .filter(|schema| is_valid(graph, **schema, instance))
+            .take(2)
+            .count()
+    }
+    pub(crate) fn is_valid<J: Json>(&self, graph: &SchemaGraph<J>, instance: &J) -> bool {
+        self.valid_count(graph, instance) == 1
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let kind = match self.valid_count(graph, instance) {
+            0 => ValidationErrorKind::OneOfNotValid,
+            1 => return,
+            _ => ValidationErrorKind::OneOfMultipleValid,
+        };
+        errors.push(ValidationError::new(kind, location, path));
+    }
+    pub(crate) fn evaluated_properties<'i, J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &'i J,
+        evaluated: &mut HashSet<&'i str>,
+    ) {
+        for schema in &self.schemas {
+            if is_valid(graph, *schema, instance) {
+                evaluated_properties(graph, *schema, instance, evaluated);
+            }
+        }
+    }
+    pub(crate) fn evaluated_items<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        evaluated: &mut [bool],
+    ) {
+        for schema in &self.schemas {
+            if is_valid(graph, *schema, instance) {
+                evaluated_items(graph, *schema, instance, evaluated);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Not {
+    pub(crate) schema: Subschema,
+}
+
+impl Not {
+    pub(crate) fn compile<'a, J: Json>(
+        ctx: &mut Context<'a, '_, J>,
+        value: &'a J,
+    ) -> BuildResult<Not> {
+        Ok(Not {
+            schema: ctx.compile_subschema(value)?,
+        })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.schema(self.schema);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<Not> {
+        Ok(Not {
+            schema: reader.schema()?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, graph: &SchemaGraph<J>, instance: &J) -> bool {
+        !is_valid(graph, self.schema, instance)
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if !self.is_valid(graph, instance) {
+            errors.push(ValidationError::new(
+                ValidationErrorKind::Not,
+                location,
+                path,
+            ));
+        }
+    }
+}
+
+/// `if`, `then` & `else` compiled as a single keyword.
+#[derive(Debug, Clone)]
+pub(crate) struct IfThenElse {
+    pub(crate) condition: Subschema,
+    pub(crate) then: Subschema,
+    pub(crate) otherwise: Subschema,
+}
+
+impl IfThenElse {
+    pub(crate) fn compile<'a, J: Json>(
+        ctx: &mut Context<'a, '_, J>,
+        parent: &'a J::Object,
+        value: &'a J,
+    ) -> BuildResult<IfThenElse> {
+        let condition = ctx.compile_subschema(value)?;
+        let then = match parent.get("then") {
+            Some(then) => ctx.compile_subschema(then)?,
+            None => SchemaId::TRUE,
+        };
+        let otherwise = match parent.get("else") {
+            Some(otherwise) => ctx.compile_subschema(otherwise)?,
+            None => SchemaId::TRUE,
+        };
+        Ok(IfThenElse {
+            condition,
+            then,
+            otherwise,
+        })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.schema(self.condition);
+        writer.schema(self.then);
+        writer.schema(self.otherwise);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<IfThenElse> {
+        Ok(IfThenElse {
+            condition: reader.schema()?,
+            then: reader.schema()?,
+            otherwise: reader.schema()?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, graph: &SchemaGraph<J>, instance: &J) -> bool {
+        if is_valid(graph, self.condition, instance) {
+            is_valid(graph, self.then, instance)
+        } else {
+            is_valid(graph, self.otherwise, instance)
+        }
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if is_valid(graph, self.condition, instance) {
+            validate(
+                graph,
+                self.then,
+                instance,
+                location,
+                &path.push("then"),
+                errors,
+            );
+        } else {
+            validate(
+                graph,
+                self.otherwise,
+                instance,
+                location,
+                &path.push("else"),
+                errors,
+            );
+        }
+    }
+    pub(crate) fn evaluated_properties<'i, J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &'i J,
+        evaluated: &mut HashSet<&'i str>,
+    ) {
+        if is_valid(graph, self.condition, instance) {
+            evaluated_properties(graph, self.condition, instance, evaluated);
+            evaluated_properties(graph, self.then, instance, evaluated);
+        } else {
+            evaluated_properties(graph, self.otherwise, instance, evaluated);
+        }
+    }
+    pub(crate) fn evaluated_items<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        evaluated: &mut [bool],
+    ) {
+        if is_valid(graph, self.condition, instance) {
+            evaluated_items(graph, self.condition, instance, evaluated);
+            evaluated_items(graph, self.then, instance, evaluated);
+        } else {
+            evaluated_items(graph, self.otherwise, instance, evaluated);
+        }
+    }
+    pub(crate) fn annotate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        annotations: &mut Vec<Annotation>,
+    ) {
+        if is_valid(graph, self.condition, instance) {
+            let condition = &path.push("if");
+            annotate(
+                graph,
+                self.condition,
+                instance,
+                location,
+                condition,
+                annotations,
+            );
+            let then = &path.push("then");
+            annotate(graph, self.then, instance, location, then, annotations);
+        } else {
+            let otherwise = &path.push("else");
+            annotate(
+                graph,
+                self.otherwise,
+                instance,
+                location,
+                otherwise,
+                annotations,
+            );
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Properties {
+    pub(crate) properties: Vec<(Box<str>, Subschema)>,
+    /// `default` values of property subschemas, inserted by [`crate::Validator::apply_defaults`].
+    pub(crate) defaults: Vec<(Box<str>, Value)>,
+}
+
+impl Properties {
+    pub(crate) fn compile<'a, J: Json>(
+        ctx: &mut Context<'a, '_, J>,
+        value: &'a J,
+    ) -> BuildResult<Properties> {
+        let defaults = value
+            .as_object()
+            .into_iter()
+            .flat_map(JsonObject::iter)
+            .filter_map(|(name, schema)| {
+                let default = schema.as_object()?.get("default")?;
+                Some((name.ok()?.as_ref().into(), Value::from_json(default)))
+            })
+            .collect();
+        Ok(Properties {
+            properties: compile_schema_map(ctx, "properties", value)?,
+            defaults,
+        })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.named_schemas(&self.properties);
+        writer.usize(self.defaults.len());
+        for (name, value) in &self.defaults {
+            writer.str(name);
+            value.encode(writer);
+        }
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<Properties> {
+        Ok(Properties {
+            properties: reader.named_schemas()?,
+            defaults: (0..reader.len()?)
+                .map(|_| Ok((reader.str()?.into(), Value::decode(reader)?)))
+                .collect::<BuildResult<_>>()?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, graph: &SchemaGraph<J>, instance: &J) -> bool {
+        let Some(object) = instance.as_object() else {
+            return true;
+        };
+        self.properties.iter().all(|(name, schema)| {
+            object
+                .get(name)
+                .is_none_or(|value| is_valid(graph, *schema, value))
+        })
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let Some(object) = instance.as_object() else {
+            return;
+        };
+        for (name, schema) in &self.properties {
+            if let Some(value) = object.get(name) {
+                validate(
+                    graph,
+                    *schema,
+                    value,
+                    &location.push(name.as_ref()),
+                    &path.push(name.as_ref()),
+                    errors,
+                );
+            }
+        }
+    }
+    pub(crate) fn evaluated_properties<'i, J: Json>(
+        &self,
+        instance: &'i J,
+        evaluated: &mut HashSet<&'i str>,
+    ) {
+        if let Some(object) = instance.as_object() {
+            for (key, _) in object.iter() {
+                if let Ok(key) = key {
+                    if self
+                        .properties
+                        .iter()
+                        .any(|(name, _)| **name == *key.as_ref())
+                    {
+                        evaluated.insert(key.as_ref());
+                    }
+                }
+            }
+        }
+    }
+    pub(crate) fn annotate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        annotations: &mut Vec<Annotation>,
+    ) {
+        let Some(object) = instance.as_object() else {
+            return;
+        };
+        for (name, schema) in &self.properties {
+            if let Some(value) = object.get(name) {
+                annotate(
+                    graph,
+                    *schema,
+                    value,
+                    &location.push(name.as_ref()),
+                    &path.push(name.as_ref()),
+                    annotations,
+                );
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PatternProperties {
+    pub(crate) patterns: Vec<(CompiledRegex, Subschema)>,
+}
+
+impl PatternProperties {
+    pub(crate) fn compile<'a, J: Json>(
+        ctx: &mut Context<'a, '_, J>,
+        value: &'a J,
+    ) -> BuildResult<PatternProperties> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| BuildError::invalid_keyword("patternProperties", "an object"))?;
+        let patterns = object
+            .iter()
+            .map(|(pattern, schema)| {
+                Ok((
+                    compile_regex(ctx, pattern?.as_ref())?,
+                    ctx.compile_subschema(schema)?,
+                ))
+            })
+            .collect::<BuildResult<_>>()?;
+        Ok(PatternProperties { patterns })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.usize(self.patterns.len());
+        for (regex, schema) in &self.patterns {
+            regex.encode(writer);
+            writer.schema(*schema);
+        }
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<PatternProperties> {
+        let patterns = (0..reader.len()?)
+            .map(|_| Ok((CompiledRegex::decode(reader)?, reader.schema()?)))
+            .collect::<BuildResult<_>>()?;
+        Ok(PatternProperties { patterns })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, graph: &SchemaGraph<J>, instance: &J) -> bool {
+        let Some(object) = instance.as_object() else {
+            return true;
+        };
+        object.iter().all(|(key, value)| {
+            let Ok(key) = key else {
+                return false;
+            };
+            self.patterns.iter().all(|(regex, schema)| {
+                !regex.is_match(key.as_ref()) || is_valid(graph, *schema, value)
+            })
+        })
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let Some(object) = instance.as_object() else {
+            return;
+        };
+        for (key, value) in object.iter() {
+            let Ok(key) = key else {
+                continue;
+            };
+            for (regex, schema) in &self.patterns {
+                if regex.is_match(key.as_ref()) {
+                    validate(
+                        graph,
+                        *schema,
+                        value,
+                        &location.push(key.as_ref()),
+                        &path.push(regex.as_str()),
+                        errors,
+                    );
+                }
+            }
+        }
+    }
+    pub(crate) fn evaluated_properties<'i, J: Json>(
+        &self,
+        instance: &'i J,
+        evaluated: &mut HashSet<&'i str>,
+    ) {
+        if let Some(object) = instance.as_object() {
+            for (key, _) in object.iter() {
+                if let Ok(key) = key {
+                    if self
+                        .patterns
+                        .iter()
+                        .any(|(regex, _)| regex.is_match(key.as_ref()))
+                    {
+                        evaluated.insert(key.as_ref());
+                    }
+                }
+            }
+        }
+    }
+    pub(crate) fn annotate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        annotations: &mut Vec<Annotation>,
+    ) {
+        let Some(object) = instance.as_object() else {
+            return;
+        };
+        for (key, value) in object.iter() {
+            let Ok(key) = key else {
+                continue;
+            };
+            for (regex, schema) in &self.patterns {
+                if regex.is_match(key.as_ref()) {
+                    annotate(
+                        graph,
+                        *schema,
+                        value,
+                        &location.push(key.as_ref()),
+                        &path.push(regex.as_str()),
+                        annotations,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct AdditionalProperties {
+    pub(crate) schema: Subschema,
+    /// Whether the schema is `false`, then a single error lists all unexpected properties.
+    pub(crate) forbidden: bool,
+    pub(crate) properties: Vec<Box<str>>,
+    pub(crate) patterns: Vec<CompiledRegex>,
+}
+
+impl AdditionalProperties {
+    pub(crate) fn compile<'a, J: Json>(
+        ctx: &mut Context<'a, '_, J>,
+        parent: &'a J::Object,
+        value: &'a J,
+    ) -> BuildResult<AdditionalProperties> {
+        let properties = match parent.get("properties").and_then(Json::as_object) {
+            Some(properties) => properties
+                .iter()
+                .map(|(name, _)| Ok(name?.as_ref().into()))
+                .collect::<BuildResult<_>>()?,
+            None => Vec::new(),
+        };
+        Ok(AdditionalProperties {
+            schema: ctx.compile_subschema(value)?,
+            forbidden: value.as_boolean() == Some(false),
+            properties,
+            patterns: compile_patterns::<J>(ctx, parent)?,
+        })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.schema(self.schema);
+        writer.bool(self.forbidden);
+        writer.strings(&self.properties);
+        writer.usize(self.patterns.len());
+        for regex in &self.patterns {
+            regex.encode(writer);
+        }
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<AdditionalProperties> {
+        Ok(AdditionalProperties {
+            schema: reader.schema()?,
+            forbidden: reader.bool()?,
+            properties: reader.strings()?,
+            patterns: (0..reader.len()?)
+                .map(|_| CompiledRegex::decode(reader))
+                .collect::<BuildResult<_>>()?,
+        })
+    }
+    pub(crate) fn is_additional(&self, key: &str) -> bool {
+        !self.properties.iter().any(|name| **name == *key)
+            && !self.patterns.iter().any(|regex| regex.is_match(key))
+    }
+    pub(crate) fn is_valid<J: Json>(&self, graph: &SchemaGraph<J>, instance: &J) -> bool {
+        let Some(object) = instance.as_object() else {
+            return true;
+        };
+        object.iter().all(|(key, value)| {
+            let Ok(key) = key else {
+                return false;
+            };
+            !self.is_additional(key.as_ref()) || is_valid(graph, self.schema, value)
+        })
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let Some(object) = instance.as_object() else {
+            return;
+        };
+        let mut unexpected = Vec::new();
+        for (key, value) in object.iter() {
+            let Ok(key) = key else {
+                continue;
+            };
+            if self.is_additional(key.as_ref()) {
+                if self.forbidden {
+                    unexpected.push(key.as_ref().to_owned());
+                } else {
+                    validate(
+                        graph,
+                        self.schema,
+                        value,
+                        &location.push(key.as_ref()),
+                        path,
+                        errors,
+                    );
+                }
+            }
+        }
+        if !unexpected.is_empty() {
+            errors.push(ValidationError::new(
+                ValidationErrorKind::AdditionalProperties { unexpected },
+                location,
+                path,
+            ));
+        }
+    }
+    pub(crate) fn evaluated_properties<'i, J: Json>(
+        &self,
+        instance: &'i J,
+        evaluated: &mut HashSet<&'i str>,
+    ) {
+        if let Some(object) = instance.as_object() {
+            evaluated.extend(
+                object
+                    .iter()
+                    .filter_map(|(key, _)| key.ok())
+                    .map(AsRef::as_ref),
+            );
+        }
+    }
+    pub(crate) fn annotate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        annotations: &mut Vec<Annotation>,
+    ) {
+        let Some(object) = instance.as_object() else {
+            return;
+        };
+        for (key, value) in object.iter() {
+            let Ok(key) = key else {
+                continue;
+            };
+            if self.is_additional(key.as_ref()) {
+                let location = &location.push(key.as_ref());
+                annotate(graph, self.schema, value, location, path, annotations);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PropertyNames {
+    pub(crate) schema: Subschema,
+}
+
+impl PropertyNames {
+    pub(crate) fn compile<'a, J: Json>(
+        ctx: &mut Context<'a, '_, J>,
+        value: &'a J,
+    ) -> BuildResult<PropertyNames> {
+        Ok(PropertyNames {
+            schema: ctx.compile_subschema(value)?,
+        })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.schema(self.schema);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<PropertyNames> {
+        Ok(PropertyNames {
+            schema: reader.schema()?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, graph: &SchemaGraph<J>, instance: &J) -> bool {
+        let Some(object) = instance.as_object() else {
+            return true;
+        };
+        object.iter().all(|(key, _)| {
+            key.ok()
+                .and_then(|key| string_to_json::<J>(key.as_ref()).ok())
+                .is_some_and(|name| is_valid(graph, self.schema, &name))
+        })
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let Some(object) = instance.as_object() else {
+            return;
+        };
+        for (key, _) in object.iter() {
+            let Some(key) = key.ok() else {
+                continue;
+            };
+            if let Ok(name) = string_to_json::<J>(key.as_ref()) {
+                validate(
+                    graph,
+                    self.schema,
+                    &name,
+                    &location.push(key.as_ref()),
+                    path,
+                    errors,
+                );
+            }
+        }
+    }
+}
+
+/// `prefixItems` and the array form of `items`.
+#[derive(Debug, Clone)]
+pub(crate) struct PrefixItems {
+    pub(crate) keyword: &'static str,
+    pub(crate) schemas: Vec<Subschema>,
+}
+
+impl PrefixItems {
+    pub(crate) fn compile<'a, J: Json>(
+        ctx: &mut Context<'a, '_, J>,
+        keyword: &'static str,
+        value: &'a J,
+    ) -> BuildResult<PrefixItems> {
+        Ok(PrefixItems {
+            keyword,
+            schemas: compile_schema_list(ctx, keyword, value)?,
+        })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.str(self.keyword);
+        writer.schemas(&self.schemas);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<PrefixItems> {
+        Ok(PrefixItems {
+            keyword: reader.name(&["prefixItems", "items"])?,
+            schemas: reader.schemas()?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, graph: &SchemaGraph<J>, instance: &J) -> bool {
+        let Some(array) = instance.as_array() else {
+            return true;
+        };
+        array
+            .iter()
+            .zip(&self.schemas)
+            .all(|(item, schema)| item.is_ok_and(|item| is_valid(graph, *schema, item)))
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let Some(array) = instance.as_array() else {
+            return;
+        };
+        for (idx, (item, schema)) in array.iter().zip(&self.schemas).enumerate() {
+            if let Ok(item) = item {
+                validate(
+                    graph,
+                    *schema,
+                    item,
+                    &location.push(idx),
+                    &path.push(idx),
+                    errors,
+                );
+            }
+        }
+    }
+    pub(crate) fn evaluated_items(&self, evaluated: &mut [bool]) {
+        for flag in evaluated.iter_mut().take(self.schemas.len()) {
+            *flag = true;
+        }
+    }
+    pub(crate) fn annotate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        annotations: &mut Vec<Annotation>,
+    ) {
+        let Some(array) = instance.as_array() else {
+            return;
+        };
+        for (idx, (item, schema)) in array.iter().zip(&self.schemas).enumerate() {
+            if let Ok(item) = item {
+                let (location, path) = (&location.push(idx), &path.push(idx));
+                annotate(graph, *schema, item, location, path, annotations);
+            }
+        }
+    }
+}
+
+/// `items` applied to all elements after the first `skip` ones, also used for `additionalItems`.
+#[derive(Debug, Clone)]
+pub(crate) struct Items {
+    pub(crate) keyword: &'static str,
+    pub(crate) schema: Subschema,
+    pub(crate) skip: usize,
+}
+
+impl Items {
+    pub(crate) fn compile<'a, J: Json>(
+        ctx: &mut Context<'a, '_, J>,
+        keyword: &'static str,
+        value: &'a J,
+        skip: usize,
+    ) -> BuildResult<Items> {
+        Ok(Items {
+            keyword,
+            schema: ctx.compile_subschema(value)?,
+            skip,
+        })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.str(self.keyword);
+        writer.schema(self.schema);
+        writer.usize(self.skip);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<Items> {
+        Ok(Items {
+            keyword: reader.name(&["items", "additionalItems"])?,
+            schema: reader.schema()?,
+            skip: reader.usize()?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, graph: &SchemaGraph<J>, instance: &J) -> bool {
+        let Some(array) = instance.as_array() else {
+            return true;
+        };
+        array
+            .iter()
+            .skip(self.skip)
+            .all(|item| item.is_ok_and(|item| is_valid(graph, self.schema, item)))
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let Some(array) = instance.as_array() else {
+            return;
+        };
+        for (idx, item) in array.iter().enumerate().skip(self.skip) {
+            if let Ok(item) = item {
+                validate(graph, self.schema, item, &location.push(idx), path, errors);
+            }
+        }
+    }
+    pub(crate) fn evaluated_items(&self, evaluated: &mut [bool]) {
+        for flag in evaluated.iter_mut().skip(self.skip) {
+            *flag = true;
+        }
+    }
+    pub(crate) fn annotate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        annotations: &mut Vec<Annotation>,
+    ) {
+        let Some(array) = instance.as_array() else {
+            return;
+        };
+        for (idx, item) in array.iter().enumerate().skip(self.skip) {
+            if let Ok(item) = item {
+                annotate(
+                    graph,
+                    self.schema,
+                    item,
+                    &location.push(idx),
+                    path,
+                    annotations,
+                );
+            }
+        }
+    }
+}
+
+/// `contains` together with `minContains` & `maxContains`.
+#[derive(Debug, Clone)]
+pub(crate) struct Contains {
+    pub(crate) schema: Subschema,
+    pub(crate) min: u64,
+    pub(crate) max: Option<u64>,
+}
+
+impl Contains {
+    pub(crate) fn compile<'a, J: Json>(
+        ctx: &mut Context<'a, '_, J>,
+        value: &'a J,
+        min: u64,
+        max: Option<u64>,
+    ) -> BuildResult<Contains> {
+        Ok(Contains {
+            schema: ctx.compile_subschema(value)?,
+            min,
+            max,
+        })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.schema(self.schema);
+        writer.u64(self.min);
+        writer.bool(self.max.is_some());
+        writer.u64(self.max.unwrap_or_default());
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<Contains> {
+        let schema = reader.schema()?;
+        let min = reader.u64()?;
+        let has_max = reader.bool()?;
+        let max = reader.u64()?;
+        Ok(Contains {
+            schema,
+            min,
+            max: has_max.then_some(max),
+        })
+    }
+    fn count<J: Json>(&self, graph: &SchemaGraph<J>, array: &J::Array) -> u64 {
+        array
+            .iter()
+            .filter(|item| {
+                item.as_ref()
+                    .is_ok_and(|item| is_valid(graph, self.schema, *item))
+            })
+            .count() as u64
+    }
+    pub(crate) fn is_valid<J: Json>(&self, graph: &SchemaGraph<J>, instance: &J) -> bool {
+        let Some(array) = instance.as_array() else {
+            return true;
+        };
+        self.is_valid_count(self.count(graph, array))
+    }
+    /// Check the number of matching items counted by the caller.
+    pub(crate) fn is_valid_count(&self, count: u64) -> bool {
+        count >= self.min && self.max.is_none_or(|max| count <= max)
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if let Some(array) = instance.as_array() {
+            self.validate_count(self.count(graph, array), location, path, errors);
+        }
+    }
+    pub(crate) fn validate_count(
+        &self,
+        count: u64,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let kind = if count < self.min {
+            if count == 0 && self.min == 1 {
+                ValidationErrorKind::Contains
+            } else {
+                ValidationErrorKind::MinContains { limit: self.min }
+            }
+        } else {
+            match self.max {
+                Some(max) if count > max => ValidationErrorKind::MaxContains { limit: max },
+                _ => return,
+            }
+        };
+        errors.push(ValidationError::new(kind, location, path));
+    }
+    pub(crate) fn evaluated_items<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        evaluated: &mut [bool],
+    ) {
+        if let Some(array) = instance.as_array() {
+            for (item, flag) in array.iter().zip(evaluated.iter_mut()) {
+                if item.is_ok_and(|item| is_valid(graph, self.schema, item)) {
+                    *flag = true;
+                }
+            }
+        }
+    }
+    pub(crate) fn annotate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        annotations: &mut Vec<Annotation>,
+    ) {
+        let Some(array) = instance.as_array() else {
+            return;
+        };
+        for (idx, item) in array.iter().enumerate() {
+            if let Ok(item) = item {
+                annotate(
+                    graph,
+                    self.schema,
+                    item,
+                    &location.push(idx),
+                    path,
+                    annotations,
+                );
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct DependentSchemas {
+    pub(crate) schemas: Vec<(Box<str>, Subschema)>,
+}
+
+impl DependentSchemas {
+    pub(crate) fn compile<'a, J: Json>(
+        ctx: &mut Context<'a, '_, J>,
+        value: &'a J,
+    ) -> BuildResult<DependentSchemas> {
+        Ok(DependentSchemas {
+            schemas: compile_schema_map(ctx, "dependentSchemas", value)?,
+        })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.named_schemas(&self.schemas);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<DependentSchemas> {
+        Ok(DependentSchemas {
+            schemas: reader.named_schemas()?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, graph: &SchemaGraph<J>, instance: &J) -> bool {
+        let Some(object) = instance.as_object() else {
+            return true;
+        };
+        self.schemas.iter().all(|(property, schema)| {
+            !object.contains_key(property) || is_valid(graph, *schema, instance)
+        })
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let Some(object) = instance.as_object() else {
+            return;
+        };
+        for (property, schema) in &self.schemas {
+            if object.contains_key(property) {
+                validate(
+                    graph,
+                    *schema,
+                    instance,
+                    location,
+                    &path.push(property.as_ref()),
+                    errors,
+                );
+            }
+        }
+    }
+    pub(crate) fn evaluated_properties<'i, J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &'i J,
+        evaluated: &mut HashSet<&'i str>,
+    ) {
+        if let Some(object) = instance.as_object() {
+            for (property, schema) in &self.schemas {
+                if object.contains_key(property) && is_valid(graph, *schema, instance) {
+                    evaluated_properties(graph, *schema, instance, evaluated);
+                }
+            }
+        }
+    }
+    pub(crate) fn annotate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        annotations: &mut Vec<Annotation>,
+    ) {
+        let Some(object) = instance.as_object() else {
+            return;
+        };
+        for (property, schema) in &self.schemas {
+            if object.contains_key(property) {
+                let path = &path.push(property.as_ref());
+                annotate(graph, *schema, instance, location, path, annotations);
+            }
+        }
+    }
+}
+
+/// Draft 4 - 7 `dependencies` which mixes `dependentRequired` & `dependentSchemas`.
+#[derive(Debug, Clone)]
+pub(crate) struct Dependencies {
+    pub(crate) required: DependentRequired,
+    pub(crate) schemas: DependentSchemas,
+}
+
+impl Dependencies {
+    pub(crate) fn compile<'a, J: Json>(
+        ctx: &mut Context<'a, '_, J>,
+        value: &'a J,
+    ) -> BuildResult<Dependencies> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| BuildError::invalid_keyword("dependencies", "an object"))?;
+        let mut required = Vec::new();
+        let mut schemas = Vec::new();
+        for (property, dependency) in object.iter() {
+            let property: Box<str> = property?.as_ref().into();
+            if dependency.is_array() {
+                required.push((property, as_string_list("dependencies", dependency)?));
+            } else {
+                schemas.push((property, ctx.compile_subschema(dependency)?));
+            }
+        }
+        Ok(Dependencies {
+            required: DependentRequired::new(required),
+            schemas: DependentSchemas { schemas },
+        })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        self.required.encode(writer);
+        self.schemas.encode(writer);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<Dependencies> {
+        Ok(Dependencies {
+            required: DependentRequired::decode(reader)?,
+            schemas: DependentSchemas::decode(reader)?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, graph: &SchemaGraph<J>, instance: &J) -> bool {
+        self.required.is_valid(instance) && self.schemas.is_valid(graph, instance)
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        self.required.validate(instance, location, path, errors);
+        self.schemas
+            .validate(graph, instance, location, path, errors);
+    }
+}
+
+fn compile_schema_list<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    keyword: &str,
+    value: &'a J,
+) -> BuildResult<Vec<Subschema>> {
+    let array = value
+        .as_array()
+        .ok_or_else(|| BuildError::invalid_keyword(keyword, "an array of schemas"))?;
+    array
+        .iter()
+        .map(|schema| ctx.compile_subschema(schema?))
+        .collect()
+}
+
+fn compile_schema_map<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    keyword: &str,
+    value: &'a J,
+) -> BuildResult<Vec<(Box<str>, Subschema)>> {
+    let object = value
+        .as_object()
+        .ok_or_else(|| BuildError::invalid_keyword(keyword, "an object"))?;
+    object
+        .iter()
+        .map(|(key, schema)| Ok((key?.as_ref().into(), ctx.compile_subschema(schema)?)))
+        .collect()
+}
+
+fn compile_patterns<J: Json>(
+    ctx: &Context<'_, '_, J>,
+    object: &J::Object,
+) -> BuildResult<Vec<CompiledRegex>> {
+    let Some(patterns) = object.get("patternProperties").and_then(Json::as_object) else {
+        return Ok(Vec::new());
+    };
+    patterns
+        .iter()
+        .map(|(pattern, _)| compile_regex(ctx, pattern?.as_ref()))
+        .collect()
+}
+
+/// Collect annotations from each schema of an in-place applicator, e.g. `allOf`.
+pub(crate) fn annotate_each<J: Json>(
+    graph: &SchemaGraph<J>,
+    schemas: &[Subschema],
+    instance: &J,
+    location: &JsonPointerNode,
+    path: &JsonPointerNode,
+    annotations: &mut Vec<Annotation>,
+) {
+    for (idx, schema) in schemas.iter().enumerate() {
+        annotate(
+            graph,
+            *schema,
+            instance,
+            location,
+            &path.push(idx),
+            annotations,
+        );
+    }
+}
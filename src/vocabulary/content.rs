@@ -0,0 +1,472 @@
+use std::{borrow::Cow, sync::Arc};
+
+use jpointer::JsonPointerNode;
+use jsonlike::prelude::*;
+
+use crate::{
+    compiler::Context,
+    content::{self, ContentEncoding, ContentMediaType},
+    error::ValidationErrorKind,
+    snapshot::{Reader, Writer},
+    value::Value,
+    vocabulary::{self, Annotation, SchemaGraph, Subschema},
+    BuildError, BuildResult, Draft, ValidationError,
+};
+
+/// The `contentEncoding`, `contentMediaType` and `contentSchema` keywords.
+///
+/// Compiled once per schema, as the media type is parsed from the decoded content and only then
+/// validated against `contentSchema`. Unless `assert` is set, they only produce annotations.
+#[derive(Clone)]
+pub(crate) struct Content<J: Json> {
+    encoding: Option<Encoding>,
+    media_type: Option<MediaType<J>>,
+    schema: Option<(Subschema, Value)>,
+    assert: bool,
+}
+
+#[derive(Clone)]
+struct Encoding {
+    name: Box<str>,
+    /// `None` for unknown encodings.
+    decoder: Option<Arc<dyn ContentEncoding>>,
+    /// Whether the decoder is registered via the builder.
+    custom: bool,
+}
+
+#[derive(Clone)]
+struct MediaType<J: Json> {
+    name: Box<str>,
+    /// `None` for unknown media types.
+    parser: Option<Arc<dyn ContentMediaType<J>>>,
+    /// Whether the parser is registered via the builder.
+    custom: bool,
+}
+
+impl<J: Json> core::fmt::Debug for Content<J> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Content")
+            .field(
+                "encoding",
+                &self.encoding.as_ref().map(|encoding| &encoding.name),
+            )
+            .field(
+                "media_type",
+                &self.media_type.as_ref().map(|media_type| &media_type.name),
+            )
+            .field("assert", &self.assert)
+            .finish()
+    }
+}
+
+impl<J: Json> Content<J> {
+    pub(crate) fn compile<'a>(
+        ctx: &mut Context<'a, '_, J>,
+        parent: &'a J::Object,
+    ) -> BuildResult<Content<J>> {
+        let encoding = match parent.get("contentEncoding") {
+            Some(value) => {
+                let name = value
+                    .as_string()
+                    .ok_or_else(|| BuildError::invalid_keyword("contentEncoding", "a string"))?
+                    .as_ref();
+                // Decoders registered via the builder override the built-in ones
+                let key = name.to_ascii_lowercase();
+                let factory = ctx.builder.content_encodings.get(&key);
+                let decoder = match factory {
+                    Some(factory) => Some(Arc::from(factory.init(value)?)),
+                    None => content::builtin_encoding(&key),
+                };
+                Some(Encoding {
+                    name: name.into(),
+                    decoder,
+                    custom: factory.is_some(),
+                })
+            }
+            None => None,
+        };
+        let media_type = match parent.get("contentMediaType") {
+            Some(value) => {
+                let name = value
+                    .as_string()
+                    .ok_or_else(|| BuildError::invalid_keyword("contentMediaType", "a string"))?
+                    .as_ref();
+                let essence = content::essence(name);
+                let factory = ctx.builder.content_media_types.get(&essence);
+                let parser = match factory {
+                    Some(factory) => Some(Arc::from(factory.init(value)?)),
+                    None => content::builtin_media_type(&essence),
+                };
+                Some(MediaType {
+                    name: name.into(),
+                    parser,
+                    custom: factory.is_some(),
+                })
+            }
+            None => None,
+        };
+        // `contentSchema` is ignored without `contentMediaType`
+        let schema = match parent.get("contentSchema") {
+            Some(value) if media_type.is_some() && ctx.draft >= Draft::Draft201909 => {
+                Some((ctx.compile_content_schema(value)?, Value::from_json(value)))
+            }
+            _ => None,
+        };
+        // Draft 7 allows asserting these keywords, later drafts only annotate them
+        let assert = ctx
+            .builder
+            .validate_content
+            .unwrap_or(ctx.draft <= Draft::Draft07);
+        Ok(Content {
+            encoding,
+            media_type,
+            schema,
+            assert,
+        })
+    }
+    /// Only built-in decoders & parsers are stored, they are looked up again when loading.
+    pub(crate) fn encode(&self, writer: &mut Writer) -> BuildResult<()> {
+        match &self.encoding {
+            Some(encoding) if encoding.custom => {
+                return Err(BuildError::not_serializable(encoding.name.as_ref()))
+            }
+            Some(encoding) => {
+                writer.bool(true);
+                writer.str(&encoding.name);
+            }
+            None => writer.bool(false),
+        }
+        match &self.media_type {
+            Some(media_type) if media_type.custom => {
+                return Err(BuildError::not_serializable(media_type.name.as_ref()))
+            }
+            Some(media_type) => {
+                writer.bool(true);
+                writer.str(&media_type.name);
+            }
+            None => writer.bool(false),
+        }
+        match &self.schema {
+            Some((schema, value)) => {
+                writer.bool(true);
+                writer.schema(*schema);
+                value.encode(writer);
+            }
+            None => writer.bool(false),
+        }
+        writer.bool(self.assert);
+        Ok(())
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<Content<J>> {
+        let encoding = if reader.bool()? {
+            let name = reader.str()?;
+            Some(Encoding {
+                name: name.into(),
+                decoder: content::builtin_encoding(&name.to_ascii_lowercase()),
+                custom: false,
+            })
+        } else {
+            None
+        };
+        let media_type = if reader.bool()? {
+            let name = reader.str()?;
+            Some(MediaType {
+                name: name.into(),
+                parser: content::builtin_media_type(&content::essence(name)),
+                custom: false,
+            })
+        } else {
+            None
+        };
+        let schema = if reader.bool()? {
+            Some((reader.schema()?, Value::decode(reader)?))
+        } else {
+            None
+        };
+        Ok(Content {
+            encoding,
+            media_type,
+            schema,
+            assert: reader.bool()?,
+        })
+    }
+    /// The keyword owning the evaluation path of errors produced without a more specific one.
+    pub(crate) fn name(&self) -> &'static str {
+        if self.media_type.is_some() {
+            "contentMediaType"
+        } else {
+            "contentEncoding"
+        }
+    }
+    /// Decode & parse the content, `Ok(None)` if there is nothing to check it against.
+    fn parse(&self, string: &str) -> Result<Option<J>, ValidationErrorKind> {
+        let content = match &self.encoding {
+            Some(Encoding {
+                name,
+                decoder: Some(decoder),
+                ..
+            }) => Cow::Owned(decoder.decode(string).ok_or_else(|| {
+                ValidationErrorKind::ContentEncoding {
+                    encoding: name.to_string(),
+                }
+            })?),
+            // The media type can not be checked without decoding first
+            Some(Encoding { decoder: None, .. }) => return Ok(None),
+            None => Cow::Borrowed(string.as_bytes()),
+        };
+        match &self.media_type {
+            Some(MediaType {
+                name,
+                parser: Some(parser),
+                ..
+            }) => parser.parse(&content).map(Some).ok_or_else(|| {
+                ValidationErrorKind::ContentMediaType {
+                    media_type: name.to_string(),
+                }
+            }),
+            _ => Ok(None),
+        }
+    }
+    pub(crate) fn is_valid(&self, graph: &SchemaGraph<J>, instance: &J) -> bool {
+        if !self.assert {
+            return true;
+        }
+        let Some(string) = instance.as_string() else {
+            return true;
+        };
+        match self.parse(string.as_ref()) {
+            Ok(Some(document)) => self
+                .schema
+                .as_ref()
+                .is_none_or(|(schema, _)| vocabulary::is_valid(graph, *schema, &document)),
+            Ok(None) => true,
+            Err(_) => false,
+        }
+    }
+    pub(crate) fn validate(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if !self.assert {
+            return;
+        }
+        let Some(string) = instance.as_string() else {
+            return;
+        };
+        match self.parse(string.as_ref()) {
+            Ok(Some(document)) => {
+                if let Some((schema, _)) = &self.schema {
+                    // Errors refer to the location of the encoded string
+                    let path = path.push("contentSchema");
+                    vocabulary::validate(graph, *schema, &document, location, &path, errors);
+                }
+            }
+            Ok(None) => {}
+            Err(kind) => {
+                let keyword = match kind {
+                    ValidationErrorKind::ContentEncoding { .. } => "contentEncoding",
+                    _ => "contentMediaType",
+                };
+                errors.push(ValidationError::new(kind, location, &path.push(keyword)));
+            }
+        }
+    }
+    pub(crate) fn annotate(
+        &self,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        annotations: &mut Vec<Annotation>,
+    ) {
+        if let Some(encoding) = &self.encoding {
+            annotations.push(Annotation::new(
+                "contentEncoding",
+                Value::String(encoding.name.clone()),
+                location,
+                &path.push("contentEncoding"),
+            ));
+        }
+        if let Some(media_type) = &self.media_type {
+            annotations.push(Annotation::new(
+                "contentMediaType",
+                Value::String(media_type.name.clone()),
+                location,
+                &path.push("contentMediaType"),
+            ));
+        }
+        if let Some((_, value)) = &self.schema {
+            annotations.push(Annotation::new(
+                "contentSchema",
+                value.clone(),
+                location,
+                &path.push("contentSchema"),
+            ));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use crate::{
+        blocking::ValidatorBuilder, BoxedContentEncoding, BoxedContentMediaType, BuildResult,
+        ContentEncoding, ContentMediaType, Draft,
+    };
+    use serde_json::{json, Map, Value};
+    use test_case::test_case;
+
+    /// Reversed UTF-8 strings.
+    struct Reversed;
+
+    impl ContentEncoding for Reversed {
+        fn decode(&self, value: &str) -> Option<Vec<u8>> {
+            Some(value.chars().rev().collect::<String>().into_bytes())
+        }
+    }
+
+    /// Lines of `key=value` pairs.
+    struct KeyValue;
+
+    impl ContentMediaType<Value> for KeyValue {
+        fn parse(&self, content: &[u8]) -> Option<Value> {
+            let mut object = Map::new();
+            for line in core::str::from_utf8(content).ok()?.lines() {
+                let (key, value) = line.split_once('=')?;
+                object.insert(key.to_owned(), Value::String(value.to_owned()));
+            }
+            Some(Value::Object(object))
+        }
+    }
+
+    fn reversed_factory(_: &Value) -> BuildResult<BoxedContentEncoding> {
+        Ok(Box::new(Reversed))
+    }
+
+    fn key_value_factory(_: &Value) -> BuildResult<BoxedContentMediaType<Value>> {
+        Ok(Box::new(KeyValue))
+    }
+
+    #[test_case(Draft::Draft06, None, true)]
+    #[test_case(Draft::Draft07, None, false)]
+    #[test_case(Draft::Draft201909, None, true)]
+    #[test_case(Draft::Draft202012, None, true)]
+    #[test_case(Draft::Draft07, Some(false), true)]
+    #[test_case(Draft::Draft202012, Some(true), false)]
+    fn test_assertion(draft: Draft, validate_content: Option<bool>, expected: bool) {
+        let schema = json!({"contentMediaType": "application/json"});
+        let mut builder = ValidatorBuilder::default();
+        builder.draft(draft);
+        if let Some(validate_content) = validate_content {
+            builder.validate_content(validate_content);
+        }
+        let validator = builder.build(&schema).expect("Invalid schema");
+        assert!(validator.is_valid(&json!("{\"a\": 1}")));
+        assert_eq!(validator.is_valid(&json!("{")), expected);
+    }
+
+    #[test_case(json!({"contentEncoding": "base64"}), json!("eyJmb28iOiAiYmFyIn0K"), true)]
+    #[test_case(json!({"contentEncoding": "base64"}), json!("eyJmb28i%iYmFyIn0K"), false)]
+    #[test_case(json!({"contentEncoding": "BASE16"}), json!("7B7D"), true)]
+    #[test_case(json!({"contentEncoding": "unknown", "contentMediaType": "application/json"}), json!("{"), true)]
+    #[test_case(json!({"contentEncoding": "base64", "contentMediaType": "application/json"}), json!("eyJmb28iOiAiYmFyIn0K"), true)]
+    #[test_case(json!({"contentEncoding": "base64", "contentMediaType": "application/json"}), json!("ezp9Cg=="), false)]
+    #[test_case(json!({"contentMediaType": "application/json; charset=utf-8"}), json!("{"), false)]
+    #[test_case(json!({"contentMediaType": "application/schema+json"}), json!("{"), false)]
+    #[test_case(json!({"contentMediaType": "text/html"}), json!("{"), true)]
+    #[test_case(json!({"contentMediaType": "application/json"}), json!(100), true)]
+    fn test_decoding(schema: serde_json::Value, instance: serde_json::Value, expected: bool) {
+        let validator = ValidatorBuilder::default()
+            .draft(Draft::Draft07)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert_eq!(validator.is_valid(&instance), expected);
+    }
+
+    #[test]
+    fn test_content_schema() {
+        let schema = json!({
+            "contentEncoding": "base64",
+            "contentMediaType": "application/json",
+            "contentSchema": {"properties": {"foo": {"type": "string"}}}
+        });
+        let validator = ValidatorBuilder::default()
+            .validate_content(true)
+            .build(&schema)
+            .expect("Invalid schema");
+        // {"foo": "bar"}
+        assert!(validator.is_valid(&json!("eyJmb28iOiAiYmFyIn0K")));
+        // {"foo": 1}
+        let instance = json!("eyJmb28iOiAxfQo=");
+        let errors: Vec<_> = validator.iter_errors(&instance).collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].evaluation_path().to_string(),
+            "/contentSchema/properties/foo/type"
+        );
+        let errors: Vec<_> = validator.iter_errors(&json!("%")).collect();
+        assert_eq!(errors[0].to_string(), "Value is not encoded as 'base64'");
+        assert_eq!(errors[0].evaluation_path().to_string(), "/contentEncoding");
+    }
+
+    #[test]
+    fn test_annotation() {
+        let schema = json!({
+            "contentEncoding": "base64",
+            "contentMediaType": "application/json",
+            "contentSchema": {"type": "object"}
+        });
+        let validator = ValidatorBuilder::default()
+            .build(&schema)
+            .expect("Invalid schema");
+        let output = validator.evaluate(&json!("%")).list();
+        assert!(output.valid);
+        let annotation = |keyword: &str| {
+            output
+                .nested
+                .iter()
+                .find_map(|unit| unit.annotations.as_ref()?.get(keyword).cloned())
+        };
+        assert_eq!(annotation("contentEncoding"), Some(json!("base64")));
+        assert_eq!(
+            annotation("contentMediaType"),
+            Some(json!("application/json"))
+        );
+        assert_eq!(annotation("contentSchema"), Some(json!({"type": "object"})));
+    }
+
+    #[test_case("1=a", true)]
+    #[test_case("2=a", false)]
+    #[test_case("1b", false)]
+    fn test_custom_decoders(instance: &str, expected: bool) {
+        let schema = json!({
+            "contentEncoding": "Reversed",
+            "contentMediaType": "text/x-key-value; charset=utf-8",
+            "contentSchema": {"properties": {"a": {"const": "1"}}}
+        });
+        let validator = ValidatorBuilder::default()
+            .content_encoding("reversed", reversed_factory)
+            .content_media_type("text/x-key-value", key_value_factory)
+            .validate_content(true)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert_eq!(validator.is_valid(&json!(instance)), expected);
+    }
+
+    #[test]
+    fn test_override_builtin() {
+        let schema = json!({"contentEncoding": "base64", "contentMediaType": "application/json"});
+        let validator = ValidatorBuilder::default()
+            .draft(Draft::Draft07)
+            .content_encoding("base64", reversed_factory)
+            .content_media_type(
+                "application/json",
+                |_: &Value| -> BuildResult<BoxedContentMediaType<Value>> { Ok(Box::new(KeyValue)) },
+            )
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("b=a")));
+        assert!(!validator.is_valid(&json!("{}")));
+    }
+}
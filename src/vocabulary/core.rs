@@ -0,0 +1,139 @@
+use std::{collections::HashSet, sync::Arc};
+
+use jpointer::JsonPointerNode;
+use jsonlike::Json;
+
+use crate::{
+    graph::SchemaId,
+    snapshot::{Reader, Writer},
+    vocabulary::{
+        annotate, decode_graph, encode_graph, evaluated_items, evaluated_properties, is_valid,
+        validate, Annotation, SchemaGraph, Subschema,
+    },
+    BuildResult, ValidationError,
+};
+
+/// `$ref` and its dynamic counterparts, pointing to a compiled schema, possibly its own parent.
+#[derive(Debug, Clone)]
+pub(crate) struct Ref {
+    pub(crate) keyword: &'static str,
+    pub(crate) schema: Subschema,
+}
+
+impl Ref {
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.str(self.keyword);
+        writer.schema(self.schema);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<Ref> {
+        Ok(Ref {
+            keyword: reader.name(&["$ref", "$dynamicRef", "$recursiveRef"])?,
+            schema: reader.schema()?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, graph: &SchemaGraph<J>, instance: &J) -> bool {
+        is_valid(graph, self.schema, instance)
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        validate(graph, self.schema, instance, location, path, errors);
+    }
+    pub(crate) fn evaluated_properties<'i, J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &'i J,
+        evaluated: &mut HashSet<&'i str>,
+    ) {
+        evaluated_properties(graph, self.schema, instance, evaluated);
+    }
+    pub(crate) fn evaluated_items<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        evaluated: &mut [bool],
+    ) {
+        evaluated_items(graph, self.schema, instance, evaluated);
+    }
+    pub(crate) fn annotate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        annotations: &mut Vec<Annotation>,
+    ) {
+        let schema = self.schema;
+        annotate(graph, schema, instance, location, path, annotations);
+    }
+}
+
+/// A reference target compiled once and shared with other validators via a compilation cache.
+///
+/// It is the only keyword of its schema and does not appear in evaluation paths.
+#[derive(Debug, Clone)]
+pub(crate) struct Shared<J: Json> {
+    pub(crate) graph: Arc<SchemaGraph<J>>,
+}
+
+impl<J: Json> Shared<J> {
+    /// The shared graph is written inline, so a loaded snapshot owns a copy of it.
+    pub(crate) fn encode(&self, writer: &mut Writer) -> BuildResult<()> {
+        encode_graph(&self.graph, writer)
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<Shared<J>> {
+        Ok(Shared {
+            graph: Arc::new(decode_graph(reader)?),
+        })
+    }
+    pub(crate) fn is_valid(&self, instance: &J) -> bool {
+        is_valid(&self.graph, SchemaId::ROOT, instance)
+    }
+    pub(crate) fn validate(
+        &self,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        validate(
+            &self.graph,
+            SchemaId::ROOT,
+            instance,
+            location,
+            path,
+            errors,
+        );
+    }
+    pub(crate) fn evaluated_properties<'i>(
+        &self,
+        instance: &'i J,
+        evaluated: &mut HashSet<&'i str>,
+    ) {
+        evaluated_properties(&self.graph, SchemaId::ROOT, instance, evaluated);
+    }
+    pub(crate) fn evaluated_items(&self, instance: &J, evaluated: &mut [bool]) {
+        evaluated_items(&self.graph, SchemaId::ROOT, instance, evaluated);
+    }
+    pub(crate) fn annotate(
+        &self,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        annotations: &mut Vec<Annotation>,
+    ) {
+        annotate(
+            &self.graph,
+            SchemaId::ROOT,
+            instance,
+            location,
+            path,
+            annotations,
+        );
+    }
+}
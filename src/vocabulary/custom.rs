@@ -0,0 +1,344 @@
+use std::{fmt, marker::PhantomData};
+
+use jpointer::{JsonPointer, JsonPointerNode};
+use jsonlike::Json;
+use url::Url;
+
+use crate::{
+    error::ValidationErrorKind,
+    vocabulary::{is_valid, validate, SchemaGraph, Subschema},
+    BoxFuture, BoxedAsyncKeyword, BoxedKeyword, BuildResult, Draft, ErrorIterator, ValidationError,
+};
+
+/// A user-defined keyword.
+pub trait Keyword<J: Json>: Send + Sync + fmt::Debug {
+    /// Check whether the instance is valid.
+    ///
+    /// The evaluation path is not tracked when only validity is checked, so errors created via
+    /// `ctx` in this method are located at the root of the schema. Errors returned by
+    /// [`Keyword::iter_errors`] have the actual path.
+    fn is_valid(&self, ctx: &KeywordContext<'_, J>, instance: &J) -> bool;
+    /// Iterate over errors of the instance located at `location`.
+    ///
+    /// Reports a single generic error for invalid instances by default.
+    fn iter_errors<'k>(
+        &'k self,
+        ctx: &'k KeywordContext<'k, J>,
+        instance: &'k J,
+        location: &JsonPointerNode,
+    ) -> ErrorIterator<'k> {
+        if self.is_valid(ctx, instance) {
+            Box::new(std::iter::empty())
+        } else {
+            Box::new(std::iter::once(ctx.invalid(location)))
+        }
+    }
+    /// The annotation attached to a valid instance, reported in `OutputUnit::annotations`.
+    fn annotate(&self, _ctx: &KeywordContext<'_, J>, _instance: &J) -> Option<J> {
+        None
+    }
+}
+
+/// A user-defined keyword that is checked asynchronously, e.g. with a lookup in a database.
+///
+/// It is only checked by the `*_async` methods of [`crate::Validator`], other methods fail with
+/// [`crate::ValidationErrorKind::RequiresAsync`] if they reach it.
+pub trait AsyncKeyword<J: Json>: Send + Sync + fmt::Debug {
+    fn is_valid<'i>(&'i self, instance: &'i J) -> BoxFuture<'i, bool>;
+}
+
+/// A subschema compiled via [`CompileContext`].
+#[derive(Debug, Clone, Copy)]
+pub struct SchemaRef(Subschema);
+
+/// Compiler operations available to custom keywords and formats.
+pub(crate) trait Compiler<'a, J: Json> {
+    fn draft(&self) -> Draft;
+    fn base_uri(&self) -> &Url;
+    fn locate(&self, parent: &J::Object, keyword: &str) -> JsonPointer;
+    fn compile_subschema(&mut self, schema: &'a J) -> BuildResult<Subschema>;
+    fn compile_entry(&mut self, reference: &str) -> BuildResult<Subschema>;
+}
+
+/// State of the compiler passed to keyword and format factories.
+pub struct CompileContext<'a, 'c, J: Json> {
+    compiler: &'c mut dyn Compiler<'a, J>,
+    parent: &'a J::Object,
+    keyword: &'c str,
+}
+
+impl<'a, 'c, J: Json> CompileContext<'a, 'c, J> {
+    pub(crate) fn new(
+        compiler: &'c mut dyn Compiler<'a, J>,
+        parent: &'a J::Object,
+        keyword: &'c str,
+    ) -> Self {
+        CompileContext {
+            compiler,
+            parent,
+            keyword,
+        }
+    }
+    /// The draft the schema is compiled with.
+    pub fn draft(&self) -> Draft {
+        self.compiler.draft()
+    }
+    /// The URI that references within the schema are resolved against.
+    pub fn base_uri(&self) -> &str {
+        self.compiler.base_uri().as_str()
+    }
+    /// The schema object containing the keyword, e.g. to read its siblings.
+    pub fn parent(&self) -> &'a J::Object {
+        self.parent
+    }
+    /// The name of the compiled keyword.
+    pub fn keyword(&self) -> &str {
+        self.keyword
+    }
+    /// Location of the keyword within the root schema.
+    ///
+    /// The location is looked up on each call, so it is better to call it only once.
+    pub fn location(&self) -> JsonPointer {
+        self.compiler.locate(self.parent, self.keyword)
+    }
+    /// Compile a subschema, for example a value of the keyword.
+    pub fn compile(&mut self, schema: &'a J) -> BuildResult<SchemaRef> {
+        Ok(SchemaRef(self.compiler.compile_subschema(schema)?))
+    }
+    /// Resolve a reference against the current base URI and compile its target.
+    pub fn resolve(&mut self, reference: &str) -> BuildResult<SchemaRef> {
+        Ok(SchemaRef(self.compiler.compile_entry(reference)?))
+    }
+}
+
+/// State of the validation passed to [`Keyword`] methods.
+///
+/// It carries the evaluation path of the keyword for the errors it creates, except in
+/// [`Keyword::is_valid`].
+pub struct KeywordContext<'k, J: Json> {
+    graph: &'k SchemaGraph<J>,
+    keyword: &'k str,
+    path: &'k JsonPointerNode<'k>,
+}
+
+impl<'k, J: Json> KeywordContext<'k, J> {
+    pub(crate) fn new(
+        graph: &'k SchemaGraph<J>,
+        keyword: &'k str,
+        path: &'k JsonPointerNode<'k>,
+    ) -> Self {
+        KeywordContext {
+            graph,
+            keyword,
+            path,
+        }
+    }
+    /// Check whether the instance is valid against a compiled subschema.
+    pub fn is_valid(&self, schema: SchemaRef, instance: &J) -> bool {
+        is_valid(self.graph, schema.0, instance)
+    }
+    /// Collect errors of the instance against a compiled subschema.
+    pub fn iter_errors(
+        &self,
+        schema: SchemaRef,
+        instance: &J,
+        location: &JsonPointerNode,
+    ) -> std::vec::IntoIter<ValidationError> {
+        let mut errors = Vec::new();
+        validate(
+            self.graph,
+            schema.0,
+            instance,
+            location,
+            self.path,
+            &mut errors,
+        );
+        errors.into_iter()
+    }
+    /// Create an error with a custom message at the given instance location.
+    pub fn error(&self, location: &JsonPointerNode, message: impl Into<String>) -> ValidationError {
+        let kind = ValidationErrorKind::CustomMessage {
+            keyword: self.keyword.to_string(),
+            message: message.into(),
+        };
+        ValidationError::new(kind, location, self.path)
+    }
+    pub(crate) fn invalid(&self, location: &JsonPointerNode) -> ValidationError {
+        let kind = ValidationErrorKind::Custom {
+            keyword: self.keyword.to_string(),
+        };
+        ValidationError::new(kind, location, self.path)
+    }
+}
+
+pub(crate) mod sealed {
+    pub trait Sealed<J, M> {}
+
+    /// Factories that only take the keyword value.
+    pub struct Schema;
+    /// Factories that also take the compilation context.
+    pub struct ContextAndSchema;
+}
+
+/// Creates a [`Keyword`] from its value in the schema.
+///
+/// Implemented for closures taking either the value, or a [`CompileContext`] and the value.
+pub trait KeywordFactory<'a, J: Json, M>: Send + Sync + sealed::Sealed<J, M> + 'a {
+    fn init(
+        &self,
+        ctx: &mut CompileContext<'a, '_, J>,
+        schema: &'a J,
+    ) -> BuildResult<BoxedKeyword<J>>;
+}
+
+impl<'a, F, J: Json + 'a> sealed::Sealed<J, sealed::Schema> for F where
+    F: Fn(&'a J) -> BuildResult<BoxedKeyword<J>> + Send + Sync + 'a
+{
+}
+
+impl<'a, F, J: Json + 'a> KeywordFactory<'a, J, sealed::Schema> for F
+where
+    F: Fn(&'a J) -> BuildResult<BoxedKeyword<J>> + Send + Sync + 'a,
+{
+    fn init(
+        &self,
+        _ctx: &mut CompileContext<'a, '_, J>,
+        schema: &'a J,
+    ) -> BuildResult<BoxedKeyword<J>> {
+        self(schema)
+    }
+}
+
+impl<'a, F, J: Json + 'a> sealed::Sealed<J, sealed::ContextAndSchema> for F where
+    F: Fn(&mut CompileContext<'a, '_, J>, &'a J) -> BuildResult<BoxedKeyword<J>> + Send + Sync + 'a
+{
+}
+
+impl<'a, F, J: Json + 'a> KeywordFactory<'a, J, sealed::ContextAndSchema> for F
+where
+    F: Fn(&mut CompileContext<'a, '_, J>, &'a J) -> BuildResult<BoxedKeyword<J>> + Send + Sync + 'a,
+{
+    fn init(
+        &self,
+        ctx: &mut CompileContext<'a, '_, J>,
+        schema: &'a J,
+    ) -> BuildResult<BoxedKeyword<J>> {
+        self(ctx, schema)
+    }
+}
+
+/// A [`KeywordFactory`] with its marker type erased, so factories of both kinds can be stored
+/// together.
+pub(crate) trait ErasedKeywordFactory<'a, J: Json>: Send + Sync + 'a {
+    fn init(
+        &self,
+        ctx: &mut CompileContext<'a, '_, J>,
+        schema: &'a J,
+    ) -> BuildResult<BoxedKeyword<J>>;
+}
+
+pub(crate) struct Erased<F, M>(pub(crate) F, PhantomData<fn() -> M>);
+
+impl<F, M> Erased<F, M> {
+    pub(crate) fn new(factory: F) -> Self {
+        Erased(factory, PhantomData)
+    }
+}
+
+impl<'a, F, M, J> ErasedKeywordFactory<'a, J> for Erased<F, M>
+where
+    F: KeywordFactory<'a, J, M>,
+    M: 'a,
+    J: Json,
+{
+    fn init(
+        &self,
+        ctx: &mut CompileContext<'a, '_, J>,
+        schema: &'a J,
+    ) -> BuildResult<BoxedKeyword<J>> {
+        self.0.init(ctx, schema)
+    }
+}
+
+/// Creates an [`AsyncKeyword`] from its value in the schema.
+///
+/// Implemented for closures taking either the value, or a [`CompileContext`] and the value.
+pub trait AsyncKeywordFactory<'a, J: Json, M>:
+    Send + Sync + async_sealed::Sealed<J, M> + 'a
+{
+    fn init(
+        &self,
+        ctx: &mut CompileContext<'a, '_, J>,
+        schema: &'a J,
+    ) -> BuildResult<BoxedAsyncKeyword<J>>;
+}
+
+mod async_sealed {
+    pub trait Sealed<J, M> {}
+}
+
+impl<'a, F, J: Json + 'a> async_sealed::Sealed<J, sealed::Schema> for F where
+    F: Fn(&'a J) -> BuildResult<BoxedAsyncKeyword<J>> + Send + Sync + 'a
+{
+}
+
+impl<'a, F, J: Json + 'a> AsyncKeywordFactory<'a, J, sealed::Schema> for F
+where
+    F: Fn(&'a J) -> BuildResult<BoxedAsyncKeyword<J>> + Send + Sync + 'a,
+{
+    fn init(
+        &self,
+        _ctx: &mut CompileContext<'a, '_, J>,
+        schema: &'a J,
+    ) -> BuildResult<BoxedAsyncKeyword<J>> {
+        self(schema)
+    }
+}
+
+impl<'a, F, J: Json + 'a> async_sealed::Sealed<J, sealed::ContextAndSchema> for F where
+    F: Fn(&mut CompileContext<'a, '_, J>, &'a J) -> BuildResult<BoxedAsyncKeyword<J>>
+        + Send
+        + Sync
+        + 'a
+{
+}
+
+impl<'a, F, J: Json + 'a> AsyncKeywordFactory<'a, J, sealed::ContextAndSchema> for F
+where
+    F: Fn(&mut CompileContext<'a, '_, J>, &'a J) -> BuildResult<BoxedAsyncKeyword<J>>
+        + Send
+        + Sync
+        + 'a,
+{
+    fn init(
+        &self,
+        ctx: &mut CompileContext<'a, '_, J>,
+        schema: &'a J,
+    ) -> BuildResult<BoxedAsyncKeyword<J>> {
+        self(ctx, schema)
+    }
+}
+
+/// An [`AsyncKeywordFactory`] with its marker type erased.
+pub(crate) trait ErasedAsyncKeywordFactory<'a, J: Json>: Send + Sync + 'a {
+    fn init(
+        &self,
+        ctx: &mut CompileContext<'a, '_, J>,
+        schema: &'a J,
+    ) -> BuildResult<BoxedAsyncKeyword<J>>;
+}
+
+impl<'a, F, M, J> ErasedAsyncKeywordFactory<'a, J> for Erased<F, M>
+where
+    F: AsyncKeywordFactory<'a, J, M>,
+    M: 'a,
+    J: Json,
+{
+    fn init(
+        &self,
+        ctx: &mut CompileContext<'a, '_, J>,
+        schema: &'a J,
+    ) -> BuildResult<BoxedAsyncKeyword<J>> {
+        self.0.init(ctx, schema)
+    }
+}
@@ -0,0 +1,341 @@
+use std::sync::Arc;
+
+use jpointer::JsonPointerNode;
+use jsonlike::prelude::*;
+
+use crate::{
+    compiler::Context,
+    error::ValidationErrorKind,
+    format::{self, UnknownFormatPolicy},
+    snapshot::{Reader, Writer},
+    validation::deferred::{self, Deferred},
+    value::Value,
+    vocabulary::{Annotation, CompileContext, Vocabulary},
+    BuildError, BuildResult, Draft, ValidationError,
+};
+
+/// The `format` keyword.
+///
+/// Unless `assert` is set, it only produces an annotation with the format name.
+#[derive(Clone)]
+pub(crate) struct Format {
+    name: Box<str>,
+    /// `None` for unknown and asynchronous formats.
+    format: Option<Arc<dyn crate::Format>>,
+    /// Index of an asynchronous format in the validator.
+    deferred: Option<usize>,
+    /// Whether the format is registered via the builder.
+    custom: bool,
+    assert: bool,
+    /// Whether an unknown format should be reported via an annotation.
+    warn: bool,
+}
+
+impl core::fmt::Debug for Format {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Format")
+            .field("name", &self.name)
+            .field("assert", &self.assert)
+            .finish()
+    }
+}
+
+impl Format {
+    pub(crate) fn compile<'a, J: Json>(
+        ctx: &mut Context<'a, '_, J>,
+        parent: &'a J::Object,
+        value: &'a J,
+    ) -> BuildResult<Format> {
+        let name = value
+            .as_string()
+            .ok_or_else(|| BuildError::invalid_keyword("format", "a string"))?
+            .as_ref();
+        // Formats registered via the builder override the built-in ones
+        let mut deferred = None;
+        let custom =
+            ctx.builder.async_formats.contains_key(name) || ctx.builder.formats.contains_key(name);
+        let format = if let Some(factory) = ctx.builder.async_formats.get(name) {
+            let mut compile_ctx = CompileContext::new(ctx, parent, "format");
+            let format = Arc::from(factory.init(&mut compile_ctx, value)?);
+            deferred = Some(ctx.defer("format", Deferred::Format(format)));
+            None
+        } else if let Some(factory) = ctx.builder.formats.get(name) {
+            let mut compile_ctx = CompileContext::new(ctx, parent, "format");
+            Some(Arc::from(factory.init(&mut compile_ctx, value)?))
+        } else {
+            format::builtin(name, ctx.draft, &ctx.builder.regex_engine)
+        };
+        let mut warn = false;
+        if format.is_none() && deferred.is_none() {
+            match ctx.builder.unknown_formats {
+                UnknownFormatPolicy::Ignore => {}
+                UnknownFormatPolicy::Warn => warn = true,
+                UnknownFormatPolicy::Error => return Err(BuildError::unknown_format(name)),
+            }
+        }
+        // The format-assertion vocabulary always requires assertion, otherwise the builder option
+        // takes precedence over the draft default
+        let assert = ctx.vocabularies.contains(Vocabulary::FormatAssertion)
+            || ctx
+                .builder
+                .validate_formats
+                .unwrap_or(ctx.draft <= Draft::Draft07);
+        Ok(Format {
+            name: name.into(),
+            format,
+            deferred,
+            custom,
+            assert,
+            warn,
+        })
+    }
+    /// Only built-in formats are stored, they are looked up again when loading.
+    pub(crate) fn encode(&self, writer: &mut Writer) -> BuildResult<()> {
+        if self.custom {
+            return Err(BuildError::not_serializable(self.name.as_ref()));
+        }
+        writer.str(&self.name);
+        writer.bool(self.assert);
+        writer.bool(self.warn);
+        Ok(())
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<Format> {
+        let name = reader.str()?;
+        Ok(Format {
+            name: name.into(),
+            format: format::builtin(name, reader.draft, reader.regex_engine),
+            deferred: None,
+            custom: false,
+            assert: reader.bool()?,
+            warn: reader.bool()?,
+        })
+    }
+    /// Whether the format is asserted via an asynchronous implementation.
+    pub(crate) fn is_deferred(&self) -> bool {
+        self.assert && self.deferred.is_some()
+    }
+    pub(crate) fn is_valid<J: Json>(&self, instance: &J) -> bool {
+        if !self.assert {
+            return true;
+        }
+        if let Some(index) = self.deferred {
+            return instance.as_string().is_none() || deferred::is_valid(index, instance);
+        }
+        let Some(format) = &self.format else {
+            return true;
+        };
+        instance
+            .as_string()
+            .is_none_or(|string| format.is_valid(string.as_ref()))
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if !self.is_valid(instance) {
+            let kind = ValidationErrorKind::Format {
+                format: self.name.to_string(),
+            };
+            errors.push(ValidationError::new(kind, location, path));
+        }
+    }
+    pub(crate) fn annotate(
+        &self,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        annotations: &mut Vec<Annotation>,
+    ) {
+        annotations.push(Annotation::new(
+            "format",
+            Value::String(self.name.clone()),
+            location,
+            path,
+        ));
+        if self.warn {
+            annotations.push(Annotation::new(
+                "warning",
+                Value::String(format!("Unknown format: '{}'", self.name).into()),
+                location,
+                path,
+            ));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use crate::{
+        blocking::ValidatorBuilder, BoxedFormat, BuildResult, CompileContext, Draft,
+        UnknownFormatPolicy,
+    };
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    struct Lowercase;
+
+    impl crate::Format for Lowercase {
+        fn is_valid(&self, value: &str) -> bool {
+            value.chars().all(char::is_lowercase)
+        }
+    }
+
+    fn lowercase_factory(_: &Value) -> BuildResult<BoxedFormat> {
+        Ok(Box::new(Lowercase))
+    }
+
+    #[test_case(Draft::Draft04, None, false)]
+    #[test_case(Draft::Draft07, None, false)]
+    #[test_case(Draft::Draft201909, None, true)]
+    #[test_case(Draft::Draft202012, None, true)]
+    #[test_case(Draft::Draft07, Some(false), true)]
+    #[test_case(Draft::Draft202012, Some(true), false)]
+    fn test_assertion(draft: Draft, validate_formats: Option<bool>, expected: bool) {
+        let schema = json!({"format": "lowercase"});
+        let mut builder = ValidatorBuilder::default();
+        builder.draft(draft).format("lowercase", lowercase_factory);
+        if let Some(validate_formats) = validate_formats {
+            builder.validate_formats(validate_formats);
+        }
+        let validator = builder.build(&schema).expect("Invalid schema");
+        assert!(validator.is_valid(&json!("abc")));
+        assert_eq!(validator.is_valid(&json!("ABC")), expected);
+    }
+
+    #[test]
+    fn test_format_assertion_vocabulary() {
+        let meta_schema = json!({
+            "$vocabulary": {
+                "https://json-schema.org/draft/2020-12/vocab/core": true,
+                "https://json-schema.org/draft/2020-12/vocab/format-assertion": true,
+            }
+        });
+        let schema = json!({"$schema": "https://example.com/meta", "format": "lowercase"});
+        let validator = ValidatorBuilder::default()
+            .meta_schema("https://example.com/meta", meta_schema)
+            .format("lowercase", lowercase_factory)
+            .validate_formats(false)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!("ABC")));
+    }
+
+    #[test]
+    fn test_annotation() {
+        let schema = json!({"properties": {"a": {"format": "lowercase"}}});
+        let validator = ValidatorBuilder::default()
+            .format("lowercase", lowercase_factory)
+            .build(&schema)
+            .expect("Invalid schema");
+        let output = validator.evaluate(&json!({"a": "ABC"})).list();
+        assert!(output.valid);
+        assert_eq!(output.nested.len(), 1);
+        let unit = &output.nested[0];
+        assert_eq!(unit.instance_location.to_string(), "/a");
+        assert_eq!(unit.evaluation_path.to_string(), "/properties/a/format");
+        assert_eq!(
+            unit.annotations.as_ref().and_then(|a| a.get("format")),
+            Some(&json!("lowercase"))
+        );
+    }
+
+    #[test_case(Draft::Draft04, "date", true)]
+    #[test_case(Draft::Draft07, "date", false)]
+    #[test_case(Draft::Draft07, "duration", true)]
+    #[test_case(Draft::Draft201909, "duration", false)]
+    fn test_builtin_formats(draft: Draft, format: &str, expected: bool) {
+        let schema = json!({"format": format});
+        let validator = ValidatorBuilder::default()
+            .draft(draft)
+            .validate_formats(true)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert_eq!(validator.is_valid(&json!("invalid")), expected);
+    }
+
+    #[test]
+    fn test_override_builtin_format() {
+        let schema = json!({"format": "date"});
+        let validator = ValidatorBuilder::default()
+            .format("date", lowercase_factory)
+            .validate_formats(true)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("invalid")));
+    }
+
+    #[test]
+    fn test_unknown_format_ignore() {
+        let schema = json!({"format": "unknown"});
+        let validator = ValidatorBuilder::default()
+            .draft(Draft::Draft07)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("a")));
+    }
+
+    #[test]
+    fn test_unknown_format_warn() {
+        let schema = json!({"format": "unknown"});
+        let validator = ValidatorBuilder::default()
+            .unknown_formats(UnknownFormatPolicy::Warn)
+            .build(&schema)
+            .expect("Invalid schema");
+        let output = validator.evaluate(&json!("a")).list();
+        let warning = output
+            .nested
+            .iter()
+            .find_map(|unit| unit.annotations.as_ref()?.get("warning"));
+        assert_eq!(warning, Some(&json!("Unknown format: 'unknown'")));
+    }
+
+    #[test]
+    fn test_unknown_format_error() {
+        let schema = json!({"format": "unknown"});
+        let error = ValidatorBuilder::default()
+            .unknown_formats(UnknownFormatPolicy::Error)
+            .build(&schema)
+            .expect_err("Should fail");
+        assert_eq!(error.to_string(), "Unknown format: 'unknown'");
+    }
+
+    struct Prefixed(String);
+
+    impl crate::Format for Prefixed {
+        fn is_valid(&self, value: &str) -> bool {
+            value.starts_with(&self.0)
+        }
+    }
+
+    #[test]
+    fn test_factory_context() {
+        let schema = json!({
+            "$id": "https://example.com/root",
+            "properties": {"a": {"format": "prefixed", "x-prefix": "id-"}}
+        });
+        let validator = ValidatorBuilder::default()
+            .draft(Draft::Draft07)
+            .validate_formats(true)
+            .format(
+                "prefixed",
+                |ctx: &mut CompileContext<'_, '_, Value>, _: &Value| -> BuildResult<BoxedFormat> {
+                    assert_eq!(ctx.draft(), Draft::Draft07);
+                    assert_eq!(ctx.base_uri(), "https://example.com/root");
+                    assert_eq!(ctx.keyword(), "format");
+                    assert_eq!(ctx.location().to_string(), "/properties/a/format");
+                    let prefix = ctx
+                        .parent()
+                        .get("x-prefix")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default();
+                    Ok(Box::new(Prefixed(prefix.to_string())))
+                },
+            )
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"a": "id-1"})));
+        assert!(!validator.is_valid(&json!({"a": "1"})));
+    }
+}
@@ -1,40 +1,1503 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
-use jsonlike::Json;
+use jpointer::{JsonPointer, JsonPointerNode};
+use jsonlike::prelude::*;
 
-use crate::{BoxedKeyword, BuildResult};
+use crate::{
+    error::ValidationErrorKind,
+    graph::{Graph, SchemaId},
+    snapshot::{Reader, Writer},
+    validation::deferred,
+    value::Value,
+    BuildError, BuildResult, ValidationError,
+};
+use custom::ErasedKeywordFactory;
+
+pub(crate) mod applicator;
+pub(crate) mod content;
+pub(crate) mod core;
+pub(crate) mod custom;
+pub(crate) mod format;
+pub(crate) mod unevaluated;
+pub(crate) mod validation;
+
+pub use custom::{
+    AsyncKeyword, CompileContext, Keyword, KeywordContext, KeywordFactory, SchemaRef,
+};
+pub use validation::PrimitiveType;
+
+/// Vocabularies defined by the JSON Schema specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vocabulary {
+    Core,
+    Applicator,
+    Unevaluated,
+    Validation,
+    MetaData,
+    FormatAnnotation,
+    FormatAssertion,
+    Content,
+}
+
+impl Vocabulary {
+    pub(crate) fn from_uri(uri: &str) -> Option<Vocabulary> {
+        match uri {
+            "https://json-schema.org/draft/2020-12/vocab/core"
+            | "https://json-schema.org/draft/2019-09/vocab/core" => Some(Vocabulary::Core),
+            "https://json-schema.org/draft/2020-12/vocab/applicator"
+            | "https://json-schema.org/draft/2019-09/vocab/applicator" => {
+                Some(Vocabulary::Applicator)
+            }
+            "https://json-schema.org/draft/2020-12/vocab/unevaluated" => {
+                Some(Vocabulary::Unevaluated)
+            }
+            "https://json-schema.org/draft/2020-12/vocab/validation"
+            | "https://json-schema.org/draft/2019-09/vocab/validation" => {
+                Some(Vocabulary::Validation)
+            }
+            "https://json-schema.org/draft/2020-12/vocab/meta-data"
+            | "https://json-schema.org/draft/2019-09/vocab/meta-data" => Some(Vocabulary::MetaData),
+            "https://json-schema.org/draft/2020-12/vocab/format-annotation"
+            | "https://json-schema.org/draft/2019-09/vocab/format" => {
+                Some(Vocabulary::FormatAnnotation)
+            }
+            "https://json-schema.org/draft/2020-12/vocab/format-assertion" => {
+                Some(Vocabulary::FormatAssertion)
+            }
+            "https://json-schema.org/draft/2020-12/vocab/content"
+            | "https://json-schema.org/draft/2019-09/vocab/content" => Some(Vocabulary::Content),
+            _ => None,
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// A set of enabled built-in vocabularies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct VocabularySet(u8);
+
+impl VocabularySet {
+    pub(crate) const fn empty() -> VocabularySet {
+        VocabularySet(0)
+    }
+    pub(crate) fn with(mut self, vocabulary: Vocabulary) -> VocabularySet {
+        self.insert(vocabulary);
+        self
+    }
+    pub(crate) fn insert(&mut self, vocabulary: Vocabulary) {
+        self.0 |= vocabulary.bit();
+    }
+    pub(crate) fn contains(&self, vocabulary: Vocabulary) -> bool {
+        self.0 & vocabulary.bit() != 0
+    }
+}
+
+/// A bundle of custom keywords enabled by meta-schemas that list its URI in `$vocabulary`.
+pub struct CustomVocabulary<'a, J: Json> {
+    pub(crate) keywords: HashMap<String, Arc<dyn ErasedKeywordFactory<'a, J>>>,
+}
+
+impl<'a, J: Json> Default for CustomVocabulary<'a, J> {
+    fn default() -> Self {
+        CustomVocabulary {
+            keywords: HashMap::default(),
+        }
+    }
+}
+
+impl<'a, J: Json> CustomVocabulary<'a, J> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn keyword<F, M>(mut self, name: impl Into<String>, factory: F) -> Self
+    where
+        F: KeywordFactory<'a, J, M>,
+        M: 'a,
+    {
+        self.keywords
+            .insert(name.into(), Arc::new(custom::Erased::new(factory)));
+        self
+    }
+}
+
+pub(crate) type SchemaGraph<J> = Graph<KeywordValue<J>>;
+/// A compiled schema, [`SchemaId::TRUE`] for schemas without any keywords.
+pub(crate) type Subschema = SchemaId;
+
+/// A value attached to an instance location by a keyword.
+#[derive(Debug, Clone)]
+pub(crate) struct Annotation {
+    pub(crate) keyword: Box<str>,
+    pub(crate) value: Value,
+    pub(crate) instance_location: JsonPointer,
+    pub(crate) evaluation_path: JsonPointer,
+}
+
+impl Annotation {
+    pub(crate) fn new(
+        keyword: impl Into<Box<str>>,
+        value: Value,
+        instance_location: &JsonPointerNode,
+        evaluation_path: &JsonPointerNode,
+    ) -> Annotation {
+        Annotation {
+            keyword: keyword.into(),
+            value,
+            instance_location: instance_location.into(),
+            evaluation_path: evaluation_path.into(),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub(crate) enum KeywordValue<J: Json> {
-    Type(Type),
-    Custom(Arc<dyn Keyword<J>>),
+    FalseSchema,
+    Ref(core::Ref),
+    AllOf(applicator::AllOf),
+    AnyOf(applicator::AnyOf),
+    OneOf(applicator::OneOf),
+    Not(applicator::Not),
+    IfThenElse(applicator::IfThenElse),
+    Properties(applicator::Properties),
+    PatternProperties(applicator::PatternProperties),
+    AdditionalProperties(applicator::AdditionalProperties),
+    PropertyNames(applicator::PropertyNames),
+    PrefixItems(applicator::PrefixItems),
+    Items(applicator::Items),
+    Contains(applicator::Contains),
+    DependentSchemas(applicator::DependentSchemas),
+    Dependencies(applicator::Dependencies),
+    Type(validation::Type),
+    Const(validation::Const),
+    Enum(validation::Enum),
+    MultipleOf(validation::MultipleOf),
+    Limit(validation::Limit),
+    SizeLimit(validation::SizeLimit),
+    Pattern(validation::Pattern),
+    UniqueItems(validation::UniqueItems),
+    Required(validation::Required),
+    DependentRequired(validation::DependentRequired),
+    Format(format::Format),
+    Content(content::Content<J>),
+    UnevaluatedProperties(unevaluated::UnevaluatedProperties),
+    UnevaluatedItems(unevaluated::UnevaluatedItems),
+    Shared(core::Shared<J>),
+    Custom(CustomKeyword<J>),
+    AsyncCustom(AsyncCustomKeyword),
 }
 
 #[derive(Debug, Clone)]
-pub struct Type {}
+pub(crate) struct CustomKeyword<J: Json> {
+    pub(crate) name: Box<str>,
+    pub(crate) keyword: Arc<dyn Keyword<J>>,
+}
 
-pub trait Keyword<J: Json>: Send + Sync + core::fmt::Debug {
-    fn is_valid(&self, instance: &J) -> bool;
+/// A custom keyword checked only during asynchronous validation.
+#[derive(Debug, Clone)]
+pub(crate) struct AsyncCustomKeyword {
+    pub(crate) name: Box<str>,
+    /// Index of the check in the validator.
+    pub(crate) index: usize,
 }
 
-mod sealed {
-    pub trait Sealed<J> {}
+impl<J: Json> KeywordValue<J> {
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            KeywordValue::FalseSchema | KeywordValue::Shared(_) => "",
+            KeywordValue::Ref(keyword) => keyword.keyword,
+            KeywordValue::AllOf(_) => "allOf",
+            KeywordValue::AnyOf(_) => "anyOf",
+            KeywordValue::OneOf(_) => "oneOf",
+            KeywordValue::Not(_) => "not",
+            KeywordValue::IfThenElse(_) => "if",
+            KeywordValue::Properties(_) => "properties",
+            KeywordValue::PatternProperties(_) => "patternProperties",
+            KeywordValue::AdditionalProperties(_) => "additionalProperties",
+            KeywordValue::PropertyNames(_) => "propertyNames",
+            KeywordValue::PrefixItems(keyword) => keyword.keyword,
+            KeywordValue::Items(keyword) => keyword.keyword,
+            KeywordValue::Contains(_) => "contains",
+            KeywordValue::DependentSchemas(_) => "dependentSchemas",
+            KeywordValue::Dependencies(_) => "dependencies",
+            KeywordValue::Type(_) => "type",
+            KeywordValue::Const(_) => "const",
+            KeywordValue::Enum(_) => "enum",
+            KeywordValue::MultipleOf(_) => "multipleOf",
+            KeywordValue::Limit(keyword) => keyword.name(),
+            KeywordValue::SizeLimit(keyword) => keyword.name(),
+            KeywordValue::Pattern(_) => "pattern",
+            KeywordValue::UniqueItems(_) => "uniqueItems",
+            KeywordValue::Required(_) => "required",
+            KeywordValue::DependentRequired(_) => "dependentRequired",
+            KeywordValue::Format(_) => "format",
+            KeywordValue::Content(keyword) => keyword.name(),
+            KeywordValue::UnevaluatedProperties(_) => "unevaluatedProperties",
+            KeywordValue::UnevaluatedItems(_) => "unevaluatedItems",
+            KeywordValue::Custom(keyword) => &keyword.name,
+            KeywordValue::AsyncCustom(keyword) => &keyword.name,
+        }
+    }
+
+    /// Relative cost of checking this keyword, siblings are evaluated from cheapest to the most
+    /// expensive so `is_valid` fails as early as possible.
+    pub(crate) fn cost(&self) -> u8 {
+        match self {
+            KeywordValue::FalseSchema
+            | KeywordValue::Type(_)
+            | KeywordValue::Const(_)
+            | KeywordValue::Enum(_)
+            | KeywordValue::MultipleOf(_)
+            | KeywordValue::Limit(_)
+            | KeywordValue::SizeLimit(_)
+            | KeywordValue::Required(_)
+            | KeywordValue::DependentRequired(_) => 0,
+            KeywordValue::Format(_) => 1,
+            // Depend on the size of the instance and their subschemas
+            KeywordValue::Properties(_)
+            | KeywordValue::AdditionalProperties(_)
+            | KeywordValue::PropertyNames(_)
+            | KeywordValue::PrefixItems(_)
+            | KeywordValue::Items(_)
+            | KeywordValue::DependentSchemas(_)
+            | KeywordValue::Dependencies(_)
+            | KeywordValue::Custom(_) => 2,
+            KeywordValue::Pattern(_)
+            | KeywordValue::PatternProperties(_)
+            | KeywordValue::UniqueItems(_)
+            | KeywordValue::Ref(_)
+            | KeywordValue::Shared(_)
+            | KeywordValue::AllOf(_)
+            | KeywordValue::AnyOf(_)
+            | KeywordValue::OneOf(_)
+            | KeywordValue::Not(_)
+            | KeywordValue::IfThenElse(_)
+            | KeywordValue::Contains(_)
+            | KeywordValue::Content(_) => 3,
+            // Evaluate their siblings once more
+            KeywordValue::UnevaluatedProperties(_) | KeywordValue::UnevaluatedItems(_) => 4,
+            // Not reaching them saves awaiting them in asynchronous validation
+            KeywordValue::AsyncCustom(_) => 5,
+        }
+    }
+
+    /// Write the keyword, failing for custom keywords & formats as their state is opaque.
+    pub(crate) fn encode(&self, writer: &mut Writer) -> BuildResult<()> {
+        match self {
+            KeywordValue::FalseSchema => writer.u8(0),
+            KeywordValue::Ref(keyword) => {
+                writer.u8(1);
+                keyword.encode(writer);
+            }
+            KeywordValue::AllOf(keyword) => {
+                writer.u8(2);
+                keyword.encode(writer);
+            }
+            KeywordValue::AnyOf(keyword) => {
+                writer.u8(3);
+                keyword.encode(writer);
+            }
+            KeywordValue::OneOf(keyword) => {
+                writer.u8(4);
+                keyword.encode(writer);
+            }
+            KeywordValue::Not(keyword) => {
+                writer.u8(5);
+                keyword.encode(writer);
+            }
+            KeywordValue::IfThenElse(keyword) => {
+                writer.u8(6);
+                keyword.encode(writer);
+            }
+            KeywordValue::Properties(keyword) => {
+                writer.u8(7);
+                keyword.encode(writer);
+            }
+            KeywordValue::PatternProperties(keyword) => {
+                writer.u8(8);
+                keyword.encode(writer);
+            }
+            KeywordValue::AdditionalProperties(keyword) => {
+                writer.u8(9);
+                keyword.encode(writer);
+            }
+            KeywordValue::PropertyNames(keyword) => {
+                writer.u8(10);
+                keyword.encode(writer);
+            }
+            KeywordValue::PrefixItems(keyword) => {
+                writer.u8(11);
+                keyword.encode(writer);
+            }
+            KeywordValue::Items(keyword) => {
+                writer.u8(12);
+                keyword.encode(writer);
+            }
+            KeywordValue::Contains(keyword) => {
+                writer.u8(13);
+                keyword.encode(writer);
+            }
+            KeywordValue::DependentSchemas(keyword) => {
+                writer.u8(14);
+                keyword.encode(writer);
+            }
+            KeywordValue::Dependencies(keyword) => {
+                writer.u8(15);
+                keyword.encode(writer);
+            }
+            KeywordValue::Type(keyword) => {
+                writer.u8(16);
+                keyword.encode(writer);
+            }
+            KeywordValue::Const(keyword) => {
+                writer.u8(17);
+                keyword.encode(writer);
+            }
+            KeywordValue::Enum(keyword) => {
+                writer.u8(18);
+                keyword.encode(writer);
+            }
+            KeywordValue::MultipleOf(keyword) => {
+                writer.u8(19);
+                keyword.encode(writer);
+            }
+            KeywordValue::Limit(keyword) => {
+                writer.u8(20);
+                keyword.encode(writer);
+            }
+            KeywordValue::SizeLimit(keyword) => {
+                writer.u8(21);
+                keyword.encode(writer);
+            }
+            KeywordValue::Pattern(keyword) => {
+                writer.u8(22);
+                keyword.encode(writer);
+            }
+            KeywordValue::UniqueItems(_) => writer.u8(23),
+            KeywordValue::Required(keyword) => {
+                writer.u8(24);
+                keyword.encode(writer);
+            }
+            KeywordValue::DependentRequired(keyword) => {
+                writer.u8(25);
+                keyword.encode(writer);
+            }
+            KeywordValue::Format(keyword) => {
+                writer.u8(26);
+                keyword.encode(writer)?;
+            }
+            KeywordValue::Content(keyword) => {
+                writer.u8(27);
+                keyword.encode(writer)?;
+            }
+            KeywordValue::UnevaluatedProperties(keyword) => {
+                writer.u8(28);
+                keyword.encode(writer);
+            }
+            KeywordValue::UnevaluatedItems(keyword) => {
+                writer.u8(29);
+                keyword.encode(writer);
+            }
+            KeywordValue::Shared(keyword) => {
+                writer.u8(30);
+                keyword.encode(writer)?;
+            }
+            KeywordValue::Custom(CustomKeyword { name, .. })
+            | KeywordValue::AsyncCustom(AsyncCustomKeyword { name, .. }) => {
+                return Err(BuildError::not_serializable(name.as_ref()));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<KeywordValue<J>> {
+        Ok(match reader.u8()? {
+            0 => KeywordValue::FalseSchema,
+            1 => KeywordValue::Ref(core::Ref::decode(reader)?),
+            2 => KeywordValue::AllOf(applicator::AllOf::decode(reader)?),
+            3 => KeywordValue::AnyOf(applicator::AnyOf::decode(reader)?),
+            4 => KeywordValue::OneOf(applicator::OneOf::decode(reader)?),
+            5 => KeywordValue::Not(applicator::Not::decode(reader)?),
+            6 => KeywordValue::IfThenElse(applicator::IfThenElse::decode(reader)?),
+            7 => KeywordValue::Properties(applicator::Properties::decode(reader)?),
+            8 => KeywordValue::PatternProperties(applicator::PatternProperties::decode(reader)?),
+            9 => KeywordValue::AdditionalProperties(applicator::AdditionalProperties::decode(
+                reader,
+            )?),
+            10 => KeywordValue::PropertyNames(applicator::PropertyNames::decode(reader)?),
+            11 => KeywordValue::PrefixItems(applicator::PrefixItems::decode(reader)?),
+            12 => KeywordValue::Items(applicator::Items::decode(reader)?),
+            13 => KeywordValue::Contains(applicator::Contains::decode(reader)?),
+            14 => KeywordValue::DependentSchemas(applicator::DependentSchemas::decode(reader)?),
+            15 => KeywordValue::Dependencies(applicator::Dependencies::decode(reader)?),
+            16 => KeywordValue::Type(validation::Type::decode(reader)?),
+            17 => KeywordValue::Const(validation::Const::decode(reader)?),
+            18 => KeywordValue::Enum(validation::Enum::decode(reader)?),
+            19 => KeywordValue::MultipleOf(validation::MultipleOf::decode(reader)?),
+            20 => KeywordValue::Limit(validation::Limit::decode(reader)?),
+            21 => KeywordValue::SizeLimit(validation::SizeLimit::decode(reader)?),
+            22 => KeywordValue::Pattern(validation::Pattern::decode(reader)?),
+            23 => KeywordValue::UniqueItems(validation::UniqueItems),
+            24 => KeywordValue::Required(validation::Required::decode(reader)?),
+            25 => KeywordValue::DependentRequired(validation::DependentRequired::decode(reader)?),
+            26 => KeywordValue::Format(format::Format::decode(reader)?),
+            27 => KeywordValue::Content(content::Content::decode(reader)?),
+            28 => KeywordValue::UnevaluatedProperties(unevaluated::UnevaluatedProperties::decode(
+                reader,
+            )?),
+            29 => KeywordValue::UnevaluatedItems(unevaluated::UnevaluatedItems::decode(reader)?),
+            30 => KeywordValue::Shared(core::Shared::decode(reader)?),
+            _ => return Err(BuildError::invalid_snapshot()),
+        })
+    }
+
+    pub(crate) fn is_valid(&self, graph: &SchemaGraph<J>, schema: SchemaId, instance: &J) -> bool {
+        match self {
+            KeywordValue::FalseSchema => false,
+            KeywordValue::Ref(keyword) => keyword.is_valid(graph, instance),
+            KeywordValue::Shared(keyword) => keyword.is_valid(instance),
+            KeywordValue::AllOf(keyword) => keyword.is_valid(graph, instance),
+            KeywordValue::AnyOf(keyword) => keyword.is_valid(graph, instance),
+            KeywordValue::OneOf(keyword) => keyword.is_valid(graph, instance),
+            KeywordValue::Not(keyword) => keyword.is_valid(graph, instance),
+            KeywordValue::IfThenElse(keyword) => keyword.is_valid(graph, instance),
+            KeywordValue::Properties(keyword) => keyword.is_valid(graph, instance),
+            KeywordValue::PatternProperties(keyword) => keyword.is_valid(graph, instance),
+            KeywordValue::AdditionalProperties(keyword) => keyword.is_valid(graph, instance),
+            KeywordValue::PropertyNames(keyword) => keyword.is_valid(graph, instance),
+            KeywordValue::PrefixItems(keyword) => keyword.is_valid(graph, instance),
+            KeywordValue::Items(keyword) => keyword.is_valid(graph, instance),
+            KeywordValue::Contains(keyword) => keyword.is_valid(graph, instance),
+            KeywordValue::DependentSchemas(keyword) => keyword.is_valid(graph, instance),
+            KeywordValue::Dependencies(keyword) => keyword.is_valid(graph, instance),
+            KeywordValue::Type(keyword) => keyword.is_valid(instance),
+            KeywordValue::Const(keyword) => keyword.is_valid(instance),
+            KeywordValue::Enum(keyword) => keyword.is_valid(instance),
+            KeywordValue::MultipleOf(keyword) => keyword.is_valid(instance),
+            KeywordValue::Limit(keyword) => keyword.is_valid(instance),
+            KeywordValue::SizeLimit(keyword) => keyword.is_valid(instance),
+            KeywordValue::Pattern(keyword) => keyword.is_valid(instance),
+            KeywordValue::UniqueItems(keyword) => keyword.is_valid(instance),
+            KeywordValue::Required(keyword) => keyword.is_valid(instance),
+            KeywordValue::DependentRequired(keyword) => keyword.is_valid(instance),
+            KeywordValue::Format(keyword) => keyword.is_valid(instance),
+            KeywordValue::Content(keyword) => keyword.is_valid(graph, instance),
+            KeywordValue::UnevaluatedProperties(keyword) => {
+                keyword.is_valid(graph, schema, instance)
+            }
+            KeywordValue::UnevaluatedItems(keyword) => keyword.is_valid(graph, schema, instance),
+            KeywordValue::Custom(keyword) => {
+                // Paths are not tracked here, errors of the context are only returned by `validate`
+                let path = JsonPointerNode::new();
+                let ctx = KeywordContext::new(graph, &keyword.name, &path);
+                keyword.keyword.is_valid(&ctx, instance)
+            }
+            KeywordValue::AsyncCustom(keyword) => deferred::is_valid(keyword.index, instance),
+        }
+    }
+
+    pub(crate) fn validate(
+        &self,
+        graph: &SchemaGraph<J>,
+        schema: SchemaId,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if let KeywordValue::IfThenElse(keyword) = self {
+            // `then` & `else` are located next to `if`
+            return keyword.validate(graph, instance, location, path, errors);
+        }
+        if let KeywordValue::Content(keyword) = self {
+            // Errors are located at the specific content keyword
+            return keyword.validate(graph, instance, location, path, errors);
+        }
+        if let KeywordValue::Shared(keyword) = self {
+            return keyword.validate(instance, location, path, errors);
+        }
+        if let KeywordValue::FalseSchema = self {
+            return errors.push(ValidationError::new(
+                ValidationErrorKind::FalseSchema,
+                location,
+                path,
+            ));
+        }
+        let path = &path.push(self.name());
+        match self {
+            KeywordValue::FalseSchema
+            | KeywordValue::IfThenElse(_)
+            | KeywordValue::Content(_)
+            | KeywordValue::Shared(_) => unreachable!(),
+            KeywordValue::Ref(keyword) => keyword.validate(graph, instance, location, path, errors),
+            KeywordValue::AllOf(keyword) => {
+                keyword.validate(graph, instance, location, path, errors)
+            }
+            KeywordValue::AnyOf(keyword) => {
+                keyword.validate(graph, instance, location, path, errors)
+            }
+            KeywordValue::OneOf(keyword) => {
+                keyword.validate(graph, instance, location, path, errors)
+            }
+            KeywordValue::Not(keyword) => keyword.validate(graph, instance, location, path, errors),
+            KeywordValue::Properties(keyword) => {
+                keyword.validate(graph, instance, location, path, errors)
+            }
+            KeywordValue::PatternProperties(keyword) => {
+                keyword.validate(graph, instance, location, path, errors)
+            }
+            KeywordValue::AdditionalProperties(keyword) => {
+                keyword.validate(graph, instance, location, path, errors)
+            }
+            KeywordValue::PropertyNames(keyword) => {
+                keyword.validate(graph, instance, location, path, errors)
+            }
+            KeywordValue::PrefixItems(keyword) => {
+                keyword.validate(graph, instance, location, path, errors)
+            }
+            KeywordValue::Items(keyword) => {
+                keyword.validate(graph, instance, location, path, errors)
+            }
+            KeywordValue::Contains(keyword) => {
+                keyword.validate(graph, instance, location, path, errors)
+            }
+            KeywordValue::DependentSchemas(keyword) => {
+                keyword.validate(graph, instance, location, path, errors)
+            }
+            KeywordValue::Dependencies(keyword) => {
+                keyword.validate(graph, instance, location, path, errors)
+            }
+            KeywordValue::Type(keyword) => keyword.validate(instance, location, path, errors),
+            KeywordValue::Const(keyword) => keyword.validate(instance, location, path, errors),
+            KeywordValue::Enum(keyword) => keyword.validate(instance, location, path, errors),
+            KeywordValue::MultipleOf(keyword) => keyword.validate(instance, location, path, errors),
+            KeywordValue::Limit(keyword) => keyword.validate(instance, location, path, errors),
+            KeywordValue::SizeLimit(keyword) => keyword.validate(instance, location, path, errors),
+            KeywordValue::Pattern(keyword) => keyword.validate(instance, location, path, errors),
+            KeywordValue::UniqueItems(keyword) => {
+                keyword.validate(instance, location, path, errors)
+            }
+            KeywordValue::Required(keyword) => keyword.validate(instance, location, path, errors),
+            KeywordValue::DependentRequired(keyword) => {
+                keyword.validate(instance, location, path, errors)
+            }
+            KeywordValue::Format(keyword) => keyword.validate(instance, location, path, errors),
+            KeywordValue::UnevaluatedProperties(keyword) => {
+                keyword.validate(graph, schema, instance, location, path, errors)
+            }
+            KeywordValue::UnevaluatedItems(keyword) => {
+                keyword.validate(graph, schema, instance, location, path, errors)
+            }
+            KeywordValue::Custom(keyword) => {
+                let ctx = KeywordContext::new(graph, &keyword.name, path);
+                errors.extend(keyword.keyword.iter_errors(&ctx, instance, location));
+            }
+            KeywordValue::AsyncCustom(keyword) => {
+                if !deferred::is_valid(keyword.index, instance) {
+                    let kind = ValidationErrorKind::Custom {
+                        keyword: keyword.name.to_string(),
+                    };
+                    errors.push(ValidationError::new(kind, location, path));
+                }
+            }
+        }
+    }
+
+    /// Collect annotations produced by this keyword and its subschemas.
+    pub(crate) fn annotate(
+        &self,
+        graph: &SchemaGraph<J>,
+        schema: SchemaId,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        annotations: &mut Vec<Annotation>,
+    ) {
+        if let KeywordValue::IfThenElse(keyword) = self {
+            return keyword.annotate(graph, instance, location, path, annotations);
+        }
+        if let KeywordValue::Content(keyword) = self {
+            return keyword.annotate(location, path, annotations);
+        }
+        if let KeywordValue::Shared(keyword) = self {
+            return keyword.annotate(instance, location, path, annotations);
+        }
+        let path = &path.push(self.name());
+        match self {
+            KeywordValue::Ref(keyword) => {
+                keyword.annotate(graph, instance, location, path, annotations)
+            }
+            KeywordValue::AllOf(keyword) => applicator::annotate_each(
+                graph,
+                &keyword.schemas,
+                instance,
+                location,
+                path,
+                annotations,
+            ),
+            KeywordValue::AnyOf(keyword) => applicator::annotate_each(
+                graph,
+                &keyword.schemas,
+                instance,
+                location,
+                path,
+                annotations,
+            ),
+            KeywordValue::OneOf(keyword) => applicator::annotate_each(
+                graph,
+                &keyword.schemas,
+                instance,
+                location,
+                path,
+                annotations,
+            ),
+            KeywordValue::Properties(keyword) => {
+                keyword.annotate(graph, instance, location, path, annotations)
+            }
+            KeywordValue::PatternProperties(keyword) => {
+                keyword.annotate(graph, instance, location, path, annotations)
+            }
+            KeywordValue::AdditionalProperties(keyword) => {
+                keyword.annotate(graph, instance, location, path, annotations)
+            }
+            KeywordValue::PrefixItems(keyword) => {
+                keyword.annotate(graph, instance, location, path, annotations)
+            }
+            KeywordValue::Items(keyword) => {
+                keyword.annotate(graph, instance, location, path, annotations)
+            }
+            KeywordValue::Contains(keyword) => {
+                keyword.annotate(graph, instance, location, path, annotations)
+            }
+            KeywordValue::DependentSchemas(keyword) => {
+                keyword.annotate(graph, instance, location, path, annotations)
+            }
+            KeywordValue::Dependencies(keyword) => {
+                keyword
+                    .schemas
+                    .annotate(graph, instance, location, path, annotations)
+            }
+            KeywordValue::Format(keyword) => keyword.annotate(location, path, annotations),
+            KeywordValue::UnevaluatedProperties(keyword) => {
+                keyword.annotate(graph, schema, instance, location, path, annotations)
+            }
+            KeywordValue::UnevaluatedItems(keyword) => {
+                keyword.annotate(graph, schema, instance, location, path, annotations)
+            }
+            KeywordValue::Custom(keyword) => {
+                let ctx = KeywordContext::new(graph, &keyword.name, path);
+                if let Some(value) = keyword.keyword.annotate(&ctx, instance) {
+                    annotations.push(Annotation::new(
+                        &*keyword.name,
+                        Value::from_json(&value),
+                        location,
+                        path,
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Collect object properties evaluated by this keyword for `unevaluatedProperties`.
+    pub(crate) fn evaluated_properties<'i>(
+        &self,
+        graph: &SchemaGraph<J>,
+        schema: SchemaId,
+        instance: &'i J,
+        evaluated: &mut HashSet<&'i str>,
+    ) {
+        match self {
+            KeywordValue::Ref(keyword) => keyword.evaluated_properties(graph, instance, evaluated),
+            KeywordValue::Shared(keyword) => keyword.evaluated_properties(instance, evaluated),
+            KeywordValue::AllOf(keyword) => {
+                keyword.evaluated_properties(graph, instance, evaluated)
+            }
+            KeywordValue::AnyOf(keyword) => {
+                keyword.evaluated_properties(graph, instance, evaluated)
+            }
+            KeywordValue::OneOf(keyword) => {
+                keyword.evaluated_properties(graph, instance, evaluated)
+            }
+            KeywordValue::IfThenElse(keyword) => {
+                keyword.evaluated_properties(graph, instance, evaluated)
+            }
+            KeywordValue::Properties(keyword) => keyword.evaluated_properties(instance, evaluated),
+            KeywordValue::PatternProperties(keyword) => {
+                keyword.evaluated_properties(instance, evaluated)
+            }
+            KeywordValue::AdditionalProperties(keyword) => {
+                keyword.evaluated_properties(instance, evaluated)
+            }
+            KeywordValue::DependentSchemas(keyword) => {
+                keyword.evaluated_properties(graph, instance, evaluated)
+            }
+            KeywordValue::Dependencies(keyword) => keyword
+                .schemas
+                .evaluated_properties(graph, instance, evaluated),
+            KeywordValue::UnevaluatedProperties(keyword)
+                if keyword.is_valid(graph, schema, instance) =>
+            {
+                if let Some(object) = instance.as_object() {
+                    evaluated.extend(
+                        object
+                            .iter()
+                            .filter_map(|(key, _)| key.ok())
+                            .map(AsRef::as_ref),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Mark array items evaluated by this keyword for `unevaluatedItems`.
+    pub(crate) fn evaluated_items(
+        &self,
+        graph: &SchemaGraph<J>,
+        schema: SchemaId,
+        instance: &J,
+        evaluated: &mut [bool],
+    ) {
+        match self {
+            KeywordValue::Ref(keyword) => keyword.evaluated_items(graph, instance, evaluated),
+            KeywordValue::Shared(keyword) => keyword.evaluated_items(instance, evaluated),
+            KeywordValue::AllOf(keyword) => keyword.evaluated_items(graph, instance, evaluated),
+            KeywordValue::AnyOf(keyword) => keyword.evaluated_items(graph, instance, evaluated),
+            KeywordValue::OneOf(keyword) => keyword.evaluated_items(graph, instance, evaluated),
+            KeywordValue::IfThenElse(keyword) => {
+                keyword.evaluated_items(graph, instance, evaluated)
+            }
+            KeywordValue::PrefixItems(keyword) => keyword.evaluated_items(evaluated),
+            KeywordValue::Items(keyword) => keyword.evaluated_items(evaluated),
+            KeywordValue::Contains(keyword) => keyword.evaluated_items(graph, instance, evaluated),
+            KeywordValue::UnevaluatedItems(keyword)
+                if keyword.is_valid(graph, schema, instance) =>
+            {
+                evaluated.fill(true);
+            }
+            _ => {}
+        }
+    }
 }
 
-pub trait KeywordFactory<'a, J: Json>: Send + Sync + sealed::Sealed<J> + 'a {
-    fn init(&self, schema: &'a J) -> BuildResult<BoxedKeyword<J>>;
+/// Write all schemas of the graph with their keywords.
+pub(crate) fn encode_graph<J: Json>(
+    graph: &SchemaGraph<J>,
+    writer: &mut Writer,
+) -> BuildResult<()> {
+    writer.usize(graph.len());
+    for id in 0..graph.len() {
+        let keywords = graph.keywords(SchemaId::new(id as u32));
+        writer.usize(keywords.len());
+        for keyword in keywords {
+            keyword.encode(writer)?;
+        }
+    }
+    Ok(())
 }
 
-impl<'a, F, J: Json + 'a> sealed::Sealed<J> for F where
-    F: Fn(&'a J) -> BuildResult<BoxedKeyword<J>> + Send + Sync + 'a
-{
+pub(crate) fn decode_graph<J: Json>(reader: &mut Reader) -> BuildResult<SchemaGraph<J>> {
+    let schemas = reader.len()?;
+    if schemas < 2 {
+        return Err(BuildError::invalid_snapshot());
+    }
+    // Ids are local to the graph, the enclosing one is restored afterwards
+    let outer = reader.set_schemas(schemas);
+    let mut graph = Graph::new();
+    for id in 0..schemas {
+        // The first two slots are reserved by the graph itself
+        let id = if id < 2 {
+            SchemaId::new(id as u32)
+        } else {
+            graph.reserve_schema()
+        };
+        let keywords = (0..reader.len()?)
+            .map(|_| KeywordValue::decode(reader))
+            .collect::<BuildResult<Vec<_>>>()?;
+        graph.set_schema(id, keywords);
+    }
+    reader.set_schemas(outer);
+    Ok(graph)
 }
 
-impl<'a, F, J: Json + 'a> KeywordFactory<'a, J> for F
-where
-    F: Fn(&'a J) -> BuildResult<BoxedKeyword<J>> + Send + Sync + 'a,
-{
-    fn init(&self, schema: &'a J) -> BuildResult<BoxedKeyword<J>> {
-        self(schema)
+/// Check whether the instance is valid against the compiled schema.
+pub(crate) fn is_valid<J: Json>(graph: &SchemaGraph<J>, schema: Subschema, instance: &J) -> bool {
+    graph
+        .keywords(schema)
+        .iter()
+        .all(|keyword| keyword.is_valid(graph, schema, instance))
+}
+
+/// Collect all errors of the instance against the compiled schema.
+pub(crate) fn validate<J: Json>(
+    graph: &SchemaGraph<J>,
+    schema: Subschema,
+    instance: &J,
+    location: &JsonPointerNode,
+    path: &JsonPointerNode,
+    errors: &mut Vec<ValidationError>,
+) {
+    for keyword in graph.keywords(schema) {
+        keyword.validate(graph, schema, instance, location, path, errors);
+    }
+}
+
+/// Collect annotations of the instance, dropping the ones from failed schemas.
+pub(crate) fn annotate<J: Json>(
+    graph: &SchemaGraph<J>,
+    schema: Subschema,
+    instance: &J,
+    location: &JsonPointerNode,
+    path: &JsonPointerNode,
+    annotations: &mut Vec<Annotation>,
+) {
+    if !is_valid(graph, schema, instance) {
+        return;
+    }
+    for keyword in graph.keywords(schema) {
+        keyword.annotate(graph, schema, instance, location, path, annotations);
+    }
+}
+
+pub(crate) fn evaluated_properties<'i, J: Json>(
+    graph: &SchemaGraph<J>,
+    schema: Subschema,
+    instance: &'i J,
+    evaluated: &mut HashSet<&'i str>,
+) {
+    for keyword in graph.keywords(schema) {
+        keyword.evaluated_properties(graph, schema, instance, evaluated);
+    }
+}
+
+pub(crate) fn evaluated_items<J: Json>(
+    graph: &SchemaGraph<J>,
+    schema: Subschema,
+    instance: &J,
+    evaluated: &mut [bool],
+) {
+    for keyword in graph.keywords(schema) {
+        keyword.evaluated_items(graph, schema, instance, evaluated);
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use super::{CustomVocabulary, KeywordValue};
+    use crate::{
+        blocking::ValidatorBuilder, BoxedKeyword, BuildResult, CompileContext, Draft,
+        ErrorIterator, Keyword, KeywordContext, SchemaRef,
+    };
+    use jpointer::JsonPointerNode;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    #[test_case(&json!({"type": "integer"}), &json!(1), true)]
+    #[test_case(&json!({"type": "integer"}), &json!(1.0), true)]
+    #[test_case(&json!({"type": "integer"}), &json!("a"), false)]
+    #[test_case(&json!({"minimum": 5}), &json!(4), false)]
+    #[test_case(&json!({"const": [1, {"a": 2}]}), &json!([1.0, {"a": 2}]), true)]
+    #[test_case(&json!({"enum": [1, "a"]}), &json!("b"), false)]
+    #[test_case(&json!({"required": ["a"], "properties": {"a": {"type": "string"}}}), &json!({"a": 1}), false)]
+    #[test_case(&json!({"additionalProperties": false, "patternProperties": {"^x-": true}}), &json!({"x-a": 1}), true)]
+    #[test_case(&json!({"additionalProperties": false, "patternProperties": {"^x-": true}}), &json!({"a": 1}), false)]
+    #[test_case(&json!({"prefixItems": [{"type": "integer"}], "items": false}), &json!([1]), true)]
+    #[test_case(&json!({"prefixItems": [{"type": "integer"}], "items": false}), &json!([1, 2]), false)]
+    #[test_case(&json!({"contains": {"const": 1}, "maxContains": 1}), &json!([1, 1]), false)]
+    #[test_case(&json!({"oneOf": [{"minimum": 1}, {"minimum": 2}]}), &json!(3), false)]
+    #[test_case(&json!({"if": {"type": "string"}, "then": {"minLength": 2}}), &json!("a"), false)]
+    #[test_case(&json!({"$defs": {"a": {"type": "integer"}}, "$ref": "#/$defs/a"}), &json!("a"), false)]
+    #[test_case(&json!({"properties": {"a": {"$ref": "#"}}, "type": "object"}), &json!({"a": {"a": 1}}), false)]
+    #[test_case(&json!({"allOf": [{"properties": {"a": true}}], "unevaluatedProperties": false}), &json!({"a": 1}), true)]
+    #[test_case(&json!({"allOf": [{"properties": {"a": true}}], "unevaluatedProperties": false}), &json!({"b": 1}), false)]
+    fn test_keywords(schema: &Value, instance: &Value, expected: bool) {
+        let validator = ValidatorBuilder::default()
+            .build(schema)
+            .expect("Invalid schema");
+        assert_eq!(validator.is_valid(instance), expected);
+        assert_eq!(validator.validate(instance).is_ok(), expected);
+    }
+
+    #[test]
+    fn test_cheap_keywords_first() {
+        let schema = json!({
+            "$defs": {"a": true},
+            "uniqueItems": true,
+            "unevaluatedProperties": false,
+            "pattern": "a",
+            "$ref": "#/$defs/a",
+            "type": "string",
+            "required": ["a"],
+        });
+        let validator = ValidatorBuilder::default()
+            .build(&schema)
+            .expect("Invalid schema");
+        let keywords: Vec<_> = validator
+            .graph()
+            .keywords(validator.root())
+            .iter()
+            .map(KeywordValue::name)
+            .collect();
+        assert_eq!(
+            keywords,
+            [
+                "required",
+                "type",
+                "$ref",
+                "pattern",
+                "uniqueItems",
+                "unevaluatedProperties"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ref_overrides_siblings() {
+        let schema =
+            json!({"definitions": {"a": true}, "$ref": "#/definitions/a", "type": "string"});
+        let validator = ValidatorBuilder::default()
+            .draft(Draft::Draft07)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!(1)));
+    }
+
+    #[test_case(&json!({"$defs": {"a": {"type": "integer"}}, "$dynamicRef": "#/$defs/a"}), &json!("a"), false)]
+    #[test_case(&json!({"$dynamicAnchor": "node", "type": "object", "properties": {"next": {"$dynamicRef": "#node"}}}), &json!({"next": {"next": 1}}), false)]
+    #[test_case(&json!({"$id": "https://example.com/strict", "$dynamicAnchor": "node", "$ref": "tree", "unevaluatedProperties": false, "$defs": {"tree": {"$id": "tree", "$dynamicAnchor": "node", "type": "object", "properties": {"data": true, "children": {"items": {"$dynamicRef": "#node"}}}}}}), &json!({"children": [{"data": 1}]}), true)]
+    #[test_case(&json!({"$id": "https://example.com/strict", "$dynamicAnchor": "node", "$ref": "tree", "unevaluatedProperties": false, "$defs": {"tree": {"$id": "tree", "$dynamicAnchor": "node", "type": "object", "properties": {"data": true, "children": {"items": {"$dynamicRef": "#node"}}}}}}), &json!({"children": [{"daat": 1}]}), false)]
+    #[test_case(&json!({"$id": "https://example.com/root", "$dynamicAnchor": "foo", "properties": {"x": {"$dynamicRef": "item#foo"}}, "$defs": {"item": {"$id": "item", "$anchor": "foo", "type": "integer"}}}), &json!({"x": "a"}), false)]
+    fn test_dynamic_ref(schema: &Value, instance: &Value, expected: bool) {
+        let validator = ValidatorBuilder::default()
+            .build(schema)
+            .expect("Invalid schema");
+        assert_eq!(validator.is_valid(instance), expected);
+        assert_eq!(validator.validate(instance).is_ok(), expected);
+    }
+
+    #[test]
+    fn test_dynamic_ref_depends_on_scope() {
+        let schema = json!({
+            "$ref": "https://example.com/a",
+            "$defs": {
+                "a": {"$id": "https://example.com/a", "$dynamicAnchor": "x", "$ref": "b"},
+                "b": {"$id": "https://example.com/b", "$dynamicAnchor": "x", "items": {"$dynamicRef": "#x"}},
+            },
+        });
+        let error = ValidatorBuilder::default()
+            .build(&schema)
+            .expect_err("Unsupported schema");
+        assert_eq!(
+            error.to_string(),
+            "'$dynamicRef' is not supported: its target depends on the dynamic scope"
+        );
+    }
+
+    /// The schema of the `recursiveRef.json` suite cases, with the given `$recursiveAnchor`s.
+    fn recursive_schema(outer: Option<bool>, inner: Option<bool>) -> Value {
+        let mut schema = json!({
+            "$id": "http://localhost:4242/recursiveRef/schema.json",
+            "$defs": {
+                "myobject": {
+                    "$id": "myobject.json",
+                    "anyOf": [
+                        {"type": "string"},
+                        {"type": "object", "additionalProperties": {"$recursiveRef": "#"}}
+                    ]
+                }
+            },
+            "anyOf": [{"type": "integer"}, {"$ref": "#/$defs/myobject"}]
+        });
+        if let Some(outer) = outer {
+            schema["$recursiveAnchor"] = json!(outer);
+        }
+        if let Some(inner) = inner {
+            schema["$defs"]["myobject"]["$recursiveAnchor"] = json!(inner);
+        }
+        schema
+    }
+
+    #[test_case(&json!({"properties": {"foo": {"$recursiveRef": "#"}}, "additionalProperties": false}), &json!({"foo": {"foo": false}}), true; "without anchor, match")]
+    #[test_case(&json!({"properties": {"foo": {"$recursiveRef": "#"}}, "additionalProperties": false}), &json!({"foo": {"bar": false}}), false; "without anchor, mismatch")]
+    #[test_case(&recursive_schema(None, Some(true)), &json!({"foo": "hi"}), true; "without nesting, match")]
+    #[test_case(&recursive_schema(None, Some(true)), &json!({"foo": {"bar": 1}}), false; "without nesting, mismatch")]
+    #[test_case(&recursive_schema(Some(true), Some(true)), &json!({"foo": {"bar": 1}}), true; "with nesting, integer")]
+    #[test_case(&recursive_schema(Some(true), Some(true)), &json!({"foo": {"bar": true}}), false; "with nesting, mismatch")]
+    #[test_case(&recursive_schema(Some(false), Some(false)), &json!({"foo": {"bar": 1}}), false; "anchor false")]
+    #[test_case(&recursive_schema(None, Some(false)), &json!({"foo": {"bar": 1}}), false; "no outer anchor, inner false")]
+    #[test_case(&recursive_schema(Some(true), None), &json!({"foo": {"bar": 1}}), false; "no anchor in initial target")]
+    #[test_case(&json!({"$id": "http://localhost:4242/strict-tree.json", "$recursiveAnchor": true, "$ref": "tree.json", "unevaluatedProperties": false, "$defs": {"tree": {"$id": "tree.json", "$recursiveAnchor": true, "type": "object", "properties": {"data": true, "children": {"type": "array", "items": {"$recursiveRef": "#"}}}}}}), &json!({"children": [{"daat": 1}]}), false; "strict tree, unknown property")]
+    #[test_case(&json!({"$id": "http://localhost:4242/strict-tree.json", "$recursiveAnchor": true, "$ref": "tree.json", "unevaluatedProperties": false, "$defs": {"tree": {"$id": "tree.json", "$recursiveAnchor": true, "type": "object", "properties": {"data": true, "children": {"type": "array", "items": {"$recursiveRef": "#"}}}}}}), &json!({"children": [{"data": 1}]}), true; "strict tree, known property")]
+    fn test_recursive_ref(schema: &Value, instance: &Value, expected: bool) {
+        let validator = ValidatorBuilder::default()
+            .draft(Draft::Draft201909)
+            .build(schema)
+            .expect("Invalid schema");
+        assert_eq!(validator.is_valid(instance), expected);
+        assert_eq!(validator.validate(instance).is_ok(), expected);
+    }
+
+    #[test]
+    fn test_recursive_ref_depends_on_scope() {
+        let schema = json!({
+            "$id": "http://localhost:4242/main.json",
+            "$defs": {
+                "inner": {
+                    "$id": "inner.json",
+                    "$recursiveAnchor": true,
+                    "additionalProperties": {"$recursiveRef": "#"}
+                }
+            },
+            "if": {"propertyNames": {"pattern": "^[a-m]"}},
+            "then": {"$id": "any.json", "$recursiveAnchor": true, "$ref": "inner.json"},
+            "else": {"$id": "integer.json", "$recursiveAnchor": true, "type": ["object", "integer"], "$ref": "inner.json"}
+        });
+        let error = ValidatorBuilder::default()
+            .draft(Draft::Draft201909)
+            .build(&schema)
+            .expect_err("Unsupported schema");
+        assert_eq!(
+            error.to_string(),
+            "'$recursiveRef' is not supported: its target depends on the dynamic scope"
+        );
+    }
+
+    #[test]
+    fn test_error_locations() {
+        let schema = json!({"properties": {"a": {"items": {"type": "string"}}}});
+        let validator = ValidatorBuilder::default()
+            .build(&schema)
+            .expect("Invalid schema");
+        let instance = json!({"a": ["x", 1]});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.instance_location().to_string(), "/a/1");
+        assert_eq!(
+            error.evaluation_path().to_string(),
+            "/properties/a/items/type"
+        );
+    }
+
+    const META_SCHEMA: &str = "https://example.com/meta";
+    const CUSTOM_VOCABULARY: &str = "https://example.com/vocab/even";
+
+    fn meta_schema(vocabularies: Value) -> Value {
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": META_SCHEMA,
+            "$vocabulary": vocabularies,
+        })
+    }
+
+    #[derive(Debug)]
+    struct Even;
+
+    impl Keyword<Value> for Even {
+        fn is_valid(&self, _: &KeywordContext<'_, Value>, instance: &Value) -> bool {
+            instance.as_u64().is_none_or(|value| value % 2 == 0)
+        }
+    }
+
+    fn even_factory(_: &Value) -> BuildResult<BoxedKeyword<Value>> {
+        Ok(Box::new(Even))
+    }
+
+    #[test]
+    fn test_vocabulary_disabled_by_meta_schema() {
+        let schema = json!({"$schema": META_SCHEMA, "minimum": 5, "properties": {"a": false}});
+        let validator = ValidatorBuilder::default()
+            .meta_schema(
+                META_SCHEMA,
+                meta_schema(json!({
+                    "https://json-schema.org/draft/2020-12/vocab/core": true,
+                    "https://json-schema.org/draft/2020-12/vocab/applicator": true,
+                })),
+            )
+            .build(&schema)
+            .expect("Invalid schema");
+        // `minimum` belongs to the validation vocabulary which is not enabled
+        assert!(validator.is_valid(&json!(1)));
+        assert!(!validator.is_valid(&json!({"a": 1})));
+    }
+
+    #[test]
+    fn test_meta_schema_within_schema() {
+        let schema = json!({
+            "$schema": META_SCHEMA,
+            "minimum": 5,
+            "$defs": {
+                "meta": meta_schema(json!({
+                    "https://json-schema.org/draft/2020-12/vocab/core": true,
+                    "https://json-schema.org/draft/2020-12/vocab/applicator": true,
+                })),
+            },
+        });
+        let validator = ValidatorBuilder::default()
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!(1)));
+    }
+
+    #[test_case(Draft::Draft04)]
+    #[test_case(Draft::Draft07)]
+    fn test_vocabularies_before_2019_09(draft: Draft) {
+        let schema = json!({"$schema": META_SCHEMA, "minimum": 5});
+        let validator = ValidatorBuilder::default()
+            .draft(draft)
+            .meta_schema(
+                META_SCHEMA,
+                meta_schema(json!({"https://example.com/vocab/unknown": true})),
+            )
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!(1)));
+    }
+
+    #[test_case("https://json-schema.org/draft/2019-09/schema")]
+    #[test_case("https://json-schema.org/draft/2020-12/schema#")]
+    fn test_official_meta_schema(uri: &str) {
+        let schema = json!({"$schema": uri, "minimum": 5, "unevaluatedProperties": false});
+        let validator = ValidatorBuilder::default()
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!(1)));
+        assert!(!validator.is_valid(&json!({"a": 1})));
+    }
+
+    #[test]
+    fn test_custom_vocabulary() {
+        let schema = json!({"$schema": META_SCHEMA, "even": true});
+        let validator = ValidatorBuilder::default()
+            .meta_schema(
+                META_SCHEMA,
+                meta_schema(json!({
+                    "https://json-schema.org/draft/2020-12/vocab/core": true,
+                    CUSTOM_VOCABULARY: true,
+                })),
+            )
+            .vocabulary(
+                CUSTOM_VOCABULARY,
+                CustomVocabulary::new().keyword("even", even_factory),
+            )
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!(2)));
+        assert!(!validator.is_valid(&json!(3)));
+    }
+
+    #[test]
+    fn test_custom_vocabulary_not_enabled() {
+        let schema = json!({"even": true});
+        let validator = ValidatorBuilder::default()
+            .vocabulary(
+                CUSTOM_VOCABULARY,
+                CustomVocabulary::new().keyword("even", even_factory),
+            )
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!(3)));
+    }
+
+    #[test_case(true, true)]
+    #[test_case(false, false)]
+    fn test_unknown_vocabulary(required: bool, is_err: bool) {
+        let schema = json!({"$schema": META_SCHEMA});
+        let result = ValidatorBuilder::default()
+            .meta_schema(
+                META_SCHEMA,
+                meta_schema(json!({"https://example.com/vocab/unknown": required})),
+            )
+            .build(&schema);
+        assert_eq!(result.is_err(), is_err);
+        if let Err(error) = result {
+            assert_eq!(
+                error.to_string(),
+                "Unknown required vocabulary: 'https://example.com/vocab/unknown'"
+            );
+        }
+    }
+
+    /// Applies a subschema to every value of an object.
+    #[derive(Debug)]
+    struct EachValue {
+        schema: SchemaRef,
+    }
+
+    impl Keyword<Value> for EachValue {
+        fn is_valid(&self, ctx: &KeywordContext<'_, Value>, instance: &Value) -> bool {
+            instance.as_object().is_none_or(|object| {
+                object
+                    .values()
+                    .all(|value| ctx.is_valid(self.schema, value))
+            })
+        }
+        fn iter_errors<'k>(
+            &'k self,
+            ctx: &'k KeywordContext<'k, Value>,
+            instance: &'k Value,
+            location: &JsonPointerNode,
+        ) -> ErrorIterator<'k> {
+            let mut errors = Vec::new();
+            if let Some(object) = instance.as_object() {
+                for (key, value) in object {
+                    let location = location.push(key.as_str());
+                    if !ctx.is_valid(self.schema, value) {
+                        errors.push(ctx.error(&location, format!("'{key}' is not allowed")));
+                    }
+                    errors.extend(ctx.iter_errors(self.schema, value, &location));
+                }
+            }
+            Box::new(errors.into_iter())
+        }
+        fn annotate(&self, _: &KeywordContext<'_, Value>, instance: &Value) -> Option<Value> {
+            instance.as_object().map(|object| json!(object.len()))
+        }
+    }
+
+    fn each_value_factory<'a>(
+        ctx: &mut CompileContext<'a, '_, Value>,
+        schema: &'a Value,
+    ) -> BuildResult<BoxedKeyword<Value>> {
+        let schema = match schema.as_str() {
+            Some(reference) => ctx.resolve(reference)?,
+            None => ctx.compile(schema)?,
+        };
+        Ok(Box::new(EachValue { schema }))
+    }
+
+    fn each_value_validator(schema: &Value) -> crate::Validator<Value> {
+        ValidatorBuilder::default()
+            .meta_schema(
+                META_SCHEMA,
+                meta_schema(json!({
+                    "https://json-schema.org/draft/2020-12/vocab/core": true,
+                    "https://json-schema.org/draft/2020-12/vocab/validation": true,
+                    CUSTOM_VOCABULARY: true,
+                })),
+            )
+            .vocabulary(
+                CUSTOM_VOCABULARY,
+                CustomVocabulary::new().keyword("eachValue", each_value_factory),
+            )
+            .build(schema)
+            .expect("Invalid schema")
+    }
+
+    #[test_case(&json!({"$schema": META_SCHEMA, "eachValue": {"type": "integer"}}), &json!({"a": 1}), true)]
+    #[test_case(&json!({"$schema": META_SCHEMA, "eachValue": {"type": "integer"}}), &json!({"a": "b"}), false)]
+    #[test_case(&json!({"$schema": META_SCHEMA, "$defs": {"a": {"minimum": 2}}, "eachValue": "#/$defs/a"}), &json!({"a": 2}), true)]
+    #[test_case(&json!({"$schema": META_SCHEMA, "$defs": {"a": {"minimum": 2}}, "eachValue": "#/$defs/a"}), &json!({"a": 1}), false)]
+    #[test_case(&json!({"$schema": META_SCHEMA, "eachValue": "#", "type": "object"}), &json!({"a": {"b": {}}}), true)]
+    #[test_case(&json!({"$schema": META_SCHEMA, "eachValue": "#", "type": "object"}), &json!({"a": {"b": 1}}), false)]
+    fn test_custom_keyword_subschemas(schema: &Value, instance: &Value, expected: bool) {
+        let validator = each_value_validator(schema);
+        assert_eq!(validator.is_valid(instance), expected);
+        assert_eq!(validator.validate(instance).is_ok(), expected);
+    }
+
+    #[test]
+    fn test_custom_keyword_errors() {
+        let schema = json!({"$schema": META_SCHEMA, "eachValue": {"type": "integer"}});
+        let validator = each_value_validator(&schema);
+        let instance = json!({"a": "b"});
+        let errors: Vec<_> = validator
+            .iter_errors(&instance)
+            .map(|error| {
+                (
+                    error.to_string(),
+                    error.instance_location().to_string(),
+                    error.evaluation_path().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    "'a' is not allowed".to_string(),
+                    "/a".to_string(),
+                    "/eachValue".to_string()
+                ),
+                (
+                    "Value is not of type 'integer'".to_string(),
+                    "/a".to_string(),
+                    "/eachValue/type".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_custom_keyword_default_errors() {
+        let schema = json!({"$schema": META_SCHEMA, "even": true});
+        let validator = ValidatorBuilder::default()
+            .meta_schema(
+                META_SCHEMA,
+                meta_schema(json!({
+                    "https://json-schema.org/draft/2020-12/vocab/core": true,
+                    CUSTOM_VOCABULARY: true,
+                })),
+            )
+            .vocabulary(
+                CUSTOM_VOCABULARY,
+                CustomVocabulary::new()
+                    .keyword("even", |_: &Value| -> BuildResult<BoxedKeyword<Value>> {
+                        Ok(Box::new(Even))
+                    }),
+            )
+            .build(&schema)
+            .expect("Invalid schema");
+        let error = validator.validate(&json!(3)).expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Value is not valid under the 'even' keyword"
+        );
+        assert_eq!(error.evaluation_path().to_string(), "/even");
+    }
+
+    #[test]
+    fn test_custom_keyword_annotations() {
+        let schema = json!({"$schema": META_SCHEMA, "eachValue": true});
+        let validator = each_value_validator(&schema);
+        let instance = json!({"a": 1, "b": 2});
+        let output = validator.evaluate(&instance).list();
+        let annotations: Vec<_> = output
+            .nested
+            .iter()
+            .filter_map(|unit| unit.annotations.as_ref())
+            .collect();
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].get("eachValue"), Some(&json!(2)));
+    }
+
+    #[test]
+    fn test_keyword_factory_context() {
+        let schema = json!({
+            "$schema": META_SCHEMA,
+            "$defs": {"a": {"even": true, "x-note": "sibling"}},
+            "$ref": "#/$defs/a",
+        });
+        let validator = ValidatorBuilder::default()
+            .meta_schema(
+                META_SCHEMA,
+                meta_schema(json!({
+                    "https://json-schema.org/draft/2020-12/vocab/core": true,
+                    CUSTOM_VOCABULARY: true,
+                })),
+            )
+            .vocabulary(
+                CUSTOM_VOCABULARY,
+                CustomVocabulary::new().keyword(
+                    "even",
+                    |ctx: &mut CompileContext<'_, '_, Value>,
+                     _: &Value|
+                     -> BuildResult<BoxedKeyword<Value>> {
+                        assert_eq!(ctx.draft(), Draft::Draft202012);
+                        assert_eq!(ctx.keyword(), "even");
+                        assert_eq!(ctx.location().to_string(), "/$defs/a/even");
+                        assert_eq!(ctx.parent().get("x-note"), Some(&json!("sibling")));
+                        Ok(Box::new(Even))
+                    },
+                ),
+            )
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!(3)));
+    }
+
+    #[test_case(&json!(2), true)]
+    #[test_case(&json!(3), false)]
+    fn test_builder_keyword(instance: &Value, expected: bool) {
+        let schema = json!({"properties": {"a": {"even": true}}});
+        let validator = ValidatorBuilder::default()
+            .keyword("even", even_factory)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert_eq!(validator.is_valid(&json!({"a": instance})), expected);
+    }
+
+    /// `minLength` counting UTF-8 bytes instead of characters.
+    #[derive(Debug)]
+    struct MinBytes(usize);
+
+    impl Keyword<Value> for MinBytes {
+        fn is_valid(&self, _: &KeywordContext<'_, Value>, instance: &Value) -> bool {
+            instance.as_str().is_none_or(|value| value.len() >= self.0)
+        }
+    }
+
+    fn min_bytes_factory(schema: &Value) -> BuildResult<BoxedKeyword<Value>> {
+        let limit = schema.as_u64().unwrap_or_default();
+        Ok(Box::new(MinBytes(limit as usize)))
+    }
+
+    #[test_case(Draft::Draft04)]
+    #[test_case(Draft::Draft07)]
+    #[test_case(Draft::Draft202012)]
+    fn test_builder_keyword_overrides_builtin(draft: Draft) {
+        let schema = json!({"minLength": 2});
+        let validator = ValidatorBuilder::default()
+            .draft(draft)
+            .keyword("minLength", min_bytes_factory)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("é")));
+        assert!(!validator.is_valid(&json!("e")));
+        let error = validator.validate(&json!("e")).expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Value is not valid under the 'minLength' keyword"
+        );
+    }
+
+    #[derive(Debug)]
+    struct Never;
+
+    impl Keyword<Value> for Never {
+        fn is_valid(&self, _: &KeywordContext<'_, Value>, _: &Value) -> bool {
+            false
+        }
+    }
+
+    #[test_case(Draft::Draft04)]
+    #[test_case(Draft::Draft07)]
+    fn test_builder_keyword_overrides_ref(draft: Draft) {
+        let schema = json!({"definitions": {"a": true}, "$ref": "#/definitions/a"});
+        let validator = ValidatorBuilder::default()
+            .draft(draft)
+            .keyword("$ref", |_: &Value| -> BuildResult<BoxedKeyword<Value>> {
+                Ok(Box::new(Never))
+            })
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!(1)));
+    }
+
+    #[test]
+    fn test_builder_keyword_overrides_vocabulary() {
+        let schema = json!({"$schema": META_SCHEMA, "even": true});
+        let validator = ValidatorBuilder::default()
+            .meta_schema(
+                META_SCHEMA,
+                meta_schema(json!({
+                    "https://json-schema.org/draft/2020-12/vocab/core": true,
+                    CUSTOM_VOCABULARY: true,
+                })),
+            )
+            .vocabulary(
+                CUSTOM_VOCABULARY,
+                CustomVocabulary::new().keyword("even", even_factory),
+            )
+            .keyword("even", |_: &Value| -> BuildResult<BoxedKeyword<Value>> {
+                Ok(Box::new(Never))
+            })
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!(2)));
     }
 }
@@ -0,0 +1,238 @@
+use std::collections::HashSet;
+
+use jpointer::JsonPointerNode;
+use jsonlike::prelude::*;
+
+use crate::{
+    error::ValidationErrorKind,
+    graph::SchemaId,
+    snapshot::{Reader, Writer},
+    vocabulary::{annotate, is_valid, validate, Annotation, KeywordValue, SchemaGraph, Subschema},
+    BuildResult, ValidationError,
+};
+
+/// Collect properties evaluated by the other keywords of the given schema.
+fn evaluated_by_siblings<'i, J: Json>(
+    graph: &SchemaGraph<J>,
+    schema: SchemaId,
+    instance: &'i J,
+) -> HashSet<&'i str> {
+    let mut evaluated = HashSet::new();
+    for keyword in graph.keywords(schema) {
+        if !matches!(keyword, KeywordValue::UnevaluatedProperties(_)) {
+            keyword.evaluated_properties(graph, schema, instance, &mut evaluated);
+        }
+    }
+    evaluated
+}
+
+/// Mark items evaluated by the other keywords of the given schema.
+fn evaluated_items_by_siblings<J: Json>(
+    graph: &SchemaGraph<J>,
+    schema: SchemaId,
+    instance: &J,
+    length: usize,
+) -> Vec<bool> {
+    let mut evaluated = vec![false; length];
+    for keyword in graph.keywords(schema) {
+        if !matches!(keyword, KeywordValue::UnevaluatedItems(_)) {
+            keyword.evaluated_items(graph, schema, instance, &mut evaluated);
+        }
+    }
+    evaluated
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct UnevaluatedProperties {
+    pub(crate) schema: Subschema,
+}
+
+impl UnevaluatedProperties {
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.schema(self.schema);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<UnevaluatedProperties> {
+        Ok(UnevaluatedProperties {
+            schema: reader.schema()?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        parent: SchemaId,
+        instance: &J,
+    ) -> bool {
+        let Some(object) = instance.as_object() else {
+            return true;
+        };
+        let evaluated = evaluated_by_siblings(graph, parent, instance);
+        object.iter().all(|(key, value)| {
+            key.is_ok_and(|key| {
+                evaluated.contains(key.as_ref()) || is_valid(graph, self.schema, value)
+            })
+        })
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        parent: SchemaId,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let Some(object) = instance.as_object() else {
+            return;
+        };
+        let evaluated = evaluated_by_siblings(graph, parent, instance);
+        let mut unexpected = Vec::new();
+        for (key, value) in object.iter() {
+            let Ok(key) = key else {
+                continue;
+            };
+            if !evaluated.contains(key.as_ref()) && !is_valid(graph, self.schema, value) {
+                if matches!(
+                    graph.keywords(self.schema).first(),
+                    Some(KeywordValue::FalseSchema)
+                ) {
+                    unexpected.push(key.as_ref().to_owned());
+                } else {
+                    validate(
+                        graph,
+                        self.schema,
+                        value,
+                        &location.push(key.as_ref()),
+                        path,
+                        errors,
+                    );
+                }
+            }
+        }
+        if !unexpected.is_empty() {
+            errors.push(ValidationError::new(
+                ValidationErrorKind::UnevaluatedProperties { unexpected },
+                location,
+                path,
+            ));
+        }
+    }
+    pub(crate) fn annotate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        parent: SchemaId,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        annotations: &mut Vec<Annotation>,
+    ) {
+        let Some(object) = instance.as_object() else {
+            return;
+        };
+        let evaluated = evaluated_by_siblings(graph, parent, instance);
+        for (key, value) in object.iter() {
+            let Ok(key) = key else {
+                continue;
+            };
+            if !evaluated.contains(key.as_ref()) {
+                let location = &location.push(key.as_ref());
+                annotate(graph, self.schema, value, location, path, annotations);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct UnevaluatedItems {
+    pub(crate) schema: Subschema,
+}
+
+impl UnevaluatedItems {
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.schema(self.schema);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<UnevaluatedItems> {
+        Ok(UnevaluatedItems {
+            schema: reader.schema()?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        parent: SchemaId,
+        instance: &J,
+    ) -> bool {
+        let Some(array) = instance.as_array() else {
+            return true;
+        };
+        let length = array.iter().count();
+        let evaluated = evaluated_items_by_siblings(graph, parent, instance, length);
+        array.iter().zip(evaluated).all(|(item, evaluated)| {
+            evaluated || item.is_ok_and(|item| is_valid(graph, self.schema, item))
+        })
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        parent: SchemaId,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let Some(array) = instance.as_array() else {
+            return;
+        };
+        let length = array.iter().count();
+        let evaluated = evaluated_items_by_siblings(graph, parent, instance, length);
+        let mut unexpected = Vec::new();
+        for (idx, (item, evaluated)) in array.iter().zip(evaluated).enumerate() {
+            let Ok(item) = item else {
+                continue;
+            };
+            if !evaluated && !is_valid(graph, self.schema, item) {
+                if matches!(
+                    graph.keywords(self.schema).first(),
+                    Some(KeywordValue::FalseSchema)
+                ) {
+                    unexpected.push(idx);
+                } else {
+                    validate(graph, self.schema, item, &location.push(idx), path, errors);
+                }
+            }
+        }
+        if !unexpected.is_empty() {
+            errors.push(ValidationError::new(
+                ValidationErrorKind::UnevaluatedItems { unexpected },
+                location,
+                path,
+            ));
+        }
+    }
+    pub(crate) fn annotate<J: Json>(
+        &self,
+        graph: &SchemaGraph<J>,
+        parent: SchemaId,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        annotations: &mut Vec<Annotation>,
+    ) {
+        let Some(array) = instance.as_array() else {
+            return;
+        };
+        let length = array.iter().count();
+        let evaluated = evaluated_items_by_siblings(graph, parent, instance, length);
+        for (idx, (item, evaluated)) in array.iter().zip(evaluated).enumerate() {
+            if let (Ok(item), false) = (item, evaluated) {
+                annotate(
+                    graph,
+                    self.schema,
+                    item,
+                    &location.push(idx),
+                    path,
+                    annotations,
+                );
+            }
+        }
+    }
+}
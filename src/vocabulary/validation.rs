@@ -0,0 +1,741 @@
+use core::fmt;
+
+use jpointer::JsonPointerNode;
+use jsonlike::prelude::*;
+
+use crate::{
+    compiler::Context,
+    error::ValidationErrorKind,
+    regex_engine::CompiledRegex,
+    snapshot::{Reader, Writer},
+    value::{equal, Value},
+    BuildError, BuildResult, ValidationError,
+};
+
+/// The basic JSON Schema types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimitiveType {
+    Array,
+    Boolean,
+    Integer,
+    Null,
+    Number,
+    Object,
+    String,
+}
+
+impl PrimitiveType {
+    const ALL: [PrimitiveType; 7] = [
+        PrimitiveType::Array,
+        PrimitiveType::Boolean,
+        PrimitiveType::Integer,
+        PrimitiveType::Null,
+        PrimitiveType::Number,
+        PrimitiveType::Object,
+        PrimitiveType::String,
+    ];
+
+    fn from_str(value: &str) -> Option<PrimitiveType> {
+        match value {
+            "array" => Some(PrimitiveType::Array),
+            "boolean" => Some(PrimitiveType::Boolean),
+            "integer" => Some(PrimitiveType::Integer),
+            "null" => Some(PrimitiveType::Null),
+            "number" => Some(PrimitiveType::Number),
+            "object" => Some(PrimitiveType::Object),
+            "string" => Some(PrimitiveType::String),
+            _ => None,
+        }
+    }
+
+    fn matches<J: Json>(self, instance: &J) -> bool {
+        match self {
+            PrimitiveType::Array => instance.is_array(),
+            PrimitiveType::Boolean => instance.is_boolean(),
+            PrimitiveType::Integer => instance
+                .as_number()
+                .and_then(JsonNumber::as_float)
+                .is_some_and(|number| number.fract() == 0.0),
+            PrimitiveType::Null => instance.is_null(),
+            PrimitiveType::Number => instance.is_number(),
+            PrimitiveType::Object => instance.is_object(),
+            PrimitiveType::String => instance.is_string(),
+        }
+    }
+}
+
+impl fmt::Display for PrimitiveType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PrimitiveType::Array => "array",
+            PrimitiveType::Boolean => "boolean",
+            PrimitiveType::Integer => "integer",
+            PrimitiveType::Null => "null",
+            PrimitiveType::Number => "number",
+            PrimitiveType::Object => "object",
+            PrimitiveType::String => "string",
+        })
+    }
+}
+
+pub(crate) fn as_f64<J: Json>(keyword: &str, value: &J) -> BuildResult<f64> {
+    value
+        .as_number()
+        .and_then(JsonNumber::as_float)
+        .ok_or_else(|| BuildError::invalid_keyword(keyword, "a number"))
+}
+
+pub(crate) fn as_u64<J: Json>(keyword: &str, value: &J) -> BuildResult<u64> {
+    value
+        .as_number()
+        .and_then(JsonNumber::as_float)
+        .filter(|number| *number >= 0.0 && number.fract() == 0.0)
+        .map(|number| number as u64)
+        .ok_or_else(|| BuildError::invalid_keyword(keyword, "a non-negative integer"))
+}
+
+pub(crate) fn as_string_list<J: Json>(keyword: &str, value: &J) -> BuildResult<Vec<Box<str>>> {
+    let array = value
+        .as_array()
+        .ok_or_else(|| BuildError::invalid_keyword(keyword, "an array of strings"))?;
+    array
+        .iter()
+        .map(|item| {
+            item?
+                .as_string()
+                .map(|item| item.as_ref().into())
+                .ok_or_else(|| BuildError::invalid_keyword(keyword, "an array of strings"))
+        })
+        .collect()
+}
+
+fn as_number<J: Json>(instance: &J) -> Option<f64> {
+    instance.as_number().and_then(JsonNumber::as_float)
+}
+
+fn error(
+    kind: ValidationErrorKind,
+    location: &JsonPointerNode,
+    path: &JsonPointerNode,
+    errors: &mut Vec<ValidationError>,
+) {
+    errors.push(ValidationError::new(kind, location, path));
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Type {
+    types: Vec<PrimitiveType>,
+}
+
+impl Type {
+    pub(crate) fn compile<J: Json>(value: &J) -> BuildResult<Type> {
+        let invalid = || BuildError::invalid_keyword("type", "a type name or an array of them");
+        let parse = |value: &J| {
+            value
+                .as_string()
+                .and_then(|name| PrimitiveType::from_str(name.as_ref()))
+                .ok_or_else(invalid)
+        };
+        let types = if let Some(array) = value.as_array() {
+            array
+                .iter()
+                .map(|item| parse(item?))
+                .collect::<BuildResult<_>>()?
+        } else {
+            vec![parse(value)?]
+        };
+        Ok(Type { types })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.usize(self.types.len());
+        for ty in &self.types {
+            writer.u8(*ty as u8);
+        }
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<Type> {
+        let types = (0..reader.len()?)
+            .map(|_| decode_variant(reader, &PrimitiveType::ALL))
+            .collect::<BuildResult<_>>()?;
+        Ok(Type { types })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, instance: &J) -> bool {
+        self.types.iter().any(|ty| ty.matches(instance))
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if !self.is_valid(instance) {
+            let kind = ValidationErrorKind::Type {
+                expected: self.types.clone(),
+            };
+            error(kind, location, path, errors);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Const {
+    value: Value,
+}
+
+impl Const {
+    pub(crate) fn compile<J: Json>(value: &J) -> Const {
+        Const {
+            value: Value::from_json(value),
+        }
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        self.value.encode(writer);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<Const> {
+        Ok(Const {
+            value: Value::decode(reader)?,
+        })
+    }
+    /// Whether the expected value is neither an array nor an object.
+    pub(crate) fn is_scalar(&self) -> bool {
+        self.value.is_scalar()
+    }
+    pub(crate) fn is_valid<J: Json>(&self, instance: &J) -> bool {
+        self.value.equals(instance)
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if !self.is_valid(instance) {
+            let kind = ValidationErrorKind::Const {
+                expected: self.value.to_string(),
+            };
+            error(kind, location, path, errors);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Enum {
+    options: Vec<Value>,
+}
+
+impl Enum {
+    pub(crate) fn compile<J: Json>(value: &J) -> BuildResult<Enum> {
+        let array = value
+            .as_array()
+            .ok_or_else(|| BuildError::invalid_keyword("enum", "an array"))?;
+        let options = array
+            .iter()
+            .map(|item| Ok(Value::from_json(item?)))
+            .collect::<BuildResult<_>>()?;
+        Ok(Enum { options })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.usize(self.options.len());
+        for option in &self.options {
+            option.encode(writer);
+        }
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<Enum> {
+        let options = (0..reader.len()?)
+            .map(|_| Value::decode(reader))
+            .collect::<BuildResult<_>>()?;
+        Ok(Enum { options })
+    }
+    /// Whether all options are neither arrays nor objects.
+    pub(crate) fn is_scalar(&self) -> bool {
+        self.options.iter().all(Value::is_scalar)
+    }
+    pub(crate) fn is_valid<J: Json>(&self, instance: &J) -> bool {
+        self.options.iter().any(|option| option.equals(instance))
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if !self.is_valid(instance) {
+            let kind = ValidationErrorKind::Enum {
+                options: Value::Array(self.options.clone()).to_string(),
+            };
+            error(kind, location, path, errors);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MultipleOf {
+    multiple_of: f64,
+}
+
+impl MultipleOf {
+    pub(crate) fn compile<J: Json>(value: &J) -> BuildResult<MultipleOf> {
+        let multiple_of = as_f64("multipleOf", value)?;
+        if multiple_of <= 0.0 {
+            return Err(BuildError::invalid_keyword(
+                "multipleOf",
+                "strictly greater than 0",
+            ));
+        }
+        Ok(MultipleOf { multiple_of })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.f64(self.multiple_of);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<MultipleOf> {
+        Ok(MultipleOf {
+            multiple_of: reader.f64()?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, instance: &J) -> bool {
+        let Some(number) = as_number(instance) else {
+            return true;
+        };
+        if self.multiple_of.fract() == 0.0 && number.fract() != 0.0 {
+            return false;
+        }
+        let remainder = (number / self.multiple_of) % 1.0;
+        if remainder.is_nan() {
+            // Overflow, e.g. a huge number divided by a small fraction
+            return false;
+        }
+        remainder < f64::EPSILON || (1.0 - remainder).abs() < f64::EPSILON
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if !self.is_valid(instance) {
+            let kind = ValidationErrorKind::MultipleOf {
+                multiple_of: self.multiple_of,
+            };
+            error(kind, location, path, errors);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Bound {
+    Minimum,
+    Maximum,
+    ExclusiveMinimum,
+    ExclusiveMaximum,
+}
+
+impl Bound {
+    const ALL: [Bound; 4] = [
+        Bound::Minimum,
+        Bound::Maximum,
+        Bound::ExclusiveMinimum,
+        Bound::ExclusiveMaximum,
+    ];
+}
+
+/// `minimum`, `maximum`, `exclusiveMinimum` & `exclusiveMaximum`.
+#[derive(Debug, Clone)]
+pub(crate) struct Limit {
+    bound: Bound,
+    limit: f64,
+}
+
+impl Limit {
+    pub(crate) fn new(bound: Bound, limit: f64) -> Limit {
+        Limit { bound, limit }
+    }
+    pub(crate) fn name(&self) -> &'static str {
+        match self.bound {
+            Bound::Minimum => "minimum",
+            Bound::Maximum => "maximum",
+            Bound::ExclusiveMinimum => "exclusiveMinimum",
+            Bound::ExclusiveMaximum => "exclusiveMaximum",
+        }
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.u8(self.bound as u8);
+        writer.f64(self.limit);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<Limit> {
+        Ok(Limit {
+            bound: decode_variant(reader, &Bound::ALL)?,
+            limit: reader.f64()?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, instance: &J) -> bool {
+        let Some(number) = as_number(instance) else {
+            return true;
+        };
+        match self.bound {
+            Bound::Minimum => number >= self.limit,
+            Bound::Maximum => number <= self.limit,
+            Bound::ExclusiveMinimum => number > self.limit,
+            Bound::ExclusiveMaximum => number < self.limit,
+        }
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if !self.is_valid(instance) {
+            let limit = self.limit;
+            let kind = match self.bound {
+                Bound::Minimum => ValidationErrorKind::Minimum { limit },
+                Bound::Maximum => ValidationErrorKind::Maximum { limit },
+                Bound::ExclusiveMinimum => ValidationErrorKind::ExclusiveMinimum { limit },
+                Bound::ExclusiveMaximum => ValidationErrorKind::ExclusiveMaximum { limit },
+            };
+            error(kind, location, path, errors);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Size {
+    MinLength,
+    MaxLength,
+    MinItems,
+    MaxItems,
+    MinProperties,
+    MaxProperties,
+}
+
+impl Size {
+    const ALL: [Size; 6] = [
+        Size::MinLength,
+        Size::MaxLength,
+        Size::MinItems,
+        Size::MaxItems,
+        Size::MinProperties,
+        Size::MaxProperties,
+    ];
+}
+
+/// Keywords that limit the size of strings, arrays or objects.
+#[derive(Debug, Clone)]
+pub(crate) struct SizeLimit {
+    size: Size,
+    limit: u64,
+}
+
+impl SizeLimit {
+    pub(crate) fn new(size: Size, limit: u64) -> SizeLimit {
+        SizeLimit { size, limit }
+    }
+    pub(crate) fn name(&self) -> &'static str {
+        match self.size {
+            Size::MinLength => "minLength",
+            Size::MaxLength => "maxLength",
+            Size::MinItems => "minItems",
+            Size::MaxItems => "maxItems",
+            Size::MinProperties => "minProperties",
+            Size::MaxProperties => "maxProperties",
+        }
+    }
+    fn actual<J: Json>(&self, instance: &J) -> Option<u64> {
+        let length = match self.size {
+            Size::MinLength | Size::MaxLength => instance.as_string()?.as_ref().chars().count(),
+            Size::MinItems | Size::MaxItems => instance.as_array()?.iter().count(),
+            Size::MinProperties | Size::MaxProperties => instance.as_object()?.iter().count(),
+        };
+        Some(length as u64)
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.u8(self.size as u8);
+        writer.u64(self.limit);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<SizeLimit> {
+        Ok(SizeLimit {
+            size: decode_variant(reader, &Size::ALL)?,
+            limit: reader.u64()?,
+        })
+    }
+    /// Whether the keyword limits the number of array items, otherwise strings or objects.
+    pub(crate) fn limits_items(&self) -> bool {
+        matches!(self.size, Size::MinItems | Size::MaxItems)
+    }
+    /// Whether the keyword limits the number of object properties.
+    pub(crate) fn limits_properties(&self) -> bool {
+        matches!(self.size, Size::MinProperties | Size::MaxProperties)
+    }
+    pub(crate) fn is_valid<J: Json>(&self, instance: &J) -> bool {
+        self.actual(instance)
+            .is_none_or(|actual| self.is_valid_size(actual))
+    }
+    /// Check a size counted by the caller.
+    pub(crate) fn is_valid_size(&self, actual: u64) -> bool {
+        match self.size {
+            Size::MinLength | Size::MinItems | Size::MinProperties => actual >= self.limit,
+            Size::MaxLength | Size::MaxItems | Size::MaxProperties => actual <= self.limit,
+        }
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if let Some(actual) = self.actual(instance) {
+            self.validate_size(actual, location, path, errors);
+        }
+    }
+    pub(crate) fn validate_size(
+        &self,
+        actual: u64,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if !self.is_valid_size(actual) {
+            let limit = self.limit;
+            let kind = match self.size {
+                Size::MinLength => ValidationErrorKind::MinLength { limit },
+                Size::MaxLength => ValidationErrorKind::MaxLength { limit },
+                Size::MinItems => ValidationErrorKind::MinItems { limit },
+                Size::MaxItems => ValidationErrorKind::MaxItems { limit },
+                Size::MinProperties => ValidationErrorKind::MinProperties { limit },
+                Size::MaxProperties => ValidationErrorKind::MaxProperties { limit },
+            };
+            error(kind, location, path, errors);
+        }
+    }
+}
+
+/// Read an index into one of the `ALL` arrays above.
+fn decode_variant<T: Copy>(reader: &mut Reader, all: &[T]) -> BuildResult<T> {
+    all.get(usize::from(reader.u8()?))
+        .copied()
+        .ok_or_else(BuildError::invalid_snapshot)
+}
+
+pub(crate) fn compile_regex<J: Json>(
+    ctx: &Context<'_, '_, J>,
+    pattern: &str,
+) -> BuildResult<CompiledRegex> {
+    CompiledRegex::new(ctx.builder.regex_engine.as_ref(), pattern)
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Pattern {
+    regex: CompiledRegex,
+}
+
+impl Pattern {
+    pub(crate) fn compile<J: Json>(ctx: &Context<'_, '_, J>, value: &J) -> BuildResult<Pattern> {
+        let pattern = value
+            .as_string()
+            .ok_or_else(|| BuildError::invalid_keyword("pattern", "a string"))?;
+        Ok(Pattern {
+            regex: compile_regex(ctx, pattern.as_ref())?,
+        })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        self.regex.encode(writer);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<Pattern> {
+        Ok(Pattern {
+            regex: CompiledRegex::decode(reader)?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, instance: &J) -> bool {
+        instance
+            .as_string()
+            .is_none_or(|string| self.regex.is_match(string.as_ref()))
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if !self.is_valid(instance) {
+            let kind = ValidationErrorKind::Pattern {
+                pattern: self.regex.as_str().to_owned(),
+            };
+            error(kind, location, path, errors);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct UniqueItems;
+
+impl UniqueItems {
+    pub(crate) fn is_valid<J: Json>(&self, instance: &J) -> bool {
+        let Some(array) = instance.as_array() else {
+            return true;
+        };
+        let items = array.iter().filter_map(Result::ok).collect::<Vec<_>>();
+        for (idx, left) in items.iter().enumerate() {
+            for right in &items[idx + 1..] {
+                if equal(*left, *right) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if !self.is_valid(instance) {
+            error(ValidationErrorKind::UniqueItems, location, path, errors);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Required {
+    properties: Vec<Box<str>>,
+}
+
+impl Required {
+    pub(crate) fn compile<J: Json>(value: &J) -> BuildResult<Required> {
+        Ok(Required {
+            properties: as_string_list("required", value)?,
+        })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.strings(&self.properties);
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<Required> {
+        Ok(Required {
+            properties: reader.strings()?,
+        })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, instance: &J) -> bool {
+        instance
+            .as_object()
+            .is_none_or(|object| self.is_valid_keys(|key| object.contains_key(key)))
+    }
+    /// Check an object by its keys only.
+    pub(crate) fn is_valid_keys(&self, contains: impl Fn(&str) -> bool) -> bool {
+        self.properties.iter().all(|property| contains(property))
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if let Some(object) = instance.as_object() {
+            self.validate_keys(|key| object.contains_key(key), location, path, errors);
+        }
+    }
+    pub(crate) fn validate_keys(
+        &self,
+        contains: impl Fn(&str) -> bool,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        for property in &self.properties {
+            if !contains(property) {
+                let kind = ValidationErrorKind::Required {
+                    property: property.to_string(),
+                };
+                error(kind, location, path, errors);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct DependentRequired {
+    dependencies: Vec<(Box<str>, Vec<Box<str>>)>,
+}
+
+impl DependentRequired {
+    pub(crate) fn new(dependencies: Vec<(Box<str>, Vec<Box<str>>)>) -> DependentRequired {
+        DependentRequired { dependencies }
+    }
+    pub(crate) fn compile<J: Json>(value: &J) -> BuildResult<DependentRequired> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| BuildError::invalid_keyword("dependentRequired", "an object"))?;
+        let dependencies = object
+            .iter()
+            .map(|(property, value)| {
+                Ok((
+                    property?.as_ref().into(),
+                    as_string_list("dependentRequired", value)?,
+                ))
+            })
+            .collect::<BuildResult<_>>()?;
+        Ok(DependentRequired { dependencies })
+    }
+    pub(crate) fn encode(&self, writer: &mut Writer) {
+        writer.usize(self.dependencies.len());
+        for (property, required) in &self.dependencies {
+            writer.str(property);
+            writer.strings(required);
+        }
+    }
+    pub(crate) fn decode(reader: &mut Reader) -> BuildResult<DependentRequired> {
+        let dependencies = (0..reader.len()?)
+            .map(|_| Ok((reader.str()?.into(), reader.strings()?)))
+            .collect::<BuildResult<_>>()?;
+        Ok(DependentRequired { dependencies })
+    }
+    pub(crate) fn is_valid<J: Json>(&self, instance: &J) -> bool {
+        instance
+            .as_object()
+            .is_none_or(|object| self.is_valid_keys(|key| object.contains_key(key)))
+    }
+    /// Check an object by its keys only.
+    pub(crate) fn is_valid_keys(&self, contains: impl Fn(&str) -> bool) -> bool {
+        self.dependencies.iter().all(|(property, required)| {
+            !contains(property) || required.iter().all(|name| contains(name))
+        })
+    }
+    pub(crate) fn validate<J: Json>(
+        &self,
+        instance: &J,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if let Some(object) = instance.as_object() {
+            self.validate_keys(|key| object.contains_key(key), location, path, errors);
+        }
+    }
+    pub(crate) fn validate_keys(
+        &self,
+        contains: impl Fn(&str) -> bool,
+        location: &JsonPointerNode,
+        path: &JsonPointerNode,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        for (property, required) in &self.dependencies {
+            if contains(property) {
+                for dependency in required {
+                    if !contains(dependency) {
+                        let kind = ValidationErrorKind::DependentRequired {
+                            property: property.to_string(),
+                            dependency: dependency.to_string(),
+                        };
+                        error(kind, location, path, errors);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Runs the JSON-Schema-Test-Suite from the `tests/suite` submodule.
+//!
+//! A test is generated for every file of the supported drafts and for their `optional/format`
+//! files, so nothing runs until the submodule is checked out. Other optional files are not run.
+#![cfg(feature = "serde_json")]
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use jsonschema::{blocking::ValidatorBuilder, Draft};
+use serde_json::Value;
+
+/// Cases that are expected to fail, as prefixes of
+/// `<draft>/<file>/<group description>/<test description>`.
+const KNOWN_FAILURES: &[&str] = &[
+    // Remote references are not retrieved
+    "draft4/refRemote.json",
+    "draft6/refRemote.json",
+    "draft7/refRemote.json",
+    "draft2019-09/refRemote.json",
+    "draft2020-12/refRemote.json",
+    // Meta-schemas are not bundled, so references to them can not be resolved
+    "draft4/definitions.json",
+    "draft6/definitions.json",
+    "draft7/definitions.json",
+    "draft2019-09/defs.json",
+    "draft2020-12/defs.json",
+    "draft4/ref.json/remote ref, containing refs itself",
+    "draft6/ref.json/remote ref, containing refs itself",
+    "draft7/ref.json/remote ref, containing refs itself",
+    "draft2019-09/ref.json/remote ref, containing refs itself",
+    "draft2020-12/ref.json/remote ref, containing refs itself",
+    "draft2019-09/id.json",
+    "draft2020-12/id.json",
+    "draft2019-09/vocabulary.json",
+    "draft2020-12/vocabulary.json",
+    // Remote references, and targets that depend on the dynamic scope
+    "draft2019-09/recursiveRef.json",
+    "draft2020-12/dynamicRef.json",
+];
+
+/// Cases of `optional/format` that are expected to fail, in the same form as `KNOWN_FAILURES`.
+const FORMAT_KNOWN_FAILURES: &[&str] = &[];
+
+/// Location of a suite file relative to the `tests` directory of the suite.
+fn location(path: &Path) -> String {
+    path.strip_prefix("tests/suite/tests")
+        .expect("Path is within the suite")
+        .iter()
+        .map(|component| component.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Run all test groups of a suite file and fail on unexpected results.
+///
+/// Known failures that pass fail too, so the list does not outlive the bugs.
+fn run(
+    path: &Path,
+    draft: Draft,
+    known_failures: &[&str],
+    configure: impl Fn(&mut ValidatorBuilder<'_, Value>),
+) {
+    let file = location(path);
+    let contents = fs::read_to_string(path)
+        .unwrap_or_else(|error| panic!("Failed to read {}: {error}", path.display()));
+    let groups: Vec<Value> = serde_json::from_str(&contents).expect("Invalid test file");
+    let mut failures = Vec::new();
+    let mut failed_known = Vec::new();
+    for group in &groups {
+        let group_description = group["description"].as_str().expect("Missing description");
+        let mut builder = ValidatorBuilder::default();
+        builder.draft(draft);
+        configure(&mut builder);
+        let validator = builder.build(&group["schema"]);
+        for test in group["tests"].as_array().expect("Missing tests") {
+            let description = test["description"].as_str().expect("Missing description");
+            let case = format!("{file}/{group_description}/{description}");
+            let expected = test["valid"].as_bool().expect("Missing validity");
+            let failure = match &validator {
+                Ok(validator) if validator.is_valid(&test["data"]) == expected => continue,
+                Ok(_) if expected => format!("{case}: expected to be valid"),
+                Ok(_) => format!("{case}: expected to be invalid"),
+                Err(error) => format!("{case}: {error}"),
+            };
+            match known_failures.iter().find(|known| case.starts_with(*known)) {
+                Some(known) => failed_known.push(*known),
+                None => failures.push(failure),
+            }
+        }
+    }
+    let passing: Vec<_> = known_failures
+        .iter()
+        .filter(|known| known.starts_with(&format!("{file}/")) || **known == file)
+        .filter(|known| !failed_known.contains(known))
+        .collect();
+    assert!(
+        failures.is_empty(),
+        "Unexpected failures:\n{}",
+        failures.join("\n")
+    );
+    assert!(passing.is_empty(), "Known failures that pass: {passing:?}");
+}
+
+#[suite::test("tests/suite/tests/draft4", pattern = "*.json")]
+fn draft4(path: PathBuf) {
+    run(&path, Draft::Draft04, KNOWN_FAILURES, |_| {});
+}
+
+#[suite::test("tests/suite/tests/draft6", pattern = "*.json")]
+fn draft6(path: PathBuf) {
+    run(&path, Draft::Draft06, KNOWN_FAILURES, |_| {});
+}
+
+#[suite::test("tests/suite/tests/draft7", pattern = "*.json")]
+fn draft7(path: PathBuf) {
+    run(&path, Draft::Draft07, KNOWN_FAILURES, |_| {});
+}
+
+#[suite::test("tests/suite/tests/draft2019-09", pattern = "*.json")]
+fn draft2019_09(path: PathBuf) {
+    run(&path, Draft::Draft201909, KNOWN_FAILURES, |_| {});
+}
+
+#[suite::test("tests/suite/tests/draft2020-12", pattern = "*.json")]
+fn draft2020_12(path: PathBuf) {
+    run(&path, Draft::Draft202012, KNOWN_FAILURES, |_| {});
+}
+
+fn assert_formats(builder: &mut ValidatorBuilder<'_, Value>) {
+    builder.validate_formats(true);
+}
+
+#[suite::test("tests/suite/tests/draft4", pattern = "optional/format/*.json")]
+fn format_draft4(path: PathBuf) {
+    run(&path, Draft::Draft04, FORMAT_KNOWN_FAILURES, assert_formats);
+}
+
+#[suite::test("tests/suite/tests/draft6", pattern = "optional/format/*.json")]
+fn format_draft6(path: PathBuf) {
+    run(&path, Draft::Draft06, FORMAT_KNOWN_FAILURES, assert_formats);
+}
+
+#[suite::test("tests/suite/tests/draft7", pattern = "optional/format/*.json")]
+fn format_draft7(path: PathBuf) {
+    run(&path, Draft::Draft07, FORMAT_KNOWN_FAILURES, assert_formats);
+}
+
+#[suite::test("tests/suite/tests/draft2019-09", pattern = "optional/format/*.json")]
+fn format_draft2019_09(path: PathBuf) {
+    run(
+        &path,
+        Draft::Draft201909,
+        FORMAT_KNOWN_FAILURES,
+        assert_formats,
+    );
+}
+
+#[suite::test("tests/suite/tests/draft2020-12", pattern = "optional/format/*.json")]
+fn format_draft2020_12(path: PathBuf) {
+    run(
+        &path,
+        Draft::Draft202012,
+        FORMAT_KNOWN_FAILURES,
+        assert_formats,
+    );
+}